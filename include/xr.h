@@ -0,0 +1,54 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+/*
+    C interface of the XR tokenizer, available when xr is built with the ffi feature:
+
+        cargo rustc --release --lib --features ffi --crate-type staticlib
+*/
+
+#ifndef XR_H
+#define XR_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Bytes allocated by xr, which must be released with xr_free. data is null on failure. */
+typedef struct XrBuffer {
+    uint8_t *data;
+    size_t len;
+} XrBuffer;
+
+/* Parses len bytes of UTF-8 text, returning its tokens and diagnostics as JSON. */
+XrBuffer xr_parse(const char *text, size_t len);
+
+void xr_free(XrBuffer buffer);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,152 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use std::path::{Path, PathBuf};
+
+use crate::{
+    arguments::BuildArgs,
+    formatter,
+    header::Header,
+    messages::Message,
+    mod_files::{self, RESERVED_STEMS},
+    sink::Outcome,
+    terminal_helper::TerminalOutput,
+};
+
+struct Entry {
+    module: Vec<String>,
+    file: String,
+    text: String,
+}
+
+// Path of the module wrapping the output of a file, mirroring its directory like the modules
+// declared by mod.rs files. The output of mod.xr (or lib.xr and main.xr) goes into the module
+// of its directory.
+fn module_path(root: Option<&Path>, file: &Path) -> Option<Vec<String>> {
+    let relative = root
+        .and_then(|root| file.strip_prefix(root).ok())
+        .unwrap_or(Path::new(file.file_name()?));
+
+    let mut names: Vec<String> = relative
+        .parent()
+        .into_iter()
+        .flat_map(Path::components)
+        .map(|c| c.as_os_str().to_str().map(str::to_string))
+        .collect::<Option<_>>()?;
+
+    let stem = file.file_stem()?.to_str()?;
+    if !RESERVED_STEMS.contains(&stem) {
+        names.push(stem.to_string());
+    }
+
+    names
+        .iter()
+        .all(|name| mod_files::is_valid_module_name(name))
+        .then_some(names)
+}
+
+fn render(mut entries: Vec<Entry>) -> String {
+    entries.sort_by(|a, b| a.module.cmp(&b.module));
+
+    let mut text = String::new();
+    let mut open: Vec<String> = Vec::new();
+
+    for e in entries {
+        let common = open
+            .iter()
+            .zip(&e.module)
+            .take_while(|(a, b)| a == b)
+            .count();
+
+        while open.len() > common {
+            open.pop();
+            text.push_str("}\n");
+        }
+
+        text.push_str(&format!("\n// ---- {} ----\n", e.file));
+        for name in &e.module[common..] {
+            text.push_str(&format!("pub mod {name} {{\n"));
+            open.push(name.clone());
+        }
+
+        text.push_str(&e.text);
+        formatter::ensure_final_newline(&mut text);
+    }
+
+    text.push_str(&"}\n".repeat(open.len()));
+    text
+}
+
+// Generates a single file with the output of all the files, each one wrapped in a module.
+pub fn build(
+    args: &BuildArgs,
+    path: &Path,
+    filenames: &[PathBuf],
+    header: &Header,
+    output: &mut TerminalOutput,
+) {
+    let mut entries: Vec<Entry> = Vec::new();
+    let filename = path.display().to_string();
+
+    crate::for_each_file(filenames, output, |file, contents, output| {
+        let Some(module) = module_path(args.input.root_of(file).as_deref(), file) else {
+            output.writeln_warning(Message::ModuleNotAmalgamated(file.display().to_string()));
+            return;
+        };
+
+        if entries.iter().any(|e| e.module == module) {
+            output.writeln_warning(Message::AmalgamatedModuleTaken(
+                file.display().to_string(),
+                module.join("::"),
+            ));
+            return;
+        }
+
+        let result = crate::parse_file(&contents, output);
+        crate::report_errors(&result, output);
+        if crate::is_rejected(file, &result, &args.generate, output) {
+            return;
+        }
+
+        let (text, _) = crate::generate_text(
+            &result,
+            &contents,
+            &Header::none(),
+            &args.generate,
+            &filename,
+            output,
+        );
+
+        entries.push(Entry {
+            module,
+            file: file.display().to_string(),
+            text,
+        });
+    });
+
+    let amalgamated = entries.len();
+    let text = header.apply(&render(entries));
+
+    if crate::write_generated(path, &text, &args.generate, output) == Some(Outcome::Written) {
+        output.writeln_success(Message::AmalgamationWritten(filename, amalgamated));
+    }
+}
@@ -0,0 +1,227 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use std::{collections::HashMap, fs, path::Path};
+
+use serde::Serialize;
+
+use crate::{
+    arguments::AnalyzeArgs,
+    duplicates::Duplicates,
+    messages::Message,
+    parser::{Sequence, Token},
+    terminal_helper::TerminalOutput,
+};
+
+// strict and reserved keywords of Rust 2021
+const KEYWORDS: [&str; 52] = [
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "if", "impl", "in",
+    "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "Self", "static", "struct", "super", "trait", "true", "try", "type",
+    "typeof", "unsafe", "unsized", "use", "virtual", "where", "while", "yield", "union",
+];
+
+pub fn is_keyword(word: &str) -> bool {
+    KEYWORDS.contains(&word)
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "kebab-case")]
+enum Kind {
+    Keyword,
+    Identifier,
+    Lifetime,
+    Literal,
+}
+
+impl Kind {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Keyword => "keyword",
+            Self::Identifier => "identifier",
+            Self::Lifetime => "lifetime",
+            Self::Literal => "literal",
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Entry<'a> {
+    kind: Kind,
+    text: &'a str,
+    count: usize,
+}
+
+// Occurrences of each identifier, keyword, lifetime and kind of literal, over all the files
+#[derive(Default)]
+struct Histogram {
+    counts: HashMap<(Kind, String), usize>,
+}
+
+impl Histogram {
+    fn add(&mut self, kind: Kind, text: &str) {
+        *self.counts.entry((kind, text.to_string())).or_default() += 1;
+    }
+
+    fn record(&mut self, tokens: &[Sequence<Token>]) {
+        let mut code = String::new();
+
+        for t in tokens {
+            // numbers are split in several tokens (as in 1.5e3), so words and numbers are taken
+            // from the runs of code between whitespace, literals and comments
+            if t.token == Token::Other {
+                code.push_str(t.text);
+                continue;
+            }
+            self.record_code(&code);
+            code.clear();
+
+            match t.token {
+                Token::StrLiteral => self.add(Kind::Literal, string_kind(t.text)),
+                Token::CharLiteral if t.text.starts_with('b') => self.add(Kind::Literal, "byte"),
+                Token::CharLiteral => self.add(Kind::Literal, "char"),
+                Token::Lifetime => self.add(Kind::Lifetime, t.text),
+                _ => {}
+            }
+        }
+        self.record_code(&code);
+    }
+
+    fn record_code(&mut self, code: &str) {
+        let mut chars = code.char_indices().peekable();
+
+        while let Some((start, c)) = chars.next() {
+            let word = c == '_' || c.is_alphanumeric();
+            if !word {
+                continue;
+            }
+
+            let mut end = start + c.len_utf8();
+            let mut float = false;
+            while let Some(&(i, next)) = chars.peek() {
+                let fraction = next == '.'
+                    && c.is_ascii_digit()
+                    && !float
+                    && code[i + 1..].starts_with(|c: char| c.is_ascii_digit());
+                if !(next == '_' || next.is_alphanumeric() || fraction) {
+                    break;
+                }
+                float |= fraction;
+                end = i + next.len_utf8();
+                chars.next();
+            }
+
+            let text = &code[start..end];
+            if c.is_ascii_digit() {
+                self.add(Kind::Literal, number_kind(text, float));
+            } else if is_keyword(text) {
+                self.add(Kind::Keyword, text);
+            } else {
+                self.add(Kind::Identifier, text);
+            }
+        }
+    }
+
+    // most frequent first
+    fn entries(&self) -> Vec<Entry<'_>> {
+        let mut entries: Vec<_> = self
+            .counts
+            .iter()
+            .map(|((kind, text), count)| Entry {
+                kind: *kind,
+                text,
+                count: *count,
+            })
+            .collect();
+        entries.sort_by(|a, b| (b.count, a.kind, a.text).cmp(&(a.count, b.kind, b.text)));
+        entries
+    }
+}
+
+fn string_kind(literal: &str) -> &'static str {
+    match literal.split(['"', '#']).next().unwrap_or_default() {
+        "r" => "raw string",
+        "b" => "byte string",
+        "br" => "raw byte string",
+        "c" => "c string",
+        "cr" => "raw c string",
+        _ => "string",
+    }
+}
+
+fn number_kind(number: &str, fraction: bool) -> &'static str {
+    let hex = number.starts_with("0x");
+    let exponent = !hex && number.contains(['e', 'E']);
+
+    if fraction || exponent || number.ends_with("f32") || number.ends_with("f64") {
+        "float"
+    } else {
+        "integer"
+    }
+}
+
+fn csv(entries: &[Entry]) -> String {
+    let mut text = String::from("kind,text,count\n");
+    for e in entries {
+        text.push_str(&format!("{},{},{}\n", e.kind.name(), e.text, e.count));
+    }
+    text
+}
+
+fn write_histogram(path: &Path, histogram: &Histogram, output: &mut TerminalOutput) {
+    let entries = histogram.entries();
+    let filename = path.display().to_string();
+
+    let text = match path.extension().is_some_and(|e| e == "json") {
+        true => serde_json::to_string_pretty(&entries).unwrap_or_default() + "\n",
+        false => csv(&entries),
+    };
+
+    match fs::write(path, text) {
+        Ok(_) => output.writeln_info(Message::HistogramWritten(filename, entries.len())),
+        Err(_) => output.writeln_error(Message::FileWriteFailed(filename)),
+    }
+}
+
+pub fn run(args: &AnalyzeArgs, output: &mut TerminalOutput) {
+    let filenames = args.input.files_to_process(output);
+    let mut histogram = Histogram::default();
+    let mut duplicates = Duplicates::default();
+
+    crate::for_each_file(&filenames, output, |file, contents, output| {
+        let result = crate::parse_file(&contents, output);
+        histogram.record(&result);
+        if args.duplicates {
+            duplicates.record(file.display().to_string(), &result);
+        }
+    });
+
+    if let Some(path) = &args.histogram {
+        write_histogram(path, &histogram, output);
+    }
+    if args.duplicates {
+        duplicates.report(args.min_tokens, output);
+    }
+
+    output.writeln_success(Message::FilesAnalyzed(filenames.len()));
+}
@@ -0,0 +1,193 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap},
+    fs::File,
+    io::{self, Read},
+    path::{Component, Path, PathBuf},
+};
+
+use flate2::read::GzDecoder;
+
+// separates the path of an archive from the path of an entry inside it, as in
+// bundle.tar.gz!src/parser.xr
+const SEPARATOR: &str = "!";
+
+enum Format {
+    Tar,
+    TarGz,
+    Zip,
+}
+
+fn format(path: &Path) -> Option<Format> {
+    let name = path.file_name()?.to_str()?.to_ascii_lowercase();
+
+    if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        Some(Format::TarGz)
+    } else if name.ends_with(".tar") {
+        Some(Format::Tar)
+    } else if name.ends_with(".zip") {
+        Some(Format::Zip)
+    } else {
+        None
+    }
+}
+
+pub fn is_archive(path: &Path) -> bool {
+    format(path).is_some()
+}
+
+pub fn entry_path(archive: &Path, entry: &Path) -> PathBuf {
+    let mut path = archive.as_os_str().to_os_string();
+    path.push(SEPARATOR);
+    path.push(entry);
+    PathBuf::from(path)
+}
+
+// archive and entry of a path built by entry_path
+pub fn split(path: &Path) -> Option<(PathBuf, PathBuf)> {
+    let text = path.to_str()?;
+
+    text.match_indices(SEPARATOR).find_map(|(i, _)| {
+        let archive = Path::new(&text[..i]);
+        is_archive(archive).then(|| (archive.to_path_buf(), PathBuf::from(&text[i + 1..])))
+    })
+}
+
+pub enum Entry {
+    Contents(Vec<u8>),
+    // an entry larger than the maximum file size is not kept, as it may be a decompression bomb
+    TooLarge(u64),
+}
+
+type Entries = BTreeMap<PathBuf, Entry>;
+
+// The entries of the archives listed, by archive, until each of them is read, as decompressing
+// an archive again for each of its entries would be wasteful.
+thread_local! {
+    static OPENED: RefCell<HashMap<PathBuf, Entries>> = RefCell::new(HashMap::new());
+}
+
+// paths of the files in the archive accepted by the filter, which are kept to be read
+pub fn list<F: Fn(&Path) -> bool>(archive: &Path, accept: F) -> io::Result<Vec<PathBuf>> {
+    let entries = load(archive, accept)?;
+    let names = entries.keys().cloned().collect();
+
+    OPENED.with(|o| o.borrow_mut().insert(archive.to_path_buf(), entries));
+    Ok(names)
+}
+
+pub fn read(archive: &Path, entry: &Path) -> io::Result<Entry> {
+    let cached = OPENED.with(|o| {
+        let mut opened = o.borrow_mut();
+        let entries = opened.get_mut(archive)?;
+        let contents = entries.remove(entry);
+        if entries.is_empty() {
+            opened.remove(archive);
+        }
+        contents
+    });
+
+    match cached {
+        Some(contents) => Ok(contents),
+        None => load(archive, |p| p == entry)?
+            .remove(entry)
+            .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound)),
+    }
+}
+
+// At most one byte more than the maximum file size is decompressed, whatever the size the
+// archive declares for the entry.
+fn read_entry(entry: impl Read, declared_size: u64) -> io::Result<Entry> {
+    let max_size = crate::configured_limits().max_file_size;
+
+    let mut contents = Vec::new();
+    entry.take(max_size + 1).read_to_end(&mut contents)?;
+
+    match contents.len() as u64 > max_size {
+        true => Ok(Entry::TooLarge(declared_size.max(contents.len() as u64))),
+        false => Ok(Entry::Contents(contents)),
+    }
+}
+
+fn load<F: Fn(&Path) -> bool>(archive: &Path, accept: F) -> io::Result<Entries> {
+    let file = File::open(archive)?;
+
+    match format(archive) {
+        Some(Format::Tar) => load_tar(file, accept),
+        Some(Format::TarGz) => load_tar(GzDecoder::new(file), accept),
+        Some(Format::Zip) => load_zip(file, accept),
+        None => Err(io::Error::from(io::ErrorKind::Unsupported)),
+    }
+}
+
+// entries with absolute paths or .. could refer to files outside of the archive once their
+// outputs are written
+fn is_relative(path: &Path) -> bool {
+    path.components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+}
+
+fn load_tar<R: Read, F: Fn(&Path) -> bool>(reader: R, accept: F) -> io::Result<Entries> {
+    let mut entries = Entries::new();
+    let mut archive = tar::Archive::new(reader);
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+
+        let path = entry.path()?.into_owned();
+        if is_relative(&path) && accept(&path) {
+            let size = entry.header().size()?;
+            entries.insert(path, read_entry(&mut entry, size)?);
+        }
+    }
+
+    Ok(entries)
+}
+
+fn load_zip<F: Fn(&Path) -> bool>(file: File, accept: F) -> io::Result<Entries> {
+    let mut entries = Entries::new();
+    let mut archive = zip::ZipArchive::new(file)?;
+
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+        if !entry.is_file() {
+            continue;
+        }
+
+        let Some(path) = entry.enclosed_name() else {
+            continue;
+        };
+
+        if accept(&path) {
+            let size = entry.size();
+            entries.insert(path, read_entry(&mut entry, size)?);
+        }
+    }
+
+    Ok(entries)
+}
@@ -20,52 +20,1927 @@
     DEALINGS IN THE SOFTWARE.
 */
 
-use clap::Parser;
-use std::path::PathBuf;
-use walkdir::WalkDir;
+use clap::{
+    builder::BoolishValueParser, error::ErrorKind, parser::ValueSource, ArgAction, ArgGroup,
+    ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum,
+};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use serde::Deserialize;
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, HashMap, HashSet},
+    ffi::OsStr,
+    fs,
+    io::{self, IsTerminal},
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{mpsc, Arc, Mutex},
+    thread,
+    time::Duration,
+};
+use termcolor::ColorChoice;
+use walkdir::{Error, WalkDir};
+
+use crate::{
+    archive_input,
+    config::Config,
+    error_codes::ErrorCode,
+    git,
+    header::Header,
+    hyperlinks,
+    indentation::IndentStyle,
+    limits::Limits,
+    lint_levels::LintLevel,
+    messages::{Language, Message},
+    remote_input,
+    reporter::{GitHubReporter, JsonReporter, Reporter},
+    string_lints::StringLints,
+    terminal_helper::{HumanReporter, TerminalOutput, Verbosity},
+    terminal_width,
+};
+
+// files found while scanning that may wait to be processed
+const STREAM_CAPACITY: usize = 256;
 
 #[derive(Parser)]
-#[command(author, version, about = "XR parser", long_about = None)]
+#[command(
+    author,
+    version,
+    about = "XR parser",
+    long_about = None,
+    after_help = "Running xr without a command is the same as running xr build.\n\n\
+                  Options may also be set through the XR_* environment variables shown in the \
+                  help of each command or in an xr.toml file. The command line takes precedence \
+                  over the environment, which takes precedence over the configuration file."
+)]
+struct Cli {
+    #[command(flatten)]
+    global: GlobalArgs,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[command(flatten)]
+    build: BuildArgs,
+}
+
 pub struct Args {
-    #[arg(short, long, group = "files")]
-    directory: Option<PathBuf>,
+    global: GlobalArgs,
+    command: Command,
+    // options given in the command line or the environment, which xr.toml cannot override
+    explicit: HashSet<String>,
+    lint_levels: HashMap<ErrorCode, LintLevel>,
+}
+
+#[derive(clap::Args)]
+struct GlobalArgs {
+    /// Configuration file (by default xr.toml is searched in the working directory and its ancestors)
+    #[arg(short, long, global = true, value_name = "PATH", env = "XR_CONFIG")]
+    config: Option<PathBuf>,
+
+    /// When to use colors
+    #[arg(long, global = true, value_enum, default_value_t = ColorWhen::Auto, env = "XR_COLOR")]
+    color: ColorWhen,
+
+    /// Print more information about the processing
+    #[arg(short, long, global = true, action = ArgAction::Count, env = "XR_VERBOSE")]
+    verbose: u8,
+
+    /// Language of the messages (by default it is obtained from the environment locale)
+    #[arg(long, global = true, value_enum, env = "XR_LANG")]
+    lang: Option<Language>,
+
+    /// Only print warnings and errors
+    #[arg(short, long, global = true, env = "XR_QUIET", value_parser = BoolishValueParser::new())]
+    quiet: bool,
+
+    /// Print statistics about the parsing time and the memory used by the tokens
+    #[arg(long, global = true, env = "XR_PROFILE", value_parser = BoolishValueParser::new())]
+    profile: bool,
+
+    /// Format of the statistics, where csv prints them for each file and directory (implies --profile)
+    #[arg(long, global = true, value_name = "FORMAT", env = "XR_STATS_FORMAT")]
+    stats_format: Option<StatsFormat>,
+
+    /// Log every decision of the parser (level, branch, token and lookahead) to a file, to debug misparses
+    #[arg(long, global = true, value_name = "PATH", env = "XR_TRACE_PARSER")]
+    trace_parser: Option<PathBuf>,
+
+    /// Check the consistency of the tokens of every parsed file, reporting violations as internal errors
+    #[arg(long, global = true, env = "XR_VALIDATE", value_parser = BoolishValueParser::new())]
+    validate: bool,
+
+    /// Record the progress of the run in a journal file, to resume it or find the file being processed when it died
+    #[arg(long, global = true, value_name = "PATH", env = "XR_JOURNAL")]
+    journal: Option<PathBuf>,
+
+    /// Skip the files completed in the run recorded in the journal
+    #[arg(long, global = true, requires = "journal", env = "XR_RESUME", value_parser = BoolishValueParser::new())]
+    resume: bool,
+
+    /// Severity of the problems that make xr exit with a failure status [default: error]
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        value_name = "SEVERITY",
+        env = "XR_FAIL_ON"
+    )]
+    fail_on: Option<FailOn>,
+
+    /// Write a report of the run, such as --report html report.html [possible formats: html]
+    #[arg(
+        long,
+        global = true,
+        num_args = 2,
+        value_names = ["FORMAT", "PATH"],
+        env = "XR_REPORT",
+        value_delimiter = ','
+    )]
+    report: Option<Vec<String>>,
+
+    /// Report the diagnostics with human (the terminal), json=PATH (a JSON file) or github
+    /// (annotations of GitHub Actions), which may be repeated to use several [default: human]
+    #[arg(
+        long,
+        global = true,
+        value_name = "REPORTER",
+        env = "XR_REPORTER",
+        value_delimiter = ','
+    )]
+    reporter: Vec<ReporterKind>,
+
+    /// Print long paths and diagnostics whole, instead of fitting them in the width of the terminal
+    #[arg(long, global = true, env = "XR_NO_TRUNCATE", value_parser = BoolishValueParser::new())]
+    no_truncate: bool,
+
+    /// When to make the paths printed links to the files, for the terminals that support them
+    #[arg(long, global = true, value_enum, default_value_t = HyperlinksWhen::Auto, env = "XR_HYPERLINKS")]
+    hyperlinks: HyperlinksWhen,
+
+    #[command(flatten)]
+    limits: LimitsArgs,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FailOn {
+    /// Fail when errors are reported
+    Error,
+    /// Fail when errors or warnings are reported
+    Warning,
+    /// Never fail because of the problems found in the files
+    Never,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum StringStyle {
+    /// Use raw strings for the strings with escaped backslashes, as in r"C:\temp"
+    Raw,
+    /// Use strings with escapes instead of raw strings
+    Plain,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CommentStyle {
+    /// Use line comments (//, /// and //!) instead of block comments
+    Line,
+    /// Use block comments (/* */, /** */ and /*! */) instead of line comments
+    Block,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ControlCharacters {
+    /// Remove the control characters
+    Strip,
+    /// Write the control characters as escapes in literals and comments, and remove them elsewhere
+    Escape,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OutputEncoding {
+    /// UTF-8
+    #[default]
+    Utf8,
+    /// UTF-8 beginning with a byte order mark, which some Windows tools require
+    Utf8Bom,
+}
+
+impl OutputEncoding {
+    pub fn encode(self, text: &str) -> Vec<u8> {
+        match self {
+            Self::Utf8 => text.as_bytes().to_vec(),
+            Self::Utf8Bom => ["\u{feff}", text].concat().into_bytes(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FileOrder {
+    /// Scanned files sorted by path, followed by the files given explicitly
+    Path,
+    /// Most recently modified first
+    Mtime,
+    /// Smallest first
+    Size,
+}
+
+// extension of the XR files found by the scan, and the suffix that replaces it in the name of
+// their generated files
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct ScanExtension {
+    extension: String,
+    suffix: String,
+}
+
+impl ScanExtension {
+    const DEFAULT_SUFFIX: &'static str = ".rs";
+
+    fn new(extension: &str) -> ScanExtension {
+        ScanExtension {
+            extension: extension.to_string(),
+            suffix: Self::DEFAULT_SUFFIX.to_string(),
+        }
+    }
+
+    fn matches(&self, file: &Path) -> bool {
+        file.extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case(&self.extension))
+    }
+}
+
+impl FromStr for ScanExtension {
+    type Err = String;
+
+    // "<ext>" or "<ext>=<suffix>", as in xri=_interface.rs
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (extension, suffix) = match s.split_once('=') {
+            Some((extension, suffix)) => (extension, suffix),
+            None => (s, Self::DEFAULT_SUFFIX),
+        };
+
+        // both xri and .xri are accepted
+        let extension = extension.trim_start_matches('.');
+        if extension.is_empty() || extension.contains(['/', '\\']) {
+            return Err(format!("invalid extension '{extension}'"));
+        }
+        if suffix.is_empty() || suffix.contains(['/', '\\']) {
+            return Err(format!("invalid suffix '{suffix}'"));
+        }
+
+        Ok(ScanExtension {
+            extension: extension.to_string(),
+            suffix: suffix.to_string(),
+        })
+    }
+}
+
+impl TryFrom<String> for ScanExtension {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
 
-    #[arg(short, long, group = "files")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum StatsFormat {
+    /// Totals of the parsing time and of the memory used by the tokens
+    #[default]
+    Text,
+    /// Lines, tokens and comment ratio of each file and directory, as CSV
+    Csv,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ReportFormat {
+    Html,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReporterKind {
+    Human,
+    Json(PathBuf),
+    GitHub,
+}
+
+impl FromStr for ReporterKind {
+    type Err = String;
+
+    // "human", "github" or "json=<path>"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "human" => Ok(ReporterKind::Human),
+            "github" => Ok(ReporterKind::GitHub),
+            "json" | "json=" => {
+                Err("the json reporter needs a path, as in json=report.json".to_string())
+            }
+            _ => match s.strip_prefix("json=") {
+                Some(path) => Ok(ReporterKind::Json(PathBuf::from(path))),
+                None => Err(format!(
+                    "invalid reporter '{s}' [possible reporters: human, json=PATH, github]"
+                )),
+            },
+        }
+    }
+}
+
+#[derive(clap::Args)]
+struct LimitsArgs {
+    /// Maximum size of the files processed, in bytes [default: 67108864]
+    #[arg(long, global = true, value_name = "BYTES", env = "XR_MAX_FILE_SIZE")]
+    max_file_size: Option<u64>,
+
+    /// Maximum number of tokens of a file [default: 10000000]
+    #[arg(long, global = true, value_name = "N", env = "XR_MAX_TOKENS")]
+    max_tokens: Option<usize>,
+
+    /// Maximum nesting depth of multiline comments [default: 1024]
+    #[arg(long, global = true, value_name = "N", env = "XR_MAX_COMMENT_DEPTH")]
+    max_comment_depth: Option<usize>,
+
+    /// Maximum number of hashes delimiting a raw string [default: 255]
+    #[arg(
+        long,
+        global = true,
+        value_name = "N",
+        env = "XR_MAX_RAW_STRING_HASHES"
+    )]
+    max_raw_string_hashes: Option<usize>,
+
+    /// Maximum time, in seconds, spent parsing each file
+    #[arg(long, global = true, value_name = "SECS", value_parser = parse_seconds, env = "XR_TIMEOUT")]
+    timeout: Option<f64>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ColorWhen {
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum HyperlinksWhen {
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Generate Rust files from XR files
+    Build(BuildArgs),
+
+    /// Parse XR files and report errors without generating any files
+    Check(CheckArgs),
+
+    /// Format XR files in place
+    Fmt(FmtArgs),
+
+    /// Print the tokens of XR files
+    Tokens(TokensArgs),
+
+    /// Rebuild XR files whenever they change
+    Watch(WatchArgs),
+
+    /// Print a detailed explanation of an error code
+    Explain(ExplainArgs),
+
+    /// Run the parser over a corpus of crash inputs, checking that it neither panics nor loses text
+    Replay(ReplayArgs),
+
+    /// Compare the tokens and diagnostics of .xr fixtures with their .expected snapshots
+    Test(TestArgs),
+
+    /// Stay resident, answering parse and generate requests sent over a unix socket
+    Daemon(DaemonArgs),
+
+    /// Install a git pre-commit hook that runs xr check on the changed files
+    InstallHook(InstallHookArgs),
+
+    /// Search the string literals and comments of XR files for API keys, tokens and other secrets
+    ScanSecrets(InputArgs),
+
+    /// Collect statistics about the code of XR files, for research into their patterns
+    Analyze(AnalyzeArgs),
+
+    /// Check that the generated files listed in a manifest match their checksums and sources
+    VerifyManifest(VerifyManifestArgs),
+}
+
+#[derive(clap::Args)]
+pub struct InputArgs {
+    /// XR files to process
+    #[arg(value_name = "FILE")]
+    files: Vec<PathBuf>,
+
+    /// Directory scanned for XR files, which may be repeated (by default, the working directory)
+    #[arg(short, long, env = "XR_DIRECTORY", value_delimiter = ',')]
+    directory: Vec<PathBuf>,
+
+    #[arg(short, long, env = "XR_FILENAMES", value_delimiter = ',')]
     filenames: Option<Vec<PathBuf>>,
+
+    /// Glob (relative to the scanned directory) of files and directories to skip
+    #[arg(short, long, value_name = "GLOB", env = "XR_EXCLUDE")]
+    exclude: Vec<String>,
+
+    /// Process scanned files in filesystem order instead of sorting them by path
+    #[arg(long, env = "XR_NO_SORT", value_parser = BoolishValueParser::new())]
+    no_sort: bool,
+
+    /// Only process the files changed since the last commit (staged or not) or untracked, according to git
+    #[arg(long, env = "XR_CHANGED_ONLY", value_parser = BoolishValueParser::new())]
+    changed_only: bool,
+
+    /// Only process the files changed since the given git revision or untracked
+    #[arg(long, value_name = "REF", env = "XR_CHANGED_FROM")]
+    changed_from: Option<String>,
+
+    /// Order in which the files are processed and reported [default: path]
+    #[arg(long, value_enum, env = "XR_ORDER")]
+    order: Option<FileOrder>,
+
+    /// Maximum depth of subdirectories scanned
+    #[arg(long, value_name = "DEPTH", env = "XR_MAX_DEPTH")]
+    max_depth: Option<usize>,
+
+    /// Extension of the XR files found by scanning directories, optionally followed by the suffix
+    /// of their generated files (as in xri=_interface.rs), which may be repeated [default: xr]
+    #[arg(long, value_name = "EXT[=SUFFIX]", env = "XR_EXTENSION")]
+    extension: Vec<ScanExtension>,
+
+    /// Process the http:// and https:// URLs given as files, downloading them (a URL ending in
+    /// #sha256=<digest> is checked against the digest and downloaded only once)
+    #[arg(long, env = "XR_ALLOW_REMOTE", value_parser = BoolishValueParser::new())]
+    allow_remote: bool,
+
+    /// Directory where the remote files pinned by a digest are kept [default: xr-remote in the temporary directory]
+    #[arg(
+        long,
+        value_name = "DIR",
+        env = "XR_REMOTE_CACHE",
+        requires = "allow_remote"
+    )]
+    remote_cache: Option<PathBuf>,
+
+    /// Also scan hidden files and directories (whose names begin with a dot, such as .git)
+    #[arg(long, env = "XR_HIDDEN", value_parser = BoolishValueParser::new())]
+    hidden: bool,
+
+    /// Process the files given without an extension as XR files, instead of skipping them
+    #[arg(long, env = "XR_ASSUME_XR", value_parser = BoolishValueParser::new())]
+    assume_xr: bool,
+}
+
+#[derive(clap::Args)]
+pub struct GenerateArgs {
+    /// Directory where the generated files are written, mirroring the scanned directory structure
+    #[arg(short, long, value_name = "DIR", env = "XR_OUT_DIR")]
+    out_dir: Option<PathBuf>,
+
+    /// Generate a mod.rs file declaring the generated modules of each scanned directory
+    #[arg(short, long, env = "XR_MOD_FILES", value_parser = BoolishValueParser::new())]
+    mod_files: bool,
+
+    /// File whose contents are prepended to every generated file, instead of the default banner
+    #[arg(long, value_name = "PATH", env = "XR_HEADER_FILE")]
+    header_file: Option<PathBuf>,
+
+    /// Do not prepend a header to the generated files
+    #[arg(long, env = "XR_NO_HEADER", value_parser = BoolishValueParser::new())]
+    no_header: bool,
+
+    /// License that every generated file must begin with, inserted when the XR file does not have it
+    #[arg(long, value_name = "PATH", env = "XR_LICENSE_FILE")]
+    license_file: Option<PathBuf>,
+
+    /// Omit the generation timestamp from the default banner (for reproducible builds)
+    #[arg(long, env = "XR_NO_TIMESTAMP", value_parser = BoolishValueParser::new())]
+    no_timestamp: bool,
+
+    /// Rewrite the indentation of the generated files (tabs, spaces or spaces:<width>)
+    #[arg(long, value_name = "STYLE", env = "XR_FIX_INDENT")]
+    fix_indent: Option<IndentStyle>,
+
+    /// Split generated files longer than this number of lines into parts, which they include
+    #[arg(long, value_name = "LINES", env = "XR_SPLIT_OUTPUT")]
+    split_output: Option<usize>,
+
+    /// Encoding of the generated files, whatever the encoding of the XR files
+    #[arg(long, value_name = "ENCODING", env = "XR_OUTPUT_ENCODING")]
+    output_encoding: Option<OutputEncoding>,
+
+    /// Rewrite the string literals of the generated files as raw strings or as strings with escapes
+    #[arg(long, value_name = "STYLE", env = "XR_REWRITE_STRINGS")]
+    rewrite_strings: Option<StringStyle>,
+
+    /// Rewrite the comments of the generated files as line comments or as block comments
+    #[arg(long, value_name = "STYLE", env = "XR_COMMENT_STYLE")]
+    comment_style: Option<CommentStyle>,
+
+    /// Remove the control characters (such as NUL) from the generated files or escape them
+    #[arg(long, value_name = "MODE", env = "XR_CONTROL_CHARACTERS")]
+    control_characters: Option<ControlCharacters>,
+
+    /// Do not generate the files that contain bidirectional control characters (such as U+202E)
+    /// in literals or comments
+    #[arg(long, env = "XR_REJECT_BIDI", value_parser = BoolishValueParser::new())]
+    reject_bidi: bool,
+
+    /// Do not generate the files of empty or whitespace-only inputs, instead of generating empty files
+    #[arg(long, env = "XR_SKIP_EMPTY", value_parser = BoolishValueParser::new())]
+    skip_empty: bool,
+
+    /// Remove trailing whitespace from the generated files (except inside strings and block comments)
+    #[arg(long, env = "XR_TRIM_TRAILING_WHITESPACE", value_parser = BoolishValueParser::new())]
+    trim_trailing_whitespace: bool,
+
+    /// Make sure that the generated files end with a new line
+    #[arg(long, env = "XR_ENSURE_FINAL_NEWLINE", value_parser = BoolishValueParser::new())]
+    ensure_final_newline: bool,
+
+    /// Format the generated files with rustfmt
+    #[arg(long, env = "XR_RUSTFMT", value_parser = BoolishValueParser::new())]
+    rustfmt: bool,
+
+    /// rustfmt executable used by --rustfmt (by default rustfmt is searched in the PATH)
+    #[arg(long, value_name = "PATH", env = "XR_RUSTFMT_PATH")]
+    rustfmt_path: Option<PathBuf>,
+
+    /// Check that the generated files are valid Rust by compiling them with rustc
+    #[arg(long, env = "XR_VERIFY_OUTPUT", value_parser = BoolishValueParser::new())]
+    verify_output: bool,
+
+    /// Omit the timestamp, report content that depends on the time or machine of the generation and check that generating twice gives the same files
+    #[arg(long, env = "XR_REPRODUCIBLE", value_parser = BoolishValueParser::new())]
+    reproducible: bool,
+
+    /// Directory where generated files are cached, which can be shared between machines
+    #[arg(long, value_name = "DIR", env = "XR_CACHE_DIR")]
+    cache_dir: Option<PathBuf>,
+
+    /// URL of a cache served over HTTP, whose entries are read with GET and stored with PUT
+    #[arg(long, value_name = "URL", env = "XR_CACHE_URL")]
+    cache_url: Option<String>,
+
+    /// Token sent as a bearer token to the cache server
+    #[arg(
+        long,
+        value_name = "TOKEN",
+        env = "XR_CACHE_TOKEN",
+        hide_env_values = true
+    )]
+    cache_token: Option<String>,
+
+    /// Use the files in the cache, without adding new ones
+    #[arg(long, env = "XR_CACHE_READ_ONLY", value_parser = BoolishValueParser::new())]
+    cache_read_only: bool,
+
+    /// Save the previous contents of the generated files that are overwritten, in files with
+    /// this suffix appended to their names (as in --backup .bak)
+    #[arg(long, value_name = "SUFFIX", env = "XR_BACKUP")]
+    backup: Option<String>,
+
+    /// Save the previous contents of the generated files that are overwritten in this directory,
+    /// under their relative paths
+    #[arg(long, value_name = "DIR", env = "XR_BACKUP_DIR")]
+    backup_dir: Option<PathBuf>,
+
+    /// Number of backups kept of each file, where the older ones end in .1, .2 and so on
+    /// [default: 1]
+    #[arg(long, value_name = "N", env = "XR_BACKUP_COUNT", value_parser = parse_positive)]
+    backup_count: Option<usize>,
+
+    /// Check that the generated files are syntactically valid Rust, without running rustc
+    #[cfg(feature = "syn")]
+    #[arg(long, env = "XR_VERIFY_SYNTAX", value_parser = BoolishValueParser::new())]
+    verify_syntax: bool,
+}
+
+#[derive(clap::Args)]
+pub struct BuildArgs {
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    #[command(flatten)]
+    pub generate: GenerateArgs,
+
+    /// Print the files that would be generated, skipped and written, without writing anything
+    #[arg(long, env = "XR_PLAN", value_parser = BoolishValueParser::new())]
+    pub plan: bool,
+
+    /// Generate a single Rust file with the output of every XR file, each one in its own module
+    #[arg(
+        long,
+        value_name = "PATH",
+        env = "XR_AMALGAMATE",
+        conflicts_with = "plan"
+    )]
+    pub amalgamate: Option<PathBuf>,
+
+    /// Write a JSON manifest with the SHA-256 of every generated file and of the XR file it was
+    /// generated from, to be checked with xr verify-manifest
+    #[arg(
+        long,
+        value_name = "PATH",
+        env = "XR_EMIT_MANIFEST",
+        conflicts_with = "plan"
+    )]
+    pub emit_manifest: Option<PathBuf>,
+
+    /// Generate the files in memory and compare them with the files on disk, failing with a diff
+    /// of the generated files that were edited by hand
+    #[arg(
+        long,
+        env = "XR_VERIFY_GENERATED",
+        value_parser = BoolishValueParser::new(),
+        conflicts_with_all = ["plan", "verify_output", "emit_manifest", "archive"]
+    )]
+    pub verify_generated: bool,
+
+    /// Print a diff of the changes to the generated files that already exist before writing them
+    #[arg(
+        long,
+        env = "XR_DIFF",
+        value_parser = BoolishValueParser::new(),
+        conflicts_with_all = ["plan", "verify_generated", "archive"]
+    )]
+    pub diff: bool,
+
+    /// Print the diffs of --diff without writing the generated files
+    #[arg(
+        long,
+        env = "XR_DIFF_ONLY",
+        value_parser = BoolishValueParser::new(),
+        conflicts_with_all = ["plan", "verify_generated", "archive"]
+    )]
+    pub diff_only: bool,
+
+    /// Print the diff of every generated file that would change and ask before overwriting it
+    /// (nothing is overwritten when the standard input is not a terminal)
+    #[arg(
+        long,
+        env = "XR_INTERACTIVE",
+        value_parser = BoolishValueParser::new(),
+        conflicts_with_all = ["plan", "verify_generated", "archive", "diff_only"]
+    )]
+    pub interactive: bool,
+
+    /// Wait for another xr instance writing to the same directory to finish, instead of failing
+    #[arg(long, env = "XR_WAIT", value_parser = BoolishValueParser::new())]
+    pub wait: bool,
+
+    /// Write the generated files into a tar archive instead of the filesystem
+    #[arg(
+        long,
+        value_name = "PATH",
+        env = "XR_ARCHIVE",
+        conflicts_with_all = ["plan", "verify_output"]
+    )]
+    pub archive: Option<PathBuf>,
+}
+
+#[derive(clap::Args)]
+pub struct CheckArgs {
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// Baseline file with known errors, which are not reported (it is created if it does not exist)
+    #[arg(long, value_name = "PATH", env = "XR_BASELINE")]
+    pub baseline: Option<PathBuf>,
+
+    /// Record the current errors in the baseline file, replacing its contents
+    #[arg(long, requires = "baseline", env = "XR_UPDATE_BASELINE", value_parser = BoolishValueParser::new())]
+    pub update_baseline: bool,
+
+    /// Print the TODO, FIXME and HACK comments of the files
+    #[arg(long, env = "XR_TODOS", value_parser = BoolishValueParser::new())]
+    pub todos: bool,
+
+    /// Report TODO, FIXME and HACK comments as warnings
+    #[arg(long, env = "XR_WARN_TODOS", value_parser = BoolishValueParser::new())]
+    pub warn_todos: bool,
+
+    /// Report string literals longer than this number of characters
+    #[arg(long, value_name = "CHARS", env = "XR_MAX_STRING_LENGTH")]
+    pub max_string_length: Option<usize>,
+
+    /// Report string literals containing this text, which may be repeated (e.g. the prefix of a
+    /// token or a local path)
+    #[arg(
+        long = "disallow-in-strings",
+        value_name = "TEXT",
+        env = "XR_DISALLOW_IN_STRINGS",
+        value_delimiter = ','
+    )]
+    pub disallowed_in_strings: Vec<String>,
+
+    /// Report string literals with non-printable characters
+    #[arg(long, env = "XR_DENY_NON_PRINTABLE", value_parser = BoolishValueParser::new())]
+    pub deny_non_printable: bool,
+
+    /// Report lines longer than this number of characters
+    #[arg(long, value_name = "CHARS", env = "XR_MAX_LINE_LENGTH")]
+    pub max_line_length: Option<usize>,
+
+    /// Allow long lines that exceed the maximum inside a string literal or a URL in a comment
+    #[arg(long, env = "XR_ALLOW_LONG_LITERALS", value_parser = BoolishValueParser::new())]
+    pub allow_long_literals: bool,
+
+    /// Report the words of the comments that are not in the dictionaries
+    #[arg(long, env = "XR_SPELL_CHECK", value_parser = BoolishValueParser::new())]
+    pub spell_check: bool,
+
+    /// Dictionary used by --spell-check, as a list of words or a Hunspell .dic file, which may be
+    /// repeated
+    #[arg(
+        long = "dictionary",
+        value_name = "PATH",
+        env = "XR_DICTIONARY",
+        value_delimiter = ','
+    )]
+    pub dictionaries: Vec<PathBuf>,
+
+    // words of the project that are always correct, from xr.toml
+    #[arg(skip)]
+    pub spelling_words: Vec<String>,
+}
+
+#[derive(clap::Args)]
+pub struct FmtArgs {
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// Report the files that are not formatted instead of rewriting them
+    #[arg(long, env = "XR_CHECK", value_parser = BoolishValueParser::new())]
+    pub check: bool,
+
+    /// Rewrite the indentation of the files (tabs, spaces or spaces:<width>)
+    #[arg(long, value_name = "STYLE", env = "XR_FIX_INDENT")]
+    pub fix_indent: Option<IndentStyle>,
+
+    /// Rewrite the string literals of the files as raw strings or as strings with escapes
+    #[arg(long, value_name = "STYLE", env = "XR_REWRITE_STRINGS")]
+    pub rewrite_strings: Option<StringStyle>,
+
+    /// Rewrite the comments of the files as line comments or as block comments
+    #[arg(long, value_name = "STYLE", env = "XR_COMMENT_STYLE")]
+    pub comment_style: Option<CommentStyle>,
+}
+
+#[derive(clap::Args)]
+pub struct WatchArgs {
+    #[command(flatten)]
+    pub build: BuildArgs,
+
+    /// Interval, in milliseconds, between checks for changes
+    #[arg(long, value_name = "MS", default_value_t = 500, env = "XR_INTERVAL")]
+    pub interval: u64,
+
+    /// File where the files that had errors or warnings are kept between runs, so that they are
+    /// processed first
+    #[arg(long, value_name = "PATH", env = "XR_WATCH_STATE")]
+    pub state: Option<PathBuf>,
+}
+
+#[derive(clap::Args)]
+pub struct DaemonArgs {
+    #[command(flatten)]
+    pub generate: GenerateArgs,
+
+    /// Unix socket on which requests are received
+    #[arg(
+        long,
+        value_name = "PATH",
+        default_value = "xr.sock",
+        env = "XR_SOCKET"
+    )]
+    pub socket: PathBuf,
+}
+
+#[derive(clap::Args)]
+pub struct InstallHookArgs {
+    /// Remove the hook instead of installing it
+    #[arg(long, env = "XR_UNINSTALL", value_parser = BoolishValueParser::new())]
+    pub uninstall: bool,
+
+    /// Replace a pre-commit hook that was not installed by xr
+    #[arg(long, env = "XR_FORCE", value_parser = BoolishValueParser::new())]
+    pub force: bool,
+}
+
+#[derive(clap::Args)]
+pub struct ExplainArgs {
+    /// Error code, such as E0002
+    pub code: String,
+}
+
+#[derive(clap::Args)]
+pub struct VerifyManifestArgs {
+    /// Manifest written by xr build --emit-manifest
+    #[arg(value_name = "PATH")]
+    pub manifest: PathBuf,
+}
+
+#[derive(clap::Args)]
+pub struct ReplayArgs {
+    /// Directory with the inputs, such as a cargo-fuzz corpus or artifacts directory
+    #[arg(value_name = "DIR")]
+    pub dir: PathBuf,
+}
+
+#[derive(clap::Args)]
+pub struct TestArgs {
+    /// Directory with the .xr fixtures
+    #[arg(value_name = "DIR")]
+    pub dir: PathBuf,
+
+    /// Write the current results to the .expected snapshots instead of comparing them
+    #[arg(long, env = "XR_BLESS", value_parser = BoolishValueParser::new())]
+    pub bless: bool,
+}
+
+#[derive(clap::Args)]
+pub struct TokensArgs {
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// Level of the parser whose tokens are printed, where 3 is the final one and 1 and 2 are
+    /// the intermediate ones
+    #[arg(
+        long,
+        default_value_t = 3,
+        value_parser = clap::value_parser!(u8).range(1..=3),
+        env = "XR_LEVEL"
+    )]
+    pub level: u8,
+
+    /// Print a token per line with its line number, as in the .expected snapshots of xr test
+    #[arg(long, env = "XR_PLAIN", value_parser = BoolishValueParser::new())]
+    pub plain: bool,
+}
+
+#[derive(clap::Args)]
+#[command(group(ArgGroup::new("analysis").required(true).multiple(true)))]
+pub struct AnalyzeArgs {
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// Write the number of occurrences of each identifier, keyword, lifetime and kind of literal
+    /// to a CSV file (or to a JSON file, when the path ends with .json)
+    #[arg(long, value_name = "PATH", group = "analysis", env = "XR_HISTOGRAM")]
+    pub histogram: Option<PathBuf>,
+
+    /// Report the regions of code that are repeated, in the same file or in different files, even
+    /// with other names and literals
+    #[arg(long, group = "analysis", env = "XR_DUPLICATES", value_parser = BoolishValueParser::new())]
+    pub duplicates: bool,
+
+    /// Minimum number of tokens (not counting whitespace and comments) of a repeated region
+    #[arg(
+        long,
+        value_name = "TOKENS",
+        default_value_t = 50,
+        value_parser = parse_positive,
+        env = "XR_MIN_TOKENS"
+    )]
+    pub min_tokens: usize,
 }
 
 impl Args {
     pub fn obtain() -> Args {
-        Args::parse()
+        let matches = Cli::command().get_matches();
+        let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+
+        if let Some((name, _)) = matches.subcommand() {
+            reject_build_args_before_subcommand(&matches, name);
+        }
+
+        if let Some([format, _]) = cli.global.report.as_deref() {
+            if ReportFormat::from_str(format, true).is_err() {
+                Cli::command()
+                    .error(
+                        ErrorKind::InvalidValue,
+                        format!("invalid report format '{format}' [possible values: html]"),
+                    )
+                    .exit();
+            }
+        }
+
+        let mut global = cli.global;
+        let mut command = cli.command.unwrap_or(Command::Build(cli.build));
+        let matches = match matches.subcommand() {
+            Some((_, sub_matches)) => sub_matches,
+            None => &matches,
+        };
+
+        if prefer_first(matches, "quiet", "verbose") {
+            global.verbose = 0;
+        } else {
+            global.quiet = false;
+        }
+
+        match &mut command {
+            Command::Build(build) => build.resolve_conflicts(matches),
+            Command::Check(check) => check.input.merge_files(),
+            Command::Tokens(tokens) => tokens.resolve_conflicts(matches),
+            Command::ScanSecrets(input) => input.merge_files(),
+            Command::Fmt(fmt) => fmt.input.merge_files(),
+            Command::Analyze(analyze) => analyze.input.merge_files(),
+            Command::Watch(watch) => watch.build.resolve_conflicts(matches),
+            Command::Daemon(daemon) => daemon.generate.resolve_conflicts(matches),
+            Command::Explain(_)
+            | Command::Replay(_)
+            | Command::Test(_)
+            | Command::VerifyManifest(_) => {}
+            Command::InstallHook(hook) => hook.resolve_conflicts(matches),
+        }
+
+        let explicit = matches
+            .ids()
+            .filter(|id| {
+                matches!(
+                    matches.value_source(id.as_str()),
+                    Some(ValueSource::CommandLine | ValueSource::EnvVariable)
+                )
+            })
+            .map(|id| id.to_string())
+            .collect();
+
+        Args {
+            global,
+            command,
+            explicit,
+            lint_levels: HashMap::new(),
+        }
+    }
+
+    pub fn color(&self) -> ColorChoice {
+        match self.global.color {
+            ColorWhen::Auto => ColorChoice::Auto,
+            ColorWhen::Always => ColorChoice::Always,
+            ColorWhen::Never => ColorChoice::Never,
+        }
+    }
+
+    pub fn language(&self) -> Language {
+        self.global.lang.unwrap_or_else(Language::from_environment)
+    }
+
+    pub fn verbosity(&self) -> Verbosity {
+        match (self.global.quiet, self.global.verbose) {
+            (true, _) => Verbosity::Quiet,
+            (false, 0) => Verbosity::Normal,
+            _ => Verbosity::Verbose,
+        }
+    }
+
+    pub fn profile(&self) -> bool {
+        self.global.profile || self.global.stats_format.is_some()
+    }
+
+    pub fn stats_format(&self) -> StatsFormat {
+        self.global.stats_format.unwrap_or_default()
+    }
+
+    pub fn lint_levels(&self) -> HashMap<ErrorCode, LintLevel> {
+        self.lint_levels.clone()
+    }
+
+    pub fn limits(&self) -> Limits {
+        let limits = &self.global.limits;
+        let default = Limits::default();
+
+        Limits {
+            max_file_size: limits.max_file_size.unwrap_or(default.max_file_size),
+            max_tokens: limits.max_tokens.unwrap_or(default.max_tokens),
+            max_comment_depth: limits
+                .max_comment_depth
+                .unwrap_or(default.max_comment_depth),
+            max_raw_string_hashes: limits
+                .max_raw_string_hashes
+                .unwrap_or(default.max_raw_string_hashes),
+            timeout: limits.timeout.map(Duration::from_secs_f64),
+        }
+    }
+
+    pub fn report(&self) -> Option<(ReportFormat, PathBuf)> {
+        match self.global.report.as_deref() {
+            Some([format, path]) => Some((
+                ReportFormat::from_str(format, true).ok()?,
+                PathBuf::from(path),
+            )),
+            _ => None,
+        }
+    }
+
+    // whether the paths printed to a stream are hyperlinks
+    pub fn hyperlinks(&self, is_terminal: bool) -> bool {
+        match self.global.hyperlinks {
+            HyperlinksWhen::Auto => is_terminal && hyperlinks::supported(),
+            HyperlinksWhen::Always => true,
+            HyperlinksWhen::Never => false,
+        }
+    }
+
+    pub fn width(&self) -> Option<usize> {
+        match self.global.no_truncate {
+            true => None,
+            false => terminal_width::detect(),
+        }
+    }
+
+    pub fn reporters(&self) -> Vec<Box<dyn Reporter>> {
+        let human = || {
+            Box::new(HumanReporter::new(
+                self.color(),
+                self.verbosity(),
+                self.width(),
+                self.hyperlinks(io::stderr().is_terminal()),
+            )) as Box<dyn Reporter>
+        };
+        if self.global.reporter.is_empty() {
+            return vec![human()];
+        }
+
+        self.global
+            .reporter
+            .iter()
+            .map(|kind| match kind {
+                ReporterKind::Human => human(),
+                ReporterKind::Json(path) => Box::new(JsonReporter::new(path)),
+                ReporterKind::GitHub => Box::new(GitHubReporter),
+            })
+            .collect()
+    }
+
+    pub fn journal(&self) -> Option<&Path> {
+        self.global.journal.as_deref()
+    }
+
+    pub fn trace_parser(&self) -> Option<&Path> {
+        self.global.trace_parser.as_deref()
+    }
+
+    pub fn resume(&self) -> bool {
+        self.global.resume
+    }
+
+    pub fn validate(&self) -> bool {
+        self.global.validate
+    }
+
+    pub fn fail_on(&self) -> FailOn {
+        self.global.fail_on.unwrap_or(FailOn::Error)
+    }
+
+    pub fn command(&self) -> &Command {
+        &self.command
+    }
+
+    pub fn into_command(self) -> Command {
+        self.command
+    }
+
+    // Options given in the command line take precedence over the configuration file
+    pub fn apply_config(&mut self) -> Result<Option<PathBuf>, Message> {
+        let working_dir = match &self.command {
+            Command::Build(build) => build.input.working_dir(),
+            Command::Check(check) => check.input.working_dir(),
+            Command::Tokens(tokens) => tokens.input.working_dir(),
+            Command::ScanSecrets(input) => input.working_dir(),
+            Command::Fmt(fmt) => fmt.input.working_dir(),
+            Command::Analyze(analyze) => analyze.input.working_dir(),
+            Command::Watch(watch) => watch.build.input.working_dir(),
+            Command::Daemon(_) => std::env::current_dir().unwrap_or(PathBuf::from(".")),
+            Command::Explain(_)
+            | Command::Replay(_)
+            | Command::Test(_)
+            | Command::InstallHook(_)
+            | Command::VerifyManifest(_) => return Ok(None),
+        };
+
+        let path = match &self.global.config {
+            Some(path) => path.clone(),
+            None => match Config::discover(&working_dir) {
+                Some(path) => path,
+                None => return Ok(None),
+            },
+        };
+
+        let config = Config::load(&path)?;
+
+        self.global.limits.apply_config(&config);
+        self.global.fail_on = self.global.fail_on.or(config.fail_on);
+        self.lint_levels = config.lint_levels();
+
+        let explicit = &self.explicit;
+        match &mut self.command {
+            Command::Build(build) => build.apply_config(config, explicit),
+            Command::Check(check) => check.apply_config(config, explicit),
+            Command::Tokens(tokens) => tokens.input.apply_config(config, explicit),
+            Command::ScanSecrets(input) => input.apply_config(config, explicit),
+            Command::Fmt(fmt) => fmt.apply_config(config, explicit),
+            Command::Analyze(analyze) => analyze.input.apply_config(config, explicit),
+            Command::Watch(watch) => watch.build.apply_config(config, explicit),
+            Command::Daemon(daemon) => daemon.generate.apply_config(&config, explicit),
+            Command::Explain(_)
+            | Command::Replay(_)
+            | Command::Test(_)
+            | Command::InstallHook(_)
+            | Command::VerifyManifest(_) => {}
+        }
+
+        Ok(Some(path))
+    }
+}
+
+impl LimitsArgs {
+    fn apply_config(&mut self, config: &Config) {
+        self.max_file_size = self.max_file_size.or(config.max_file_size);
+        self.max_tokens = self.max_tokens.or(config.max_tokens);
+        self.max_comment_depth = self.max_comment_depth.or(config.max_comment_depth);
+        self.max_raw_string_hashes = self.max_raw_string_hashes.or(config.max_raw_string_hashes);
+        self.timeout = self
+            .timeout
+            .or(config.timeout.filter(|t| is_valid_seconds(*t)));
+    }
+}
+
+impl FmtArgs {
+    fn apply_config(&mut self, config: Config, explicit: &HashSet<String>) {
+        self.rewrite_strings = self.rewrite_strings.or(config.rewrite_strings);
+        self.comment_style = self.comment_style.or(config.comment_style);
+        self.input.apply_config(config, explicit);
+    }
+}
+
+impl CheckArgs {
+    fn apply_config(&mut self, mut config: Config, explicit: &HashSet<String>) {
+        let flag = |flag: &mut bool, id, value| config_flag(flag, id, value, explicit);
+
+        self.max_string_length = self.max_string_length.or(config.max_string_length);
+        self.disallowed_in_strings
+            .append(&mut config.disallowed_in_strings);
+        flag(
+            &mut self.deny_non_printable,
+            "deny_non_printable",
+            config.deny_non_printable,
+        );
+        self.max_line_length = self.max_line_length.or(config.max_line_length);
+        flag(
+            &mut self.allow_long_literals,
+            "allow_long_literals",
+            config.allow_long_literals,
+        );
+        flag(&mut self.spell_check, "spell_check", config.spell_check);
+        self.dictionaries.append(&mut config.dictionaries);
+        self.spelling_words.append(&mut config.spelling_words);
+        self.input.apply_config(config, explicit);
     }
 
-    fn working_dir(self) -> PathBuf {
-        match self.directory {
-            Some(dir) => dir,
+    pub fn string_lints(&self) -> StringLints {
+        StringLints {
+            max_length: self.max_string_length,
+            disallowed: self.disallowed_in_strings.clone(),
+            non_printable: self.deny_non_printable,
+        }
+    }
+}
+
+impl InputArgs {
+    fn merge_files(&mut self) {
+        if !self.files.is_empty() {
+            self.filenames
+                .get_or_insert_with(Vec::new)
+                .append(&mut self.files);
+        }
+    }
+
+    fn apply_config(&mut self, config: Config, explicit: &HashSet<String>) {
+        let flag = |flag: &mut bool, id, value| config_flag(flag, id, value, explicit);
+
+        self.exclude.extend(config.exclude);
+        flag(&mut self.no_sort, "no_sort", config.sort.map(|sort| !sort));
+        self.order = self.order.or(config.order);
+        self.max_depth = self.max_depth.or(config.max_depth);
+        if self.extension.is_empty() {
+            self.extension = config.extensions;
+        }
+        flag(&mut self.assume_xr, "assume_xr", config.assume_xr);
+        flag(&mut self.hidden, "hidden", config.hidden);
+        flag(&mut self.allow_remote, "allow_remote", config.allow_remote);
+        self.remote_cache = self.remote_cache.take().or(config.remote_cache);
+    }
+
+    fn extensions(&self) -> Vec<ScanExtension> {
+        match self.extension.is_empty() {
+            true => vec![ScanExtension::new("xr")],
+            false => self.extension.clone(),
+        }
+    }
+
+    // suffix of the generated file of an input, by the extension it was scanned for
+    pub fn output_suffix(&self, input: &Path) -> String {
+        self.extensions()
+            .into_iter()
+            .find(|e| e.matches(input))
+            .map_or(ScanExtension::DEFAULT_SUFFIX.to_string(), |e| e.suffix)
+    }
+
+    pub fn working_dir(&self) -> PathBuf {
+        match self.directory.first() {
+            Some(dir) => dir.clone(),
             None => std::env::current_dir().unwrap_or(PathBuf::from(".")),
         }
     }
 
-    pub fn files_to_process(self) -> Vec<PathBuf> {
-        if let Some(filenames) = self.filenames {
-            filenames
+    // directories scanned, or that would be scanned when only files are given
+    pub fn roots(&self) -> Vec<PathBuf> {
+        if self.directory.is_empty() {
+            vec![self.working_dir()]
         } else {
-            let mut filenames = Vec::<PathBuf>::new();
-
-            for entry in WalkDir::new(self.working_dir())
-                .follow_links(true)
-                .into_iter()
-                .filter_map(|e| e.ok())
-            {
-                let filename = entry.path();
-
-                if let Some(extension) = filename.extension() {
-                    if extension.to_ascii_lowercase() == "xr" {
-                        filenames.push(filename.to_path_buf());
+            self.directory.clone()
+        }
+    }
+
+    // innermost root containing the file, as roots may be nested
+    pub fn root_of(&self, file: &Path) -> Option<PathBuf> {
+        self.roots()
+            .into_iter()
+            .filter(|root| file.starts_with(root))
+            .max_by_key(|root| root.components().count())
+    }
+
+    pub fn scans_directory(&self) -> bool {
+        self.filenames.is_none() || !self.directory.is_empty()
+    }
+
+    pub fn files_to_process(&self, output: &mut TerminalOutput) -> Vec<PathBuf> {
+        let mut filenames = Vec::<PathBuf>::new();
+
+        if self.scans_directory() {
+            let excluded = self.exclude_set(output);
+
+            for root in self.roots() {
+                self.scan(&root, &excluded, &mut |found| {
+                    match found {
+                        Ok(file) => filenames.push(file),
+                        Err(e) => output.writeln_warning(skipped_entry_message(&e)),
                     }
+                    true
+                });
+            }
+        }
+
+        self.explicit_files(&mut filenames, output);
+
+        // the same file may be found through several roots, or also be given explicitly
+        let mut seen = HashSet::new();
+        filenames.retain(|f| seen.insert(f.canonicalize().unwrap_or(f.clone())));
+
+        if self.changed_only || self.changed_from.is_some() {
+            match git::changed_files(&self.working_dir(), self.changed_from.as_deref()) {
+                Ok(changed) => {
+                    filenames.retain(|f| changed.contains(&f.canonicalize().unwrap_or(f.clone())))
+                }
+                Err(e) => {
+                    output.writeln_error(e);
+                    filenames.clear();
                 }
             }
-            filenames
         }
+
+        // stable sorts, so that files that compare equal keep their order by path
+        match self.order.unwrap_or(FileOrder::Path) {
+            FileOrder::Path => {}
+            FileOrder::Mtime => filenames
+                .sort_by_cached_key(|f| Reverse(fs::metadata(f).and_then(|m| m.modified()).ok())),
+            FileOrder::Size => {
+                filenames.sort_by_cached_key(|f| fs::metadata(f).map(|m| m.len()).ok())
+            }
+        }
+
+        filenames
+    }
+
+    // Files can be processed while the directories are still being scanned, unless they are
+    // sorted or filtered by git, which needs every file to be known first.
+    pub fn can_stream(&self) -> bool {
+        self.scans_directory()
+            && self.order.unwrap_or(FileOrder::Path) == FileOrder::Path
+            && !self.changed_only
+            && self.changed_from.is_none()
+    }
+
+    // Scans the directories in another thread, which sends the files it finds through a bounded
+    // channel to a pool of workers that read them, so that each file is processed as soon as it is
+    // found instead of after the walk of the whole tree. The files are then processed by the
+    // calling thread, which keeps the state of the run, such as the journal and the summary, in the
+    // order in which they were found. Returns the number of files processed.
+    pub fn stream_files<T: Send>(
+        &self,
+        output: &mut TerminalOutput,
+        read: impl Fn(&Path) -> T + Sync,
+        mut process: impl FnMut(&Path, T, &mut TerminalOutput),
+    ) -> usize {
+        let excluded = self.exclude_set(output);
+        let mut seen = HashSet::new();
+        let mut count = 0;
+
+        thread::scope(|scope| {
+            let (sender, found) = mpsc::sync_channel(STREAM_CAPACITY);
+            let (read_sender, receiver) = mpsc::sync_channel(STREAM_CAPACITY);
+            // the scan stops once every worker has stopped receiving
+            let found = Arc::new(Mutex::new(found));
+            let seen = &mut seen;
+
+            scope.spawn(move || {
+                let mut index = 0;
+                for root in self.roots() {
+                    // the walk stops if the files are no longer received
+                    self.scan(&root, &excluded, &mut |found| {
+                        let found = match found {
+                            Ok(file)
+                                if !seen.insert(file.canonicalize().unwrap_or(file.clone())) =>
+                            {
+                                return true;
+                            }
+                            found => found,
+                        };
+                        index += 1;
+                        sender.send((index, found)).is_ok()
+                    });
+                }
+            });
+
+            let workers = thread::available_parallelism().map_or(1, |n| n.get());
+            for _ in 0..workers {
+                let (found, read, read_sender) = (found.clone(), &read, read_sender.clone());
+                scope.spawn(move || loop {
+                    // the lock is released before the file is read
+                    let next = found.lock().map(|found| found.recv());
+                    let Ok(Ok((index, found))) = next else {
+                        return;
+                    };
+                    let read = found.map(|file| {
+                        let contents = read(&file);
+                        (file, contents)
+                    });
+                    if read_sender.send((index, read)).is_err() {
+                        return;
+                    }
+                });
+            }
+            drop((found, read_sender));
+
+            // the workers finish in any order, so the files read ahead wait for their turn
+            let mut pending = BTreeMap::new();
+            let mut next = 1;
+            for (index, read) in receiver {
+                pending.insert(index, read);
+
+                while let Some(read) = pending.remove(&next) {
+                    if crate::interrupt::requested() {
+                        return;
+                    }
+                    next += 1;
+
+                    match read {
+                        Ok((file, contents)) => {
+                            count += 1;
+                            process(&file, contents, output);
+                        }
+                        Err(e) => output.writeln_warning(skipped_entry_message(&e)),
+                    }
+                }
+            }
+        });
+
+        let mut explicit = Vec::new();
+        self.explicit_files(&mut explicit, output);
+        for file in explicit {
+            if crate::interrupt::requested() {
+                break;
+            }
+            if seen.insert(file.canonicalize().unwrap_or(file.clone())) {
+                count += 1;
+                process(&file, read(&file), output);
+            }
+        }
+
+        count
+    }
+
+    fn explicit_files(&self, filenames: &mut Vec<PathBuf>, output: &mut TerminalOutput) {
+        for file in self.filenames.iter().flatten() {
+            if remote_input::is_url(file) {
+                if self.allow_remote {
+                    remote_input::set_cache_dir(self.remote_cache.as_deref());
+                    filenames.push(file.clone());
+                } else {
+                    output.writeln_error(Message::RemoteNotAllowed(file.display().to_string()));
+                }
+            } else if archive_input::is_archive(file) {
+                self.list_archive(file, filenames, output);
+            } else if file.extension().is_none() && !self.assume_xr {
+                output.writeln_warning(Message::ExtensionMissing(file.display().to_string()));
+            } else {
+                filenames.push(file.clone());
+            }
+        }
+    }
+
+    // found is called with each file (or entry that cannot be read), and the scan stops when it
+    // returns false
+    fn scan(
+        &self,
+        root: &Path,
+        excluded: &GlobSet,
+        found: &mut dyn FnMut(Result<PathBuf, Error>) -> bool,
+    ) {
+        let mut walker = WalkDir::new(root).follow_links(true);
+
+        if !self.no_sort {
+            walker = walker.sort_by_file_name();
+        }
+
+        if let Some(depth) = self.max_depth {
+            walker = walker.max_depth(depth);
+        }
+
+        let entries = walker.into_iter().filter_entry(|e| {
+            // the root is scanned even if hidden, as it was given explicitly
+            if !self.hidden && e.depth() > 0 && is_hidden(e.file_name()) {
+                return false;
+            }
+
+            match e.path().strip_prefix(root) {
+                Ok(relative) => !excluded.is_match(relative),
+                Err(_) => true,
+            }
+        });
+
+        let extensions = self.extensions();
+
+        for entry in entries {
+            let proceed = match entry {
+                Ok(entry) if extensions.iter().any(|e| e.matches(entry.path())) => {
+                    found(Ok(entry.into_path()))
+                }
+                Ok(_) => true,
+                Err(e) => found(Err(e)),
+            };
+
+            if !proceed {
+                return;
+            }
+        }
+    }
+
+    // the XR files inside an archive are processed without extracting them
+    fn list_archive(
+        &self,
+        archive: &Path,
+        filenames: &mut Vec<PathBuf>,
+        output: &mut TerminalOutput,
+    ) {
+        let extensions = self.extensions();
+
+        match archive_input::list(archive, |p| extensions.iter().any(|e| e.matches(p))) {
+            Ok(entries) => filenames.extend(
+                entries
+                    .iter()
+                    .map(|entry| archive_input::entry_path(archive, entry)),
+            ),
+            Err(e) => output.writeln_error(Message::ArchiveUnreadable(
+                archive.display().to_string(),
+                e.to_string(),
+            )),
+        }
+    }
+
+    fn exclude_set(&self, output: &mut TerminalOutput) -> GlobSet {
+        let mut builder = GlobSetBuilder::new();
+
+        for pattern in &self.exclude {
+            match Glob::new(pattern) {
+                Ok(glob) => {
+                    builder.add(glob);
+                }
+                Err(e) => output.writeln_warning(Message::ExcludePatternIgnored(e.to_string())),
+            }
+        }
+
+        builder.build().unwrap_or(GlobSet::empty())
+    }
+}
+
+impl Command {
+    pub fn generate_args(&self) -> Option<&GenerateArgs> {
+        match self {
+            Command::Build(build) => Some(&build.generate),
+            Command::Watch(watch) => Some(&watch.build.generate),
+            Command::Daemon(daemon) => Some(&daemon.generate),
+            _ => None,
+        }
+    }
+}
+
+impl GenerateArgs {
+    fn resolve_conflicts(&mut self, matches: &ArgMatches) {
+        if prefer_first(matches, "no_header", "header_file") {
+            self.header_file = None;
+        } else {
+            self.no_header = false;
+        }
+    }
+
+    fn apply_config(&mut self, config: &Config, explicit: &HashSet<String>) {
+        let flag = |flag: &mut bool, id, value| config_flag(flag, id, value, explicit);
+
+        self.out_dir = self.out_dir.take().or(config.out_dir.clone());
+        flag(&mut self.mod_files, "mod_files", config.mod_files);
+        if self.header_file.is_none() {
+            flag(
+                &mut self.no_header,
+                "no_header",
+                config.header.map(|header| !header),
+            );
+        }
+        if !self.no_header {
+            self.header_file = self.header_file.take().or(config.header_file.clone());
+        }
+        self.license_file = self.license_file.take().or(config.license_file.clone());
+        flag(
+            &mut self.no_timestamp,
+            "no_timestamp",
+            config.timestamp.map(|t| !t),
+        );
+        self.split_output = self.split_output.or(config.split_output);
+        self.output_encoding = self.output_encoding.or(config.output_encoding);
+        self.rewrite_strings = self.rewrite_strings.or(config.rewrite_strings);
+        self.comment_style = self.comment_style.or(config.comment_style);
+        self.control_characters = self.control_characters.or(config.control_characters);
+        flag(&mut self.reject_bidi, "reject_bidi", config.reject_bidi);
+        flag(&mut self.skip_empty, "skip_empty", config.skip_empty);
+        flag(
+            &mut self.trim_trailing_whitespace,
+            "trim_trailing_whitespace",
+            config.trim_trailing_whitespace,
+        );
+        flag(
+            &mut self.ensure_final_newline,
+            "ensure_final_newline",
+            config.ensure_final_newline,
+        );
+        flag(&mut self.rustfmt, "rustfmt", config.rustfmt);
+        flag(
+            &mut self.verify_output,
+            "verify_output",
+            config.verify_output,
+        );
+        flag(&mut self.reproducible, "reproducible", config.reproducible);
+        self.cache_dir = self.cache_dir.take().or(config.cache_dir.clone());
+        flag(
+            &mut self.cache_read_only,
+            "cache_read_only",
+            config.cache_read_only,
+        );
+        self.cache_url = self.cache_url.take().or(config.cache_url.clone());
+        self.backup = self.backup.take().or(config.backup.clone());
+        self.backup_dir = self.backup_dir.take().or(config.backup_dir.clone());
+        self.backup_count = self.backup_count.or(config.backup_count);
+    }
+
+    pub fn header_file(&self) -> Option<&Path> {
+        self.header_file.as_deref()
+    }
+
+    pub fn license_file(&self) -> Option<&Path> {
+        self.license_file.as_deref()
+    }
+
+    pub fn header_enabled(&self) -> bool {
+        !self.no_header
+    }
+
+    pub fn timestamp_enabled(&self) -> bool {
+        !self.no_timestamp && !self.reproducible
+    }
+
+    pub fn fix_indent(&self) -> Option<IndentStyle> {
+        self.fix_indent
+    }
+
+    pub fn split_output(&self) -> Option<usize> {
+        self.split_output
+    }
+
+    pub fn output_encoding(&self) -> OutputEncoding {
+        self.output_encoding.unwrap_or_default()
+    }
+
+    pub fn rewrite_strings(&self) -> Option<StringStyle> {
+        self.rewrite_strings
+    }
+
+    pub fn comment_style(&self) -> Option<CommentStyle> {
+        self.comment_style
+    }
+
+    pub fn control_characters(&self) -> Option<ControlCharacters> {
+        self.control_characters
+    }
+
+    pub fn reject_bidi(&self) -> bool {
+        self.reject_bidi
+    }
+
+    pub fn skip_empty(&self) -> bool {
+        self.skip_empty
+    }
+
+    pub fn trim_trailing_whitespace(&self) -> bool {
+        self.trim_trailing_whitespace
+    }
+
+    pub fn ensure_final_newline(&self) -> bool {
+        self.ensure_final_newline
+    }
+
+    pub fn verify_output(&self) -> bool {
+        self.verify_output
+    }
+
+    pub fn reproducible(&self) -> bool {
+        self.reproducible
+    }
+
+    pub fn cache_dir(&self) -> Option<&Path> {
+        self.cache_dir.as_deref()
+    }
+
+    pub fn cache_url(&self) -> Option<&str> {
+        self.cache_url.as_deref()
+    }
+
+    pub fn backup(&self) -> Option<&str> {
+        self.backup.as_deref()
+    }
+
+    pub fn backup_dir(&self) -> Option<&Path> {
+        self.backup_dir.as_deref()
+    }
+
+    pub fn backup_count(&self) -> usize {
+        self.backup_count.unwrap_or(1)
+    }
+
+    pub fn cache_token(&self) -> Option<&str> {
+        self.cache_token.as_deref()
+    }
+
+    pub fn cache_read_only(&self) -> bool {
+        self.cache_read_only
+    }
+
+    // options that change the generated files (or whether they are verified), which are part
+    // of the key of the cached files
+    pub fn fingerprint(&self, header: &Header) -> String {
+        format!(
+            "header={:?} fix-indent={:?} strings={:?} comments={:?} control={:?} reject-bidi={} trim={} final-newline={} rustfmt={:?} verify={} verify-syntax={} reproducible={}",
+            header.fingerprint(),
+            self.fix_indent,
+            self.rewrite_strings,
+            self.comment_style,
+            self.control_characters,
+            self.reject_bidi,
+            self.trim_trailing_whitespace,
+            self.ensure_final_newline,
+            self.rustfmt(),
+            self.verify_output,
+            self.verify_syntax(),
+            self.reproducible,
+        )
+    }
+
+    #[cfg(feature = "syn")]
+    pub fn verify_syntax(&self) -> bool {
+        self.verify_syntax
+    }
+
+    #[cfg(not(feature = "syn"))]
+    pub fn verify_syntax(&self) -> bool {
+        false
+    }
+
+    // rustfmt executable, when the generated files are formatted
+    pub fn rustfmt(&self) -> Option<&Path> {
+        match &self.rustfmt_path {
+            _ if !self.rustfmt => None,
+            Some(path) => Some(path),
+            None => Some(Path::new("rustfmt")),
+        }
+    }
+}
+
+impl TokensArgs {
+    fn resolve_conflicts(&mut self, matches: &ArgMatches) {
+        self.input.merge_files();
+        if !prefer_first(matches, "plain", "level") {
+            self.plain = false;
+        }
+    }
+}
+
+impl InstallHookArgs {
+    fn resolve_conflicts(&mut self, matches: &ArgMatches) {
+        if prefer_first(matches, "uninstall", "force") {
+            self.force = false;
+        } else {
+            self.uninstall = false;
+        }
+    }
+}
+
+impl BuildArgs {
+    fn resolve_conflicts(&mut self, matches: &ArgMatches) {
+        self.input.merge_files();
+        self.generate.resolve_conflicts(matches);
+    }
+
+    fn apply_config(&mut self, config: Config, explicit: &HashSet<String>) {
+        self.generate.apply_config(&config, explicit);
+        self.input.apply_config(config, explicit);
+    }
+
+    // directory locked while the files are generated
+    pub fn lock_dir(&self) -> PathBuf {
+        match &self.generate.out_dir {
+            Some(dir) => dir.clone(),
+            None => self.input.working_dir(),
+        }
+    }
+
+    // directories whose generated files are declared by mod.rs files
+    pub fn output_roots(&self) -> Vec<PathBuf> {
+        match &self.generate.out_dir {
+            Some(dir) => vec![dir.clone()],
+            None => self.input.roots(),
+        }
+    }
+
+    pub fn output_file(&self, input: &Path) -> Option<PathBuf> {
+        let mut file = match (archive_input::split(input), &self.generate.out_dir) {
+            // the output of a remote file is named after the end of its URL
+            _ if remote_input::is_url(input) => self
+                .generate
+                .out_dir
+                .clone()
+                .unwrap_or_default()
+                .join(remote_input::file_name(input)?),
+            // the outputs of the files of an archive are written next to it, unless there is an
+            // output directory
+            (Some((_, entry)), Some(dir)) => dir.join(entry),
+            (Some((archive, entry)), None) => archive.parent()?.join(entry),
+            (None, dir) => self.output_base(input, dir.as_deref())?,
+        };
+
+        // the suffix depends on the extension of the file, not on the fragment of a URL
+        let suffix = self.input.output_suffix(&file);
+        let mut name = file.file_stem()?.to_os_string();
+        name.push(suffix);
+        file.set_file_name(name);
+        Some(file)
+    }
+
+    fn output_base(&self, input: &Path, out_dir: Option<&Path>) -> Option<PathBuf> {
+        let file = match out_dir {
+            Some(dir) => match self.input.root_of(input) {
+                Some(root) => dir.join(input.strip_prefix(root).ok()?),
+                None => dir.join(input.file_name()?),
+            },
+            None => input.to_path_buf(),
+        };
+
+        Some(file)
+    }
+
+    pub fn generate_mod_files(&self) -> bool {
+        self.generate.mod_files && self.input.scans_directory()
+    }
+}
+
+fn is_hidden(name: &OsStr) -> bool {
+    name.as_encoded_bytes().starts_with(b".")
+}
+
+fn is_valid_seconds(seconds: f64) -> bool {
+    seconds.is_finite() && seconds > 0.0 && seconds < 1e9
+}
+
+fn parse_seconds(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(seconds) if is_valid_seconds(seconds) => Ok(seconds),
+        _ => Err("expected a positive number of seconds".to_string()),
+    }
+}
+
+fn parse_positive(value: &str) -> Result<usize, String> {
+    match value.parse::<usize>() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err("expected a positive number".to_string()),
+    }
+}
+
+// a boolean of xr.toml only applies when the option is given neither in the command line nor in
+// the environment, so that both can turn it off
+fn config_flag(flag: &mut bool, id: &str, value: Option<bool>, explicit: &HashSet<String>) {
+    if let (false, Some(value)) = (explicit.contains(id), value) {
+        *flag = value;
+    }
+}
+
+fn from_command_line(matches: &ArgMatches, id: &str) -> bool {
+    matches.value_source(id) == Some(ValueSource::CommandLine)
+}
+
+// the options of xr build given before a subcommand would otherwise be silently ignored
+fn reject_build_args_before_subcommand(matches: &ArgMatches, subcommand: &str) {
+    let build = <BuildArgs as clap::Args>::augment_args(clap::Command::new("build"));
+
+    for arg in build.get_arguments() {
+        if from_command_line(matches, arg.get_id().as_str()) {
+            Cli::command()
+                .error(
+                    ErrorKind::ArgumentConflict,
+                    format!(
+                        "the argument '{}' must be given after the subcommand '{subcommand}'",
+                        arg.get_long()
+                            .map(|l| format!("--{l}"))
+                            .unwrap_or_else(|| arg.get_id().to_string())
+                    ),
+                )
+                .exit();
+        }
+    }
+}
+
+// Conflicting options cannot both be given in the command line, but a value given in the
+// command line overrides a conflicting one obtained from the environment.
+fn prefer_first(matches: &ArgMatches, first: &str, second: &str) -> bool {
+    let explicit = |id| {
+        !matches!(
+            matches.value_source(id),
+            None | Some(ValueSource::DefaultValue)
+        )
+    };
+    let first_present = explicit(first);
+    let second_present = explicit(second);
+
+    if first_present && second_present {
+        match (
+            from_command_line(matches, first),
+            from_command_line(matches, second),
+        ) {
+            (true, true) => Cli::command()
+                .error(
+                    ErrorKind::ArgumentConflict,
+                    format!(
+                        "the arguments '--{}' and '--{}' cannot be used together",
+                        first.replace('_', "-"),
+                        second.replace('_', "-")
+                    ),
+                )
+                .exit(),
+            (false, true) => false,
+            _ => true,
+        }
+    } else {
+        first_present
+    }
+}
+
+fn skipped_entry_message(e: &Error) -> Message {
+    let path = match e.path() {
+        Some(p) => p.display().to_string(),
+        None => String::from("?"),
+    };
+
+    if let Some(ancestor) = e.loop_ancestor() {
+        Message::SkippedLoop(path, ancestor.display().to_string())
+    } else if let Some(error) = e.io_error() {
+        Message::SkippedIo(path, error.to_string())
+    } else {
+        Message::Skipped(path)
     }
 }
@@ -0,0 +1,123 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use std::{
+    cell::RefCell,
+    ffi::OsString,
+    fs, io,
+    path::{Component, Path, PathBuf},
+};
+
+use crate::{header::Header, messages::Message};
+
+struct Backups {
+    suffix: String,
+    dir: Option<PathBuf>,
+    count: usize,
+    header: Header,
+}
+
+thread_local! {
+    static BACKUPS: RefCell<Option<Backups>> = const { RefCell::new(None) };
+}
+
+pub fn enable(
+    suffix: Option<&str>,
+    dir: Option<&Path>,
+    count: usize,
+    header: &Header,
+) -> Result<(), Message> {
+    let suffix = suffix.unwrap_or_default();
+    // next to the file, a backup without a suffix would be the file itself
+    if suffix.is_empty() && dir.is_none() {
+        return Err(Message::BackupSuffixEmpty);
+    }
+
+    BACKUPS.with(|b| {
+        *b.borrow_mut() = Some(Backups {
+            suffix: suffix.to_string(),
+            dir: dir.map(Path::to_path_buf),
+            count,
+            header: header.clone(),
+        })
+    });
+    Ok(())
+}
+
+// Saves the contents of a file about to be replaced by different contents, moving its older
+// backups from name to name.1, name.2 and so on, and dropping the ones beyond the count.
+pub fn save(path: &Path, contents: &[u8]) -> io::Result<()> {
+    BACKUPS.with(|b| {
+        let Some(backups) = &*b.borrow() else {
+            return Ok(());
+        };
+        let Ok(existing) = fs::read(path) else {
+            return Ok(());
+        };
+        // a new timestamp alone does not make the previous contents worth keeping
+        let header = &backups.header;
+        if header.without_timestamp(&existing) == header.without_timestamp(contents) {
+            return Ok(());
+        }
+
+        let backup = backups.path(path);
+        for n in (1..backups.count).rev() {
+            let older = numbered(&backup, n - 1);
+            if older.exists() {
+                fs::rename(older, numbered(&backup, n))?;
+            }
+        }
+
+        if let Some(dir) = backup.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&backup, existing)
+    })
+}
+
+impl Backups {
+    fn path(&self, file: &Path) -> PathBuf {
+        // in a directory, the backups keep the relative path of the file
+        let mut name = match &self.dir {
+            Some(dir) => dir
+                .join(
+                    file.components()
+                        .filter(|c| matches!(c, Component::Normal(_)))
+                        .collect::<PathBuf>(),
+                )
+                .into_os_string(),
+            None => file.as_os_str().to_owned(),
+        };
+        name.push(&self.suffix);
+        PathBuf::from(name)
+    }
+}
+
+fn numbered(backup: &Path, n: usize) -> PathBuf {
+    if n == 0 {
+        return backup.to_path_buf();
+    }
+
+    let mut name = OsString::from(backup);
+    name.push(format!(".{n}"));
+    PathBuf::from(name)
+}
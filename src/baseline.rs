@@ -0,0 +1,146 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, path::Path};
+
+use crate::{diagnostics::Diagnostic, messages::Message};
+
+const BASELINE_VERSION: u32 = 1;
+
+// maximum number of characters of the offending text used to identify a diagnostic
+const CONTEXT_LENGTH: usize = 80;
+
+#[derive(Serialize, Deserialize)]
+struct BaselineEntry {
+    file: String,
+    code: String,
+    fingerprint: String,
+    count: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+struct BaselineFile {
+    version: u32,
+    diagnostics: Vec<BaselineEntry>,
+}
+
+type Key = (String, String, String);
+
+#[derive(Default)]
+pub struct Baseline {
+    counts: BTreeMap<Key, usize>,
+}
+
+// FNV-1a is used because, unlike the standard library hashers, it is stable across releases
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf29ce484222325, |hash, b| {
+        (hash ^ u64::from(b)).wrapping_mul(0x100000001b3)
+    })
+}
+
+// Line numbers are deliberately left out so that the fingerprint survives unrelated edits
+fn fingerprint(diagnostic: &Diagnostic) -> String {
+    let first_line = diagnostic.text.lines().next().unwrap_or_default();
+    let context: String = first_line
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .chars()
+        .take(CONTEXT_LENGTH)
+        .collect();
+
+    format!("{:016x}", fnv1a(&context))
+}
+
+fn key(file: &str, diagnostic: &Diagnostic) -> Key {
+    (
+        file.to_string(),
+        diagnostic.code.to_string(),
+        fingerprint(diagnostic),
+    )
+}
+
+impl Baseline {
+    pub fn load(path: &Path) -> Result<Baseline, Message> {
+        let filename = path.display().to_string();
+
+        let contents = fs::read_to_string(path)
+            .map_err(|e| Message::BaselineUnreadable(filename.clone(), e.to_string()))?;
+
+        let file: BaselineFile = serde_json::from_str(&contents)
+            .map_err(|e| Message::BaselineInvalid(filename.clone(), e.to_string()))?;
+
+        if file.version != BASELINE_VERSION {
+            return Err(Message::BaselineInvalid(
+                filename,
+                format!("unsupported version {}", file.version),
+            ));
+        }
+
+        let counts = file
+            .diagnostics
+            .into_iter()
+            .map(|e| ((e.file, e.code, e.fingerprint), e.count))
+            .collect();
+
+        Ok(Baseline { counts })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Message> {
+        let file = BaselineFile {
+            version: BASELINE_VERSION,
+            diagnostics: self
+                .counts
+                .iter()
+                .map(|((file, code, fingerprint), count)| BaselineEntry {
+                    file: file.clone(),
+                    code: code.clone(),
+                    fingerprint: fingerprint.clone(),
+                    count: *count,
+                })
+                .collect(),
+        };
+
+        let filename = path.display().to_string();
+
+        let json = serde_json::to_string_pretty(&file)
+            .map_err(|e| Message::BaselineInvalid(filename.clone(), e.to_string()))?;
+
+        fs::write(path, json + "\n").map_err(|_| Message::FileWriteFailed(filename))
+    }
+
+    pub fn record(&mut self, file: &str, diagnostic: &Diagnostic) {
+        *self.counts.entry(key(file, diagnostic)).or_default() += 1;
+    }
+
+    // Returns true (consuming one occurrence) when the diagnostic is already known
+    pub fn suppress(&mut self, file: &str, diagnostic: &Diagnostic) -> bool {
+        match self.counts.get_mut(&key(file, diagnostic)) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                true
+            }
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,77 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use crate::{
+    diagnostics::Diagnostic,
+    error_codes::ErrorCode,
+    messages::Message,
+    parser::{Sequence, Span, Token},
+};
+
+// Characters that change the direction of the text, which can make code look different from
+// what the compiler sees (CVE-2021-42574, "Trojan Source"). These are the ones rustc rejects.
+const CODEPOINTS: [char; 9] = [
+    '\u{202A}', '\u{202B}', '\u{202C}', '\u{202D}', '\u{202E}', '\u{2066}', '\u{2067}', '\u{2068}',
+    '\u{2069}',
+];
+
+pub fn is_bidi_control(c: char) -> bool {
+    CODEPOINTS.contains(&c)
+}
+
+// Bidirectional control characters in string and char literals and in comments, one
+// diagnostic per character
+pub fn lint<'a>(tokens: &[Sequence<'a, Token>]) -> Vec<Diagnostic<'a>> {
+    let mut diagnostics = Vec::new();
+    let mut line_number = 1;
+
+    for t in tokens {
+        if let Some(number) = t.token.line_after() {
+            line_number = number;
+        }
+
+        let message: fn(char) -> Message = match t.token {
+            Token::StrLiteral | Token::CharLiteral => Message::BidiInLiteral,
+            Token::SingleLineComment | Token::MultiLineComment => Message::BidiInComment,
+            _ => continue,
+        };
+
+        for (i, c) in t.text.char_indices().filter(|(_, c)| is_bidi_control(*c)) {
+            diagnostics.push(Diagnostic {
+                code: ErrorCode::W0010,
+                message: message(c).to_string(),
+                line: line_number + t.text[..i].matches('\n').count(),
+                span: Span {
+                    start: t.span.start + i,
+                    end: t.span.start + i + c.len_utf8(),
+                },
+                text: &t.text[i..i + c.len_utf8()],
+            });
+        }
+    }
+
+    diagnostics
+}
+
+pub fn contains_bidi_control(tokens: &[Sequence<Token>]) -> bool {
+    !lint(tokens).is_empty()
+}
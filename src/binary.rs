@@ -0,0 +1,62 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use std::str;
+
+// only the beginning of the file is examined, as binary formats show it in their first bytes
+const SAMPLE_LEN: usize = 8192;
+
+// a source file may have a few stray NULs or invalid bytes, which are reported as invalid
+// tokens, but in an archive or executable they are a large share of the bytes
+const MAX_SUSPICIOUS_PERCENT: usize = 10;
+
+// whether the contents look like those of a binary file rather than text, judging by the
+// NUL bytes and invalid UTF-8 sequences at its beginning
+pub fn looks_binary(bytes: &[u8]) -> bool {
+    let sample = &bytes[..bytes.len().min(SAMPLE_LEN)];
+    let suspicious = nul_bytes(sample) + invalid_utf8_bytes(sample);
+
+    suspicious * 100 > sample.len() * MAX_SUSPICIOUS_PERCENT
+}
+
+fn nul_bytes(sample: &[u8]) -> usize {
+    sample.iter().filter(|&&b| b == 0).count()
+}
+
+fn invalid_utf8_bytes(mut sample: &[u8]) -> usize {
+    let mut invalid = 0;
+
+    loop {
+        match str::from_utf8(sample) {
+            Ok(_) => return invalid,
+            Err(e) => {
+                // a sequence cut at the end of the sample is not counted
+                let Some(len) = e.error_len() else {
+                    return invalid;
+                };
+
+                invalid += len;
+                sample = &sample[e.valid_up_to() + len..];
+            }
+        }
+    }
+}
@@ -0,0 +1,248 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use std::{
+    cell::RefCell,
+    fs,
+    path::{Path, PathBuf},
+    process,
+};
+#[cfg(feature = "remote")]
+use std::{io::Read, time::Duration};
+
+use sha2::{Digest, Sha256};
+
+use crate::{messages::Message, terminal_helper::TerminalOutput};
+
+#[cfg(feature = "remote")]
+const REMOTE_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Generated files stored by the hash of everything they depend on (the XR file, the options
+// that change the output and the version of xr), so that a directory or server shared between
+// machines can be used by all of them. Only files generated without any diagnostic are stored,
+// as nothing is reported when a file is taken from the cache.
+struct Cache {
+    dir: Option<PathBuf>,
+    remote: Option<Remote>,
+    read_only: bool,
+    hits: usize,
+}
+
+// Cache served over HTTP, where each entry is read with GET and stored with PUT at the URL of
+// the cache followed by the key. Once the server fails, it is no longer used and the files are
+// generated locally.
+struct Remote {
+    url: String,
+    #[cfg_attr(not(feature = "remote"), allow(dead_code))]
+    token: Option<String>,
+    #[cfg(feature = "remote")]
+    agent: ureq::Agent,
+    error: Option<String>,
+}
+
+#[cfg(feature = "remote")]
+impl Remote {
+    fn request(&self, method: &str, key: &str) -> ureq::Request {
+        let request = self
+            .agent
+            .request(method, &format!("{}/{key}", self.url.trim_end_matches('/')));
+
+        match &self.token {
+            Some(token) => request.set("Authorization", &format!("Bearer {token}")),
+            None => request,
+        }
+    }
+
+    fn get(&mut self, key: &str) -> Option<String> {
+        if self.error.is_some() {
+            return None;
+        }
+
+        match self.request("GET", key).call() {
+            Ok(response) => {
+                let mut text = String::new();
+                response.into_reader().read_to_string(&mut text).ok()?;
+                Some(text)
+            }
+            // a missing entry is not a failure of the server
+            Err(ureq::Error::Status(404, _)) => None,
+            Err(e) => {
+                self.error = Some(e.to_string());
+                None
+            }
+        }
+    }
+
+    fn put(&mut self, key: &str, text: &str) {
+        if self.error.is_none() {
+            if let Err(e) = self.request("PUT", key).send_string(text) {
+                self.error = Some(e.to_string());
+            }
+        }
+    }
+}
+
+// without the HTTP client the server is never reached, which enable reports as its failure
+#[cfg(not(feature = "remote"))]
+impl Remote {
+    fn get(&mut self, _key: &str) -> Option<String> {
+        None
+    }
+
+    fn put(&mut self, _key: &str, _text: &str) {}
+}
+
+thread_local! {
+    static CACHE: RefCell<Option<Cache>> = const { RefCell::new(None) };
+}
+
+pub fn enable(dir: Option<&Path>, url: Option<&str>, token: Option<&str>, read_only: bool) {
+    let remote = url.map(|url| Remote {
+        url: url.to_string(),
+        token: token.map(str::to_string),
+        #[cfg(feature = "remote")]
+        agent: ureq::AgentBuilder::new().timeout(REMOTE_TIMEOUT).build(),
+        error: (!cfg!(feature = "remote")).then(|| Message::RemoteUnsupported.to_string()),
+    });
+
+    CACHE.with(|c| {
+        *c.borrow_mut() = Some(Cache {
+            dir: dir.map(Path::to_path_buf),
+            remote,
+            read_only,
+            hits: 0,
+        })
+    });
+}
+
+pub fn enabled() -> bool {
+    CACHE.with(|c| c.borrow().is_some())
+}
+
+pub fn key(contents: &str, options: &str) -> String {
+    let mut hasher = Sha256::new();
+
+    for part in [env!("CARGO_PKG_VERSION"), options, contents] {
+        hasher.update(part.len().to_le_bytes());
+        hasher.update(part.as_bytes());
+    }
+
+    hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+pub fn sha256(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+// entries are spread over subdirectories, named after the first two digits of the key
+fn entry_path(dir: &Path, key: &str) -> PathBuf {
+    dir.join(&key[..2]).join(key)
+}
+
+pub fn get(key: &str) -> Option<String> {
+    CACHE.with(|c| {
+        let mut cache = c.borrow_mut();
+        let cache = cache.as_mut()?;
+
+        let local = cache
+            .dir
+            .as_ref()
+            .and_then(|dir| fs::read_to_string(entry_path(dir, key)).ok());
+
+        let text = match local {
+            Some(text) => text,
+            None => {
+                let text = cache.remote.as_mut()?.get(key)?;
+                // kept locally, so that it is not downloaded again
+                if !cache.read_only {
+                    if let Some(dir) = &cache.dir {
+                        write_entry(dir, key, &text);
+                    }
+                }
+                text
+            }
+        };
+
+        cache.hits += 1;
+        Some(text)
+    })
+}
+
+pub fn put(key: &str, text: &str) {
+    CACHE.with(|c| {
+        let mut cache = c.borrow_mut();
+        let Some(cache) = cache.as_mut().filter(|c| !c.read_only) else {
+            return;
+        };
+
+        if let Some(dir) = &cache.dir {
+            write_entry(dir, key, text);
+        }
+
+        if let Some(remote) = &mut cache.remote {
+            remote.put(key, text);
+        }
+    });
+}
+
+fn write_entry(cache_dir: &Path, key: &str, text: &str) {
+    let path = entry_path(cache_dir, key);
+    let Some(dir) = path.parent() else {
+        return;
+    };
+
+    // written under a temporary name and then renamed, so that other processes sharing the
+    // cache never read an incomplete entry
+    let temporary = dir.join(format!("{key}.{}", process::id()));
+
+    let written = fs::create_dir_all(dir)
+        .and_then(|_| fs::write(&temporary, text))
+        .and_then(|_| fs::rename(&temporary, &path));
+
+    if written.is_err() {
+        fs::remove_file(&temporary).ok();
+    }
+}
+
+pub fn report(output: &mut TerminalOutput) {
+    CACHE.with(|c| {
+        if let Some(cache) = c.borrow().as_ref() {
+            if let Some(Remote {
+                url,
+                error: Some(e),
+                ..
+            }) = &cache.remote
+            {
+                output.writeln_warning(Message::RemoteCacheFailed(url.clone(), e.clone()));
+            }
+
+            output.writeln_info(Message::CacheHits(cache.hits));
+        }
+    });
+}
@@ -0,0 +1,128 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use std::collections::HashMap;
+
+use xr::comments::{CommentToken, DocStyle};
+
+use crate::{
+    arguments::CommentStyle,
+    formatter,
+    parser::{Sequence, Token},
+};
+
+// Text of the comments that are rewritten in the given style, by offset of the comment. Block
+// comments are only rewritten when nothing follows them on their last line, and when they span
+// several lines, when nothing precedes them on their first line. Line comments that contain the
+// beginning or the end of a block comment are kept, as they would change where it ends.
+pub fn conversions(tokens: &[Sequence<Token>], style: CommentStyle) -> HashMap<usize, String> {
+    let mut conversions = HashMap::new();
+
+    for (i, t) in tokens.iter().enumerate() {
+        let converted = match (style, &t.token) {
+            (CommentStyle::Line, Token::MultiLineComment) if ends_line(tokens, i) => {
+                block_to_line(t.text, indentation(tokens, i))
+            }
+            (CommentStyle::Block, Token::SingleLineComment) => line_to_block(t.text),
+            _ => None,
+        };
+
+        if let Some(converted) = converted {
+            conversions.insert(t.span.start, converted);
+        }
+    }
+
+    conversions
+}
+
+fn ends_line(tokens: &[Sequence<Token>], i: usize) -> bool {
+    match tokens.get(i + 1) {
+        Some(t) if matches!(t.token, Token::Whitespace { .. }) => {
+            formatter::is_line_end(tokens.get(i + 2))
+        }
+        next => formatter::is_line_end(next),
+    }
+}
+
+// whitespace before the token when it is the first one of its line
+fn indentation<'a>(tokens: &[Sequence<'a, Token>], i: usize) -> Option<&'a str> {
+    let line_start = |i: usize| i == 0 || tokens[i - 1].token.line_after().is_some();
+
+    if line_start(i) {
+        Some("")
+    } else if matches!(tokens[i - 1].token, Token::Whitespace { .. }) && line_start(i - 1) {
+        Some(tokens[i - 1].text)
+    } else {
+        None
+    }
+}
+
+// doc comments are kept as doc comments
+fn block_to_line(text: &str, indent: Option<&str>) -> Option<String> {
+    let comment = CommentToken::parse(text).filter(|_| text.ends_with("*/"))?;
+    let marker = match comment.doc {
+        Some(DocStyle::Outer) => "///",
+        Some(DocStyle::Inner) => "//!",
+        None => "//",
+    };
+
+    let mut lines = comment.lines();
+    if lines.len() > 1 {
+        indent?;
+    }
+
+    // the lines with only the end and only the beginning of the comment are dropped
+    if lines.len() > 1 && lines.last().is_some_and(|l| l.is_empty()) {
+        lines.pop();
+    }
+    if lines.len() > 1 && lines[0].is_empty() {
+        lines.remove(0);
+    }
+
+    let lines: Vec<String> = lines
+        .into_iter()
+        .map(|content| match content {
+            "" => marker.to_string(),
+            content => format!("{marker} {content}"),
+        })
+        .collect();
+
+    Some(lines.join(&format!("\n{}", indent.unwrap_or_default())))
+}
+
+fn line_to_block(text: &str) -> Option<String> {
+    let (marker, opener) = match CommentToken::parse(text)?.doc {
+        Some(DocStyle::Outer) => ("///", "/**"),
+        Some(DocStyle::Inner) => ("//!", "/*!"),
+        None => ("//", "/*"),
+    };
+
+    let content = text.strip_prefix(marker)?.trim_end();
+    if content.contains("*/") || content.contains("/*") {
+        return None;
+    }
+
+    match content.strip_prefix(' ').unwrap_or(content) {
+        "" => Some(format!("{opener} */")),
+        content => Some(format!("{opener} {content} */")),
+    }
+}
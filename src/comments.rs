@@ -0,0 +1,315 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use crate::parser::{Sequence, Span, Token};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Placement {
+    /// The comment is on its own line(s), before the token that owns it
+    Leading,
+    /// The comment follows the token that owns it, on the same line
+    Trailing,
+}
+
+/// Association of a comment with the token it documents. Indexes refer to the parsed tokens.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Attachment {
+    pub comment: usize,
+    /// `None` for comments separated by a blank line from any following token, or at the end
+    /// of the file
+    pub owner: Option<usize>,
+    pub placement: Placement,
+}
+
+/// Whether a doc comment documents the item that follows it (`///`, `/**`) or the item that
+/// contains it (`//!`, `/*!`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DocStyle {
+    Outer,
+    Inner,
+}
+
+/// A comment token, whose content is its text without the delimiters.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CommentToken<'a> {
+    pub text: &'a str,
+    /// Whether it is a block comment (`/* */`) rather than a line comment (`//`)
+    pub block: bool,
+    /// `None` for comments that are not doc comments, including `////` and `/***`
+    pub doc: Option<DocStyle>,
+}
+
+// /*** and //// are regular comments, and so is /**/
+fn is_outer_doc(text: &str, marker: &str) -> bool {
+    match text.strip_prefix(marker) {
+        Some(after) => !after.starts_with(&marker[marker.len() - 1..]) && after != "/",
+        None => false,
+    }
+}
+
+impl<'a> CommentToken<'a> {
+    /// Returns `None` when the text does not begin with `//` or `/*`.
+    pub fn parse(text: &'a str) -> Option<CommentToken<'a>> {
+        let block = match text.get(..2)? {
+            "//" => false,
+            "/*" => true,
+            _ => return None,
+        };
+        let (outer, inner) = match block {
+            true => ("/**", "/*!"),
+            false => ("///", "//!"),
+        };
+
+        let doc = if is_outer_doc(text, outer) {
+            Some(DocStyle::Outer)
+        } else if text.starts_with(inner) {
+            Some(DocStyle::Inner)
+        } else {
+            None
+        };
+
+        Some(CommentToken { text, block, doc })
+    }
+
+    /// Each line of the comment without the delimiters, the doc markers and the whitespace
+    /// around them. The lines after the first line of a block comment lose their common
+    /// indentation, or their leading `*` when all of them have one.
+    pub fn lines(&self) -> Vec<&'a str> {
+        let body = match self.doc {
+            Some(_) => &self.text[3..],
+            None => &self.text[2..],
+        };
+        if !self.block {
+            return vec![body.trim_start_matches('/').trim()];
+        }
+
+        // comments that are not terminated end at the end of the file
+        let body = body.strip_suffix("*/").unwrap_or(body);
+        let body = match self.doc {
+            Some(_) => body,
+            None => body.trim_start_matches('*'),
+        };
+        let mut lines = body.split('\n').map(str::trim_end);
+        let first = lines.next().unwrap_or_default().trim();
+        let rest: Vec<&str> = lines.collect();
+
+        // continuation lines are often decorated with a leading *, as in
+        //  * text
+        let decorated = rest
+            .iter()
+            .filter(|l| !l.trim().is_empty())
+            .all(|l| l.trim_start().starts_with('*'));
+        let common = rest
+            .iter()
+            .filter(|l| !l.trim().is_empty())
+            .map(|l| l.len() - l.trim_start().len())
+            .min()
+            .unwrap_or(0);
+
+        let rest = rest.into_iter().map(|line| match decorated {
+            true => {
+                let line = line.trim_start();
+                let line = line.strip_prefix('*').unwrap_or(line);
+                line.strip_prefix(' ').unwrap_or(line)
+            }
+            false => line.get(common..).unwrap_or(line.trim_start()),
+        });
+
+        std::iter::once(first).chain(rest).collect()
+    }
+
+    /// Text of the comment without its delimiters and without the blank lines at its start and
+    /// at its end, such as `Returns the sum` for `/**\n * Returns the sum\n */`.
+    pub fn content(&self) -> String {
+        let lines = self.lines();
+        let start = lines
+            .iter()
+            .position(|l| !l.is_empty())
+            .unwrap_or(lines.len());
+        let end = lines
+            .iter()
+            .rposition(|l| !l.is_empty())
+            .map_or(start, |i| i + 1);
+
+        lines[start..end].join("\n")
+    }
+}
+
+impl<'a> Sequence<'a, Token> {
+    /// The comment of a `SingleLineComment` or `MultiLineComment` token, `None` for other
+    /// tokens.
+    pub fn comment(&self) -> Option<CommentToken<'a>> {
+        match is_comment(&self.token) {
+            true => CommentToken::parse(self.text),
+            false => None,
+        }
+    }
+}
+
+/// Words that mark unfinished work in comments.
+pub const MARKERS: [&str; 3] = ["TODO", "FIXME", "HACK"];
+
+/// Marker of unfinished work in a comment, such as `// TODO: handle tabs`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Marker<'a> {
+    /// One of [`MARKERS`]
+    pub word: &'static str,
+    /// Index of the comment in the parsed tokens
+    pub comment: usize,
+    pub line: usize,
+    /// Line of the comment with the marker, without the comment delimiters
+    pub text: &'a str,
+}
+
+fn is_comment(token: &Token) -> bool {
+    matches!(token, Token::SingleLineComment | Token::MultiLineComment)
+}
+
+// tokens other than comments, new lines and whitespace
+fn is_code(t: &Sequence<Token>) -> bool {
+    match t.token {
+        Token::BlankLine(_) | Token::NewLine(_) | Token::Whitespace { .. } => false,
+        Token::Other => !t.text.trim().is_empty(),
+        ref token => !is_comment(token),
+    }
+}
+
+// last code token before the comment, if it is on the same line
+fn previous_on_same_line(tokens: &[Sequence<Token>], comment: usize) -> Option<usize> {
+    for i in (0..comment).rev() {
+        let t = &tokens[i];
+
+        if t.token.line_after().is_some() || t.text.contains('\n') {
+            return None;
+        }
+
+        if is_code(t) {
+            return Some(i);
+        }
+    }
+
+    None
+}
+
+// next code token, unless a blank line separates it from the comment
+fn next_without_blank_line(tokens: &[Sequence<Token>], comment: usize) -> Option<usize> {
+    let mut new_lines = 0;
+
+    for (i, t) in tokens.iter().enumerate().skip(comment + 1) {
+        if is_code(t) {
+            return Some(i);
+        }
+
+        if is_comment(&t.token) {
+            new_lines = 0;
+        } else {
+            new_lines += t.text.matches('\n').count();
+        }
+
+        if new_lines > 1 {
+            return None;
+        }
+    }
+
+    None
+}
+
+/// Attaches each comment to the token it belongs to: a comment after code on the same line
+/// trails that code, any other comment leads the next code token.
+pub fn attach(tokens: &[Sequence<Token>]) -> Vec<Attachment> {
+    tokens
+        .iter()
+        .enumerate()
+        .filter(|(_, t)| is_comment(&t.token))
+        .map(|(i, _)| match previous_on_same_line(tokens, i) {
+            Some(owner) => Attachment {
+                comment: i,
+                owner: Some(owner),
+                placement: Placement::Trailing,
+            },
+            None => Attachment {
+                comment: i,
+                owner: next_without_blank_line(tokens, i),
+                placement: Placement::Leading,
+            },
+        })
+        .collect()
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+// markers that appear as whole words, so that TODOS or FIXMED do not count
+fn markers_in_line(line: &str) -> impl Iterator<Item = &'static str> + '_ {
+    MARKERS.into_iter().filter(move |word| {
+        line.match_indices(word).any(|(i, _)| {
+            let before = line[..i].chars().next_back();
+            let after = line[i + word.len()..].chars().next();
+            !before.is_some_and(is_word_char) && !after.is_some_and(is_word_char)
+        })
+    })
+}
+
+/// Finds the TODO, FIXME and HACK markers of the comments, in order.
+pub fn markers<'a>(tokens: &[Sequence<'a, Token>]) -> Vec<Marker<'a>> {
+    let mut markers = Vec::new();
+    let mut line_number = 1;
+
+    for (i, t) in tokens.iter().enumerate() {
+        if let Some(comment) = t.comment() {
+            for (offset, line) in comment.lines().into_iter().enumerate() {
+                markers.extend(markers_in_line(line).map(|word| Marker {
+                    word,
+                    comment: i,
+                    line: line_number + offset,
+                    text: line.trim(),
+                }));
+            }
+        }
+
+        match t.token.line_after() {
+            Some(number) => line_number = number,
+            None => line_number += t.text.matches('\n').count(),
+        }
+    }
+
+    markers
+}
+
+/// Comments owned by the token at the given index.
+pub fn comments_of(attachments: &[Attachment], token: usize) -> impl Iterator<Item = &Attachment> {
+    attachments.iter().filter(move |a| a.owner == Some(token))
+}
+
+/// Span of a token extended to cover the comments it owns, so that moving that span moves the
+/// token together with its comments.
+pub fn extended_span(tokens: &[Sequence<Token>], attachments: &[Attachment], token: usize) -> Span {
+    comments_of(attachments, token).fold(tokens[token].span, |span, a| {
+        let comment = tokens[a.comment].span;
+        Span {
+            start: span.start.min(comment.start),
+            end: span.end.max(comment.end),
+        }
+    })
+}
@@ -0,0 +1,139 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use serde::Deserialize;
+
+use crate::{
+    arguments::{
+        CommentStyle, ControlCharacters, FailOn, FileOrder, OutputEncoding, ScanExtension,
+        StringStyle,
+    },
+    error_codes::ErrorCode,
+    lint_levels::LintLevel,
+    messages::Message,
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::{Path, PathBuf},
+};
+
+pub const CONFIG_FILE_NAME: &str = "xr.toml";
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Config {
+    pub out_dir: Option<PathBuf>,
+    pub exclude: Vec<String>,
+    pub mod_files: Option<bool>,
+    pub header_file: Option<PathBuf>,
+    pub license_file: Option<PathBuf>,
+    pub header: Option<bool>,
+    pub timestamp: Option<bool>,
+    pub split_output: Option<usize>,
+    pub output_encoding: Option<OutputEncoding>,
+    pub rewrite_strings: Option<StringStyle>,
+    pub comment_style: Option<CommentStyle>,
+    pub control_characters: Option<ControlCharacters>,
+    pub reject_bidi: Option<bool>,
+    pub skip_empty: Option<bool>,
+    pub trim_trailing_whitespace: Option<bool>,
+    pub ensure_final_newline: Option<bool>,
+    pub rustfmt: Option<bool>,
+    pub verify_output: Option<bool>,
+    pub reproducible: Option<bool>,
+    pub cache_dir: Option<PathBuf>,
+    pub cache_read_only: Option<bool>,
+    pub cache_url: Option<String>,
+    pub backup: Option<String>,
+    pub backup_dir: Option<PathBuf>,
+    pub backup_count: Option<usize>,
+    pub sort: Option<bool>,
+    pub order: Option<FileOrder>,
+    pub max_depth: Option<usize>,
+    pub extensions: Vec<ScanExtension>,
+    pub assume_xr: Option<bool>,
+    pub hidden: Option<bool>,
+    pub allow_remote: Option<bool>,
+    pub remote_cache: Option<PathBuf>,
+    pub max_file_size: Option<u64>,
+    pub max_tokens: Option<usize>,
+    pub max_comment_depth: Option<usize>,
+    pub max_raw_string_hashes: Option<usize>,
+    pub timeout: Option<f64>,
+    pub fail_on: Option<FailOn>,
+    pub max_string_length: Option<usize>,
+    pub disallowed_in_strings: Vec<String>,
+    pub deny_non_printable: Option<bool>,
+    pub max_line_length: Option<usize>,
+    pub allow_long_literals: Option<bool>,
+    pub spell_check: Option<bool>,
+    pub dictionaries: Vec<PathBuf>,
+    pub spelling_words: Vec<String>,
+    pub lints: BTreeMap<String, LintLevel>,
+}
+
+impl Config {
+    pub fn discover(start: &Path) -> Option<PathBuf> {
+        let start = start.canonicalize().unwrap_or(start.to_path_buf());
+
+        start
+            .ancestors()
+            .map(|dir| dir.join(CONFIG_FILE_NAME))
+            .find(|file| file.is_file())
+    }
+
+    pub fn load(path: &Path) -> Result<Config, Message> {
+        let filename = path.display().to_string();
+
+        let contents = fs::read_to_string(path)
+            .map_err(|e| Message::ConfigurationUnreadable(filename.clone(), e.to_string()))?;
+
+        let mut config: Config = toml::from_str(&contents)
+            .map_err(|e| Message::ConfigurationInvalid(filename.clone(), e.to_string()))?;
+
+        if let Some(code) = config.lints.keys().find(|c| ErrorCode::parse(c).is_none()) {
+            let error = Message::UnknownErrorCode(code.clone()).to_string();
+            return Err(Message::ConfigurationInvalid(filename, error));
+        }
+
+        // paths are relative to the directory containing the configuration file
+        if let Some(dir) = path.parent() {
+            config.out_dir = config.out_dir.map(|p| dir.join(p));
+            config.header_file = config.header_file.map(|p| dir.join(p));
+            config.license_file = config.license_file.map(|p| dir.join(p));
+            config.cache_dir = config.cache_dir.map(|p| dir.join(p));
+            config.backup_dir = config.backup_dir.map(|p| dir.join(p));
+            config.remote_cache = config.remote_cache.map(|p| dir.join(p));
+            config.dictionaries = config.dictionaries.iter().map(|p| dir.join(p)).collect();
+        }
+
+        Ok(config)
+    }
+
+    pub fn lint_levels(&self) -> HashMap<ErrorCode, LintLevel> {
+        self.lints
+            .iter()
+            .filter_map(|(code, level)| Some((ErrorCode::parse(code)?, *level)))
+            .collect()
+    }
+}
@@ -0,0 +1,170 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use std::collections::HashMap;
+
+use unicode_security::{
+    confusable_detection::skeleton, mixed_script::AugmentedScriptSet, MixedScript,
+};
+
+use crate::{
+    diagnostics::Diagnostic,
+    error_codes::ErrorCode,
+    messages::Message,
+    parser::{Sequence, Span, Token},
+};
+
+struct Identifier<'a> {
+    text: &'a str,
+    line: usize,
+    span: Span,
+}
+
+// identifiers of the code with characters other than ASCII, as only those can mix scripts or be
+// confused with other identifiers
+fn identifiers<'a>(tokens: &[Sequence<'a, Token>]) -> Vec<Identifier<'a>> {
+    let mut identifiers = Vec::new();
+    let mut line_number = 1;
+
+    for t in tokens {
+        if let Some(number) = t.token.line_after() {
+            line_number = number;
+        }
+        if t.token != Token::Other {
+            continue;
+        }
+
+        let mut start = None;
+        for (i, c) in t.text.char_indices().chain([(t.text.len(), ' ')]) {
+            match (start, c.is_alphanumeric() || c == '_') {
+                (None, true) => start = Some(i),
+                (Some(s), false) => {
+                    let text = &t.text[s..i];
+                    if !text.is_ascii() && !text.starts_with(|c: char| c.is_numeric()) {
+                        identifiers.push(Identifier {
+                            text,
+                            line: line_number,
+                            span: Span {
+                                start: t.span.start + s,
+                                end: t.span.start + i,
+                            },
+                        });
+                    }
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    identifiers
+}
+
+// names of the scripts of the identifier, in the order in which they appear
+fn scripts(identifier: &str) -> Vec<String> {
+    let mut scripts = Vec::new();
+
+    for c in identifier.chars() {
+        let set = AugmentedScriptSet::for_char(c);
+        if set.is_all() {
+            continue;
+        }
+        if let Some(script) = set.base.iter().next().map(|s| s.full_name().to_string()) {
+            if !scripts.contains(&script) {
+                scripts.push(script);
+            }
+        }
+    }
+
+    scripts
+}
+
+// the identifier with the characters that look like an ASCII character replaced by it
+fn ascii_lookalike(identifier: &str) -> String {
+    identifier
+        .chars()
+        .map(|c| {
+            let shape: String = skeleton(c.encode_utf8(&mut [0; 4])).collect();
+            match shape.chars().next() {
+                Some(ascii) if !c.is_ascii() && shape.len() == 1 && ascii.is_ascii() => ascii,
+                _ => c,
+            }
+        })
+        .collect()
+}
+
+// Identifiers that mix scripts, such as Latin and Cyrillic, and identifiers that look like
+// another identifier of the file or like an ASCII identifier
+pub fn lint<'a>(tokens: &[Sequence<'a, Token>]) -> Vec<Diagnostic<'a>> {
+    let mut diagnostics = Vec::new();
+    let mut skeletons = HashMap::new();
+
+    for t in tokens.iter().filter(|t| t.token == Token::Other) {
+        for word in t.text.split(|c: char| !(c.is_alphanumeric() || c == '_')) {
+            if word.is_ascii() && !word.is_empty() {
+                skeletons.entry(skeleton(word).collect()).or_insert(word);
+            }
+        }
+    }
+
+    for identifier in identifiers(tokens) {
+        let mut message = None;
+
+        if !identifier.text.is_single_script() {
+            message = Some(Message::MixedScriptIdentifier(
+                identifier.text.to_string(),
+                scripts(identifier.text).join(", "),
+            ));
+        }
+
+        let shape: String = skeleton(identifier.text).collect();
+        let lookalike = ascii_lookalike(identifier.text);
+        let similar = match skeletons.get(&shape) {
+            Some(other) if *other != identifier.text => Some(other.to_string()),
+            Some(_) => None,
+            None if lookalike.is_ascii() => Some(lookalike),
+            None => None,
+        };
+        skeletons.entry(shape).or_insert(identifier.text);
+
+        for (code, message) in [
+            message.map(|m| (ErrorCode::W0011, m)),
+            similar.map(|other| {
+                let m = Message::ConfusableIdentifier(identifier.text.to_string(), other);
+                (ErrorCode::W0012, m)
+            }),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            diagnostics.push(Diagnostic {
+                code,
+                message: message.to_string(),
+                line: identifier.line,
+                span: identifier.span,
+                text: identifier.text,
+            });
+        }
+    }
+
+    diagnostics
+}
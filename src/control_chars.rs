@@ -0,0 +1,50 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use crate::{arguments::ControlCharacters, parser::is_control};
+
+// Text of a token without its control characters, or None when it has none. When escaping, they
+// are written as escapes in literals that are not raw (\xNN in byte literals) and as \u{NN} in
+// comments, as an escape can't be written anywhere else.
+pub fn clean(text: &str, escape: bool, mode: ControlCharacters) -> Option<String> {
+    if !text.chars().any(is_control) {
+        return None;
+    }
+
+    let literal = text.trim_matches('😀');
+    let raw = ["r", "br", "cr"].iter().any(|p| literal.starts_with(p));
+    let byte = literal.starts_with('b');
+    let escape = escape && mode == ControlCharacters::Escape && !raw;
+
+    let mut cleaned = String::with_capacity(text.len());
+    for c in text.chars() {
+        if !is_control(c) {
+            cleaned.push(c);
+        } else if escape && byte {
+            cleaned.push_str(&format!("\\x{:02x}", c as u32));
+        } else if escape {
+            cleaned.push_str(&format!("\\u{{{:x}}}", c as u32));
+        }
+    }
+
+    Some(cleaned)
+}
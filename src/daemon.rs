@@ -0,0 +1,331 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, Read, Write},
+    os::unix::net::{UnixListener, UnixStream},
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Sender},
+    thread,
+};
+
+use serde::{Deserialize, Serialize};
+use xr::panic_message;
+
+use crate::{
+    arguments::DaemonArgs,
+    diagnostics::{self, OwnedDiagnostic},
+    header::Header,
+    isolation, lint_levels,
+    messages::Message,
+    terminal_helper::TerminalOutput,
+    InputError,
+};
+
+// Requests and replies are JSON documents preceded by their length in bytes, written as a
+// 32-bit big-endian number. Requests only name files, so anything larger is a broken client.
+const MAX_REQUEST_SIZE: usize = 1 << 20;
+
+#[derive(Deserialize)]
+#[serde(tag = "request", rename_all = "kebab-case", deny_unknown_fields)]
+enum Request {
+    Parse { file: PathBuf },
+    Generate { file: PathBuf, output: PathBuf },
+    Shutdown,
+}
+
+#[derive(Serialize, Default)]
+struct Reply {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    diagnostics: Vec<DiagnosticReply>,
+    errors: Vec<String>,
+    warnings: Vec<String>,
+    cached: bool,
+}
+
+#[derive(Serialize, Clone)]
+struct DiagnosticReply {
+    code: String,
+    severity: &'static str,
+    line: usize,
+    message: String,
+}
+
+// The diagnostics of a file are kept until its contents change, so that a parse request for an
+// unchanged file is answered without parsing it again. Generated files are always written anew,
+// unless they are found in the cache (--cache-dir or --cache-url).
+struct Parsed {
+    contents: String,
+    diagnostics: Vec<DiagnosticReply>,
+}
+
+struct Daemon<'a> {
+    args: &'a DaemonArgs,
+    header: &'a Header,
+    parsed: HashMap<PathBuf, Parsed>,
+}
+
+pub fn run(args: &DaemonArgs, header: &Header, output: &mut TerminalOutput) {
+    let socket = args.socket.display().to_string();
+
+    if args.socket.exists() {
+        // a socket nobody is listening on was left behind by a daemon that did not stop cleanly
+        if UnixStream::connect(&args.socket).is_ok() {
+            output.writeln_error(Message::DaemonAlreadyRunning(socket));
+            return;
+        }
+        fs::remove_file(&args.socket).ok();
+    }
+
+    let listener = match UnixListener::bind(&args.socket) {
+        Ok(listener) => listener,
+        Err(e) => {
+            output.writeln_error(Message::DaemonSocketFailed(socket, e.to_string()));
+            return;
+        }
+    };
+
+    output.writeln_info(Message::DaemonListening(socket));
+
+    let mut daemon = Daemon {
+        args,
+        header,
+        parsed: HashMap::new(),
+    };
+
+    // clients are read by threads of their own, but their requests are handled one at a time here
+    let (sender, requests) = mpsc::channel();
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let sender = sender.clone();
+            thread::spawn(move || serve(stream, sender));
+        }
+    });
+
+    for (request, reply) in requests {
+        let stop = matches!(request, Request::Shutdown);
+        reply.send(daemon.handle(request, output)).ok();
+
+        if stop {
+            break;
+        }
+    }
+
+    fs::remove_file(&args.socket).ok();
+    output.writeln_info(Message::DaemonStopped);
+}
+
+fn read_request(stream: &mut impl Read) -> io::Result<Option<Vec<u8>>> {
+    let mut length = [0; 4];
+    match stream.read_exact(&mut length) {
+        Ok(_) => {}
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    }
+
+    let length = u32::from_be_bytes(length) as usize;
+    if length > MAX_REQUEST_SIZE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            Message::DaemonRequestTooLarge(length, MAX_REQUEST_SIZE).to_string(),
+        ));
+    }
+
+    let mut request = vec![0; length];
+    stream.read_exact(&mut request)?;
+    Ok(Some(request))
+}
+
+fn write_reply(stream: &mut impl Write, reply: &Reply) -> io::Result<()> {
+    let json = serde_json::to_vec(reply).map_err(io::Error::from)?;
+    stream.write_all(&(json.len() as u32).to_be_bytes())?;
+    stream.write_all(&json)
+}
+
+fn error_reply(error: Message) -> Reply {
+    Reply {
+        error: Some(error.to_string()),
+        ..Reply::default()
+    }
+}
+
+fn diagnostic_reply(d: &OwnedDiagnostic) -> DiagnosticReply {
+    DiagnosticReply {
+        code: d.code.to_string(),
+        severity: lint_levels::severity(d.code).name(),
+        line: d.line,
+        message: d.message.clone(),
+    }
+}
+
+fn read_input(file: &Path) -> Result<String, Message> {
+    crate::read_input(file).map_err(|(InputError::Skipped(e) | InputError::Failed(e))| e)
+}
+
+// Reads the requests of a client, passing them to the thread that handles them, until the client
+// disconnects or asks the daemon to stop.
+fn serve(mut stream: UnixStream, requests: Sender<(Request, Sender<Reply>)>) {
+    loop {
+        let request = match read_request(&mut stream) {
+            Ok(Some(request)) => request,
+            Ok(None) => return,
+            Err(e) => {
+                write_reply(
+                    &mut stream,
+                    &error_reply(Message::InvalidDaemonRequest(e.to_string())),
+                )
+                .ok();
+                return;
+            }
+        };
+
+        let reply = match serde_json::from_slice::<Request>(&request) {
+            Ok(request) => {
+                let stop = matches!(request, Request::Shutdown);
+                let (sender, reply) = mpsc::channel();
+                if requests.send((request, sender)).is_err() {
+                    return;
+                }
+                match reply.recv() {
+                    Ok(reply) if stop => {
+                        write_reply(&mut stream, &reply).ok();
+                        return;
+                    }
+                    Ok(reply) => reply,
+                    Err(_) => return,
+                }
+            }
+            Err(e) => error_reply(Message::InvalidDaemonRequest(e.to_string())),
+        };
+
+        if write_reply(&mut stream, &reply).is_err() {
+            return;
+        }
+    }
+}
+
+impl Daemon<'_> {
+    fn handle(&mut self, request: Request, output: &mut TerminalOutput) -> Reply {
+        let file = match &request {
+            Request::Parse { file } | Request::Generate { file, .. } => file.clone(),
+            Request::Shutdown => return Reply::default(),
+        };
+
+        output.writeln_info(Message::ProcessingFile(file.display().to_string()));
+
+        let result = isolation::run(|| match request {
+            Request::Generate {
+                file,
+                output: new_file,
+            } => self.generate(&file, &new_file, output),
+            _ => self.parse(&file, output),
+        });
+
+        match result {
+            Ok(Ok(reply)) => reply,
+            Ok(Err(e)) => error_reply(e),
+            Err(payload) => {
+                // the results of a file that made the parser panic are not trusted
+                self.parsed.remove(&file);
+                error_reply(Message::InternalError(
+                    file.display().to_string(),
+                    panic_message(payload.as_ref()).to_string(),
+                ))
+            }
+        }
+    }
+
+    fn parse(&mut self, file: &Path, output: &mut TerminalOutput) -> Result<Reply, Message> {
+        let contents = read_input(file)?;
+        let cached = self.is_cached(file, &contents);
+
+        if !cached {
+            let result = crate::parse_file(&contents, output);
+            let diagnostics = diagnostics::collect(&result)
+                .iter()
+                .map(|d| diagnostic_reply(&d.to_owned()))
+                .collect();
+            self.parsed.insert(
+                file.to_path_buf(),
+                Parsed {
+                    contents,
+                    diagnostics,
+                },
+            );
+        }
+
+        Ok(Reply {
+            diagnostics: self.parsed[file].diagnostics.clone(),
+            cached,
+            ..Reply::default()
+        })
+    }
+
+    // generated as xr build generates it, with the same options, checks and write path
+    fn generate(
+        &mut self,
+        file: &Path,
+        new_file: &Path,
+        output: &mut TerminalOutput,
+    ) -> Result<Reply, Message> {
+        let contents = read_input(file)?;
+        let cached = self.is_cached(file, &contents);
+
+        output.capture();
+        let result = crate::generate_file(
+            file,
+            new_file,
+            contents.clone(),
+            self.header,
+            &self.args.generate,
+            output,
+        );
+        let captured = output.take_captured();
+
+        let diagnostics: Vec<DiagnosticReply> =
+            result.diagnostics.iter().map(diagnostic_reply).collect();
+        self.parsed.insert(
+            file.to_path_buf(),
+            Parsed {
+                contents,
+                diagnostics: diagnostics.clone(),
+            },
+        );
+
+        Ok(Reply {
+            diagnostics,
+            errors: captured.errors,
+            warnings: captured.warnings,
+            cached,
+            ..Reply::default()
+        })
+    }
+
+    fn is_cached(&self, file: &Path, contents: &str) -> bool {
+        self.parsed
+            .get(file)
+            .is_some_and(|parsed| parsed.contents == contents)
+    }
+}
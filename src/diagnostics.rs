@@ -0,0 +1,167 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use xr::line_index::LineIndex;
+
+use crate::{
+    bidi, confusables,
+    error_codes::{ErrorCode, Severity},
+    indentation, lint_levels,
+    messages::Message,
+    parser::{self, Sequence, Span, Token},
+    suppressions,
+    terminal_helper::TerminalOutput,
+};
+
+pub struct Diagnostic<'a> {
+    pub code: ErrorCode,
+    pub message: String,
+    pub line: usize,
+    pub span: Span,
+    pub text: &'a str,
+}
+
+// a diagnostic that outlives the text of its file, kept until the file is reported
+pub struct OwnedDiagnostic {
+    pub code: ErrorCode,
+    pub message: String,
+    pub line: usize,
+}
+
+impl Diagnostic<'_> {
+    pub fn to_owned(&self) -> OwnedDiagnostic {
+        OwnedDiagnostic {
+            code: self.code,
+            message: self.message.clone(),
+            line: self.line,
+        }
+    }
+}
+
+pub fn collect<'a>(tokens: &[Sequence<'a, Token>]) -> Vec<Diagnostic<'a>> {
+    apply_suppressions(tokens, lint(tokens))
+}
+
+// the diagnostics of the tokens, before the suppressions in their comments are applied
+pub fn lint<'a>(tokens: &[Sequence<'a, Token>]) -> Vec<Diagnostic<'a>> {
+    let mut diagnostics = Vec::new();
+    let lines = LineIndex::from_tokens(tokens);
+
+    for t in tokens {
+        match &t.token {
+            Token::Invalid(kind) => diagnostics.push(Diagnostic {
+                code: kind.code(),
+                message: kind.to_string(),
+                line: lines.line(t.span.start),
+                span: t.span,
+                text: t.text,
+            }),
+            // outside of literals and comments, control characters are invalid tokens
+            Token::StrLiteral
+            | Token::CharLiteral
+            | Token::SingleLineComment
+            | Token::MultiLineComment => {
+                for (i, c) in t
+                    .text
+                    .char_indices()
+                    .filter(|(_, c)| parser::is_control(*c))
+                {
+                    diagnostics.push(Diagnostic {
+                        code: ErrorCode::E0015,
+                        message: Message::ControlCharacter(c).to_string(),
+                        line: lines.line(t.span.start + i),
+                        span: Span {
+                            start: t.span.start + i,
+                            end: t.span.start + i + c.len_utf8(),
+                        },
+                        text: &t.text[i..i + c.len_utf8()],
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    diagnostics.extend(indentation::lint(tokens));
+    diagnostics.extend(bidi::lint(tokens));
+    diagnostics.extend(confusables::lint(tokens));
+    diagnostics
+}
+
+// Removes the diagnostics silenced by the xr-ignore comments of the tokens, which must be applied
+// once to the diagnostics of every lint, so that the suppressions that silence nothing are found.
+pub fn apply_suppressions<'a>(
+    tokens: &[Sequence<'a, Token>],
+    mut diagnostics: Vec<Diagnostic<'a>>,
+) -> Vec<Diagnostic<'a>> {
+    let mut suppressions = suppressions::find(tokens);
+    diagnostics.retain(|d| !suppressions.iter_mut().any(|s| s.suppress(d)));
+
+    for s in &suppressions {
+        for code in s.unknown_codes() {
+            diagnostics.push(Diagnostic {
+                code: ErrorCode::W0014,
+                message: Message::UnknownSuppressionCode(code.to_string()).to_string(),
+                line: s.line,
+                span: s.span,
+                text: s.text,
+            });
+        }
+    }
+
+    for s in suppressions.iter().filter(|s| !s.is_used()) {
+        diagnostics.push(Diagnostic {
+            code: ErrorCode::W0001,
+            message: Message::UnusedSuppression.to_string(),
+            line: s.line,
+            span: s.span,
+            text: s.text,
+        });
+    }
+
+    diagnostics.retain(|d| !lint_levels::is_allowed(d.code));
+    diagnostics.sort_by_key(|d| d.span.start);
+    diagnostics
+}
+
+pub fn error_count(diagnostics: &[Diagnostic]) -> usize {
+    diagnostics
+        .iter()
+        .filter(|d| lint_levels::severity(d.code) == Severity::Error)
+        .count()
+}
+
+pub fn report(diagnostics: &[Diagnostic], output: &mut TerminalOutput) {
+    for d in diagnostics {
+        report_one(d.code, d.line, &d.message, output);
+    }
+}
+
+pub fn report_owned(diagnostics: &[OwnedDiagnostic], output: &mut TerminalOutput) {
+    for d in diagnostics {
+        report_one(d.code, d.line, &d.message, output);
+    }
+}
+
+fn report_one(code: ErrorCode, line: usize, message: &str, output: &mut TerminalOutput) {
+    output.diagnostic(code, Some(line), message);
+}
@@ -0,0 +1,188 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use std::{
+    cell::RefCell,
+    fs,
+    io::{self, IsTerminal},
+    mem,
+    path::{Path, PathBuf},
+};
+
+use xr::output_sink::OutputSink;
+
+use crate::{
+    header::Header, manifest, messages::Message, sink, terminal_helper::TerminalOutput, text_diff,
+};
+
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum Mode {
+    // print the diffs and write the files
+    #[default]
+    Print,
+    // print the diffs without writing the files
+    PrintOnly,
+    // print the diffs and ask before overwriting each file
+    Confirm,
+}
+
+struct Change {
+    path: PathBuf,
+    existing: String,
+    generated: String,
+    // written once the overwrite is confirmed
+    contents: Vec<u8>,
+}
+
+#[derive(Default)]
+struct Preview {
+    mode: Mode,
+    changes: Vec<Change>,
+    // the answer to every remaining question, after all or quit
+    answer: Option<bool>,
+}
+
+thread_local! {
+    static PREVIEW: RefCell<Preview> = RefCell::default();
+}
+
+// Keeps the changes to the generated files that already exist, to be printed as diffs once the
+// file they were generated from is processed, and writes the files unless only the diffs are
+// wanted or the overwrites must be confirmed first.
+struct Sink {
+    header: Header,
+}
+
+impl OutputSink for Sink {
+    fn write(&mut self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        let mode = PREVIEW.with(|p| p.borrow().mode);
+        let changed = match fs::read(path) {
+            Ok(existing) => self.record_change(path, &existing, contents),
+            Err(_) => false,
+        };
+
+        match mode {
+            Mode::PrintOnly => {
+                sink::hold_back();
+                Ok(())
+            }
+            // written, and recorded in the manifest, once the overwrite is confirmed
+            Mode::Confirm if changed => {
+                sink::hold_back();
+                Ok(())
+            }
+            Mode::Print | Mode::Confirm => sink::Files.write(path, contents),
+        }
+    }
+}
+
+impl Sink {
+    // keeps the change to an existing file, unless the contents are the same
+    fn record_change(&self, path: &Path, existing: &[u8], contents: &[u8]) -> bool {
+        // a new timestamp alone is not a change worth reviewing
+        let existing = self.header.without_timestamp(existing);
+        let generated = self.header.without_timestamp(contents);
+        if existing == generated {
+            return false;
+        }
+
+        let change = Change {
+            path: path.to_path_buf(),
+            existing: String::from_utf8_lossy(&existing).into_owned(),
+            generated: String::from_utf8_lossy(&generated).into_owned(),
+            contents: contents.to_vec(),
+        };
+        PREVIEW.with(|p| p.borrow_mut().changes.push(change));
+        true
+    }
+}
+
+pub fn enable(mode: Mode, header: &Header) {
+    PREVIEW.with(|p| p.borrow_mut().mode = mode);
+    sink::replace(Box::new(Sink {
+        header: header.clone(),
+    }));
+
+    // without a terminal there is nobody to answer, so nothing is overwritten
+    if mode == Mode::Confirm && !io::stdin().is_terminal() {
+        PREVIEW.with(|p| p.borrow_mut().answer = Some(false));
+    }
+}
+
+pub fn end_file(output: &mut TerminalOutput) {
+    let (mode, changes) = PREVIEW.with(|p| {
+        let mut preview = p.borrow_mut();
+        (preview.mode, mem::take(&mut preview.changes))
+    });
+
+    for change in changes {
+        let path = change.path.display().to_string();
+
+        output.writeln_result(Message::GeneratedFileChanges(path.clone()));
+        text_diff::print(
+            &text_diff::hunks(&change.existing, &change.generated),
+            output,
+        );
+
+        if mode != Mode::Confirm {
+            continue;
+        }
+
+        if !confirm(&path, output) {
+            output.writeln_warning(Message::OverwriteDeclined(path));
+        } else if sink::Files.write(&change.path, &change.contents).is_err() {
+            output.writeln_error(Message::FileWriteFailed(path));
+        } else {
+            manifest::record(&change.path, &change.contents);
+        }
+    }
+}
+
+fn confirm(path: &str, output: &mut TerminalOutput) -> bool {
+    if let Some(answer) = PREVIEW.with(|p| p.borrow().answer) {
+        return answer;
+    }
+
+    loop {
+        output.prompt(Message::ConfirmOverwrite(path.to_string()));
+
+        let mut line = String::new();
+        let answer = match io::stdin().read_line(&mut line) {
+            // the end of the input declines this and every other overwrite
+            Ok(0) | Err(_) => "q",
+            Ok(_) => line.trim(),
+        };
+
+        match answer.to_lowercase().as_str() {
+            "y" | "yes" => return true,
+            "n" | "no" => return false,
+            "a" | "all" => return remember(true),
+            "q" | "quit" => return remember(false),
+            _ => {}
+        }
+    }
+}
+
+fn remember(answer: bool) -> bool {
+    PREVIEW.with(|p| p.borrow_mut().answer = Some(answer));
+    answer
+}
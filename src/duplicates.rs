@@ -0,0 +1,203 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+};
+
+use crate::{
+    analyze::is_keyword,
+    messages::Message,
+    parser::{Sequence, Token},
+    terminal_helper::TerminalOutput,
+};
+
+// Token of the code with the identifiers and literals replaced by placeholders, so that copies
+// with renamed variables or other values are still found
+struct Normalized {
+    key: String,
+    text: String,
+    line: usize,
+}
+
+struct File {
+    name: String,
+    tokens: Vec<Normalized>,
+}
+
+struct Match {
+    first: (usize, usize),
+    second: (usize, usize),
+    len: usize,
+}
+
+fn normalize(tokens: &[Sequence<Token>]) -> Vec<Normalized> {
+    let mut normalized = Vec::new();
+    let mut line_number = 1;
+
+    for t in tokens {
+        if let Some(number) = t.token.line_after() {
+            line_number = number;
+        }
+
+        let key = match t.token {
+            Token::Other if t.text.starts_with(|c: char| c.is_ascii_digit()) => "$number",
+            Token::Other if is_keyword(t.text) => t.text,
+            Token::Other if t.text.starts_with(|c: char| c == '_' || c.is_alphabetic()) => "$name",
+            Token::Other => t.text,
+            Token::StrLiteral => "$string",
+            Token::CharLiteral => "$char",
+            Token::Lifetime => "$lifetime",
+            _ => continue,
+        };
+
+        normalized.push(Normalized {
+            key: key.to_string(),
+            text: t.text.to_string(),
+            line: line_number,
+        });
+    }
+
+    normalized
+}
+
+// Regions of at least min_tokens normalized tokens that appear more than once
+#[derive(Default)]
+pub struct Duplicates {
+    files: Vec<File>,
+}
+
+impl Duplicates {
+    pub fn record(&mut self, name: String, tokens: &[Sequence<Token>]) {
+        self.files.push(File {
+            name,
+            tokens: normalize(tokens),
+        });
+    }
+
+    fn keys(&self, (file, start): (usize, usize), len: usize) -> impl Iterator<Item = &str> {
+        self.files[file].tokens[start..start + len]
+            .iter()
+            .map(|t| t.key.as_str())
+    }
+
+    fn hash(&self, window: (usize, usize), len: usize) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.keys(window, len).for_each(|key| key.hash(&mut hasher));
+        hasher.finish()
+    }
+
+    fn same(&self, a: (usize, usize), b: (usize, usize)) -> bool {
+        let (a, b) = (
+            self.files[a.0].tokens.get(a.1),
+            self.files[b.0].tokens.get(b.1),
+        );
+        matches!((a, b), (Some(a), Some(b)) if a.key == b.key)
+    }
+
+    // Each window is compared with the first window with the same tokens, and the matches are
+    // extended as far as the tokens are the same. Only the matches that can't be extended
+    // backwards are reported, so that a long clone is reported once.
+    fn clones(&self, min_tokens: usize) -> Vec<Match> {
+        let mut first_window = HashMap::new();
+        let mut clones = Vec::new();
+
+        for (f, file) in self.files.iter().enumerate() {
+            for start in 0..(file.tokens.len() + 1).saturating_sub(min_tokens) {
+                let window = (f, start);
+                let first = *first_window
+                    .entry(self.hash(window, min_tokens))
+                    .or_insert(window);
+                if first == window
+                    || !self
+                        .keys(first, min_tokens)
+                        .eq(self.keys(window, min_tokens))
+                {
+                    continue;
+                }
+
+                let extends_previous =
+                    start > 0 && first.1 > 0 && self.same((first.0, first.1 - 1), (f, start - 1));
+                if extends_previous {
+                    continue;
+                }
+
+                let mut len = min_tokens;
+                while self.same((first.0, first.1 + len), (f, start + len)) {
+                    len += 1;
+                }
+
+                // repeated tokens, as in a long list of items, overlap themselves
+                if first.0 == f && first.1 + len > start {
+                    continue;
+                }
+
+                clones.push(Match {
+                    first,
+                    second: window,
+                    len,
+                });
+            }
+        }
+
+        clones
+    }
+
+    // percentage of the tokens that are the same, including the names and the literals
+    fn similarity(&self, clone: &Match) -> usize {
+        let first = &self.files[clone.first.0].tokens[clone.first.1..];
+        let second = &self.files[clone.second.0].tokens[clone.second.1..];
+        let same = first
+            .iter()
+            .zip(second)
+            .take(clone.len)
+            .filter(|(a, b)| a.text == b.text)
+            .count();
+        same * 100 / clone.len
+    }
+
+    fn location(&self, (file, start): (usize, usize), len: usize) -> String {
+        let file = &self.files[file];
+        format!(
+            "{}:{}-{}",
+            file.name,
+            file.tokens[start].line,
+            file.tokens[start + len - 1].line
+        )
+    }
+
+    pub fn report(&self, min_tokens: usize, output: &mut TerminalOutput) {
+        let clones = self.clones(min_tokens);
+
+        for clone in &clones {
+            output.writeln_result(Message::DuplicateRegion(
+                self.location(clone.first, clone.len),
+                self.location(clone.second, clone.len),
+                clone.len,
+                self.similarity(clone),
+            ));
+        }
+
+        output.writeln_info(Message::DuplicatesFound(clones.len()));
+    }
+}
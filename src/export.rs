@@ -0,0 +1,99 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use serde::Serialize;
+
+use crate::{
+    parser::{self, Sequence, Token},
+    visitor::{self, Position, TokenVisitor},
+};
+
+/// Token of a parsed text, in a form that can be handed to other languages.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ExportedToken<'a> {
+    /// Name of the kind of token, such as `StrLiteral` (see [`parser::TokenKind`])
+    pub kind: &'static str,
+    pub text: &'a str,
+    pub line: usize,
+    pub column: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Error or warning reported by the parser for one of the tokens.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ExportedDiagnostic {
+    pub code: String,
+    pub severity: &'static str,
+    pub message: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct Exported<'a> {
+    pub tokens: Vec<ExportedToken<'a>>,
+    pub diagnostics: Vec<ExportedDiagnostic>,
+}
+
+pub fn kind_name(token: &Token) -> &'static str {
+    token.kind().as_str()
+}
+
+impl<'a> TokenVisitor<'a> for Exported<'a> {
+    fn visit_token(&mut self, token: &Token, text: &'a str, position: Position) {
+        self.tokens.push(ExportedToken {
+            kind: kind_name(token),
+            text,
+            line: position.line,
+            column: position.column,
+            start: position.offset,
+            end: position.offset + text.len(),
+        });
+
+        if let Token::Invalid(kind) = token {
+            let code = kind.code();
+
+            self.diagnostics.push(ExportedDiagnostic {
+                code: code.to_string(),
+                severity: code.severity().name(),
+                message: kind.to_string(),
+                line: position.line,
+                column: position.column,
+            });
+        }
+    }
+}
+
+/// Describes the tokens of an already parsed text.
+pub fn export<'a>(tokens: &[Sequence<'a, Token>]) -> Exported<'a> {
+    let mut exported = Exported::default();
+    visitor::walk(tokens, &mut exported);
+    exported
+}
+
+/// Parses `text` and describes its tokens and diagnostics as a JSON object with the fields
+/// `tokens` and `diagnostics`.
+pub fn parse_to_json(text: &str) -> String {
+    let exported = export(&parser::parse(text));
+    serde_json::to_string(&exported).expect("exported tokens are always serializable")
+}
@@ -0,0 +1,93 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use std::{
+    ffi::c_char,
+    panic::{self, AssertUnwindSafe},
+    ptr, slice,
+};
+
+use crate::export;
+
+/// Bytes allocated by xr, which must be released with [`xr_free`].
+///
+/// `data` is null when the text could not be parsed.
+#[repr(C)]
+pub struct XrBuffer {
+    pub data: *mut u8,
+    pub len: usize,
+}
+
+impl XrBuffer {
+    fn empty() -> XrBuffer {
+        XrBuffer {
+            data: ptr::null_mut(),
+            len: 0,
+        }
+    }
+}
+
+/// Parses `len` bytes of UTF-8 text and returns its tokens and diagnostics as JSON, in the
+/// format of [`export::parse_to_json`]. Invalid UTF-8 sequences are replaced by U+FFFD.
+///
+/// # Safety
+///
+/// `text` must point to `len` readable bytes, or be null when `len` is 0.
+#[no_mangle]
+pub unsafe extern "C" fn xr_parse(text: *const c_char, len: usize) -> XrBuffer {
+    let bytes = if text.is_null() || len == 0 {
+        &[]
+    } else {
+        slice::from_raw_parts(text.cast::<u8>(), len)
+    };
+
+    // unwinding into C is undefined behaviour
+    let json = panic::catch_unwind(AssertUnwindSafe(|| {
+        export::parse_to_json(&String::from_utf8_lossy(bytes))
+    }));
+
+    match json {
+        Ok(json) => {
+            let bytes = Box::into_raw(json.into_bytes().into_boxed_slice());
+            XrBuffer {
+                data: bytes.cast::<u8>(),
+                len: bytes.len(),
+            }
+        }
+        Err(_) => XrBuffer::empty(),
+    }
+}
+
+/// Releases a buffer returned by [`xr_parse`].
+///
+/// # Safety
+///
+/// `buffer` must have been returned by [`xr_parse`] and not released before.
+#[no_mangle]
+pub unsafe extern "C" fn xr_free(buffer: XrBuffer) {
+    if !buffer.data.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(
+            buffer.data,
+            buffer.len,
+        )));
+    }
+}
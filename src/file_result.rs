@@ -0,0 +1,76 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::{
+    diagnostics::{self, OwnedDiagnostic},
+    messages::Message,
+    terminal_helper::TerminalOutput,
+};
+
+// Outcome of processing a file, returned instead of being printed as the file is processed, so
+// that it can be reported by the terminal, summarized or collected by other means.
+pub struct FileResult {
+    pub diagnostics: Vec<OwnedDiagnostic>,
+    pub output_path: Option<PathBuf>,
+    pub duration: Duration,
+    pub bytes_in: usize,
+    pub bytes_out: usize,
+}
+
+impl FileResult {
+    pub fn new(contents: &str) -> FileResult {
+        FileResult {
+            diagnostics: Vec::new(),
+            output_path: None,
+            duration: Duration::ZERO,
+            bytes_in: contents.len(),
+            bytes_out: 0,
+        }
+    }
+
+    pub fn written(&mut self, path: &Path, text: &str) {
+        self.output_path = Some(path.to_path_buf());
+        self.bytes_out = text.len();
+    }
+
+    // terminal reporter of the result
+    pub fn report(&self, output: &mut TerminalOutput) {
+        if !self.duration.is_zero() {
+            output.writeln(Message::FileParsed(self.duration));
+        }
+
+        diagnostics::report_owned(&self.diagnostics, output);
+
+        if let Some(path) = &self.output_path {
+            output.writeln_verbose(Message::FileGenerated(
+                path.display().to_string(),
+                self.bytes_in,
+                self.bytes_out,
+            ));
+        }
+    }
+}
@@ -0,0 +1,130 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use std::fs;
+
+use crate::{
+    arguments::{CommentStyle, FmtArgs, StringStyle},
+    comment_style,
+    indentation::{IndentStyle, Reindenter},
+    messages::Message,
+    parser::{Sequence, Token},
+    raw_strings,
+    terminal_helper::TerminalOutput,
+};
+
+pub fn is_line_end(next: Option<&Sequence<Token>>) -> bool {
+    match next {
+        Some(t) => t.token.line_after().is_some(),
+        None => true,
+    }
+}
+
+// Removes trailing whitespace (preserving the contents of string literals and block comments)
+// and makes sure that the text ends with a new line. The indentation, the string literals and
+// the comments are rewritten when a style is given.
+pub fn format(
+    tokens: &[Sequence<Token>],
+    indent: Option<IndentStyle>,
+    strings: Option<StringStyle>,
+    comments: Option<CommentStyle>,
+) -> String {
+    let reindenter = indent.map(|style| Reindenter::new(tokens, style));
+    let comments = comments
+        .map(|style| comment_style::conversions(tokens, style))
+        .unwrap_or_default();
+    let mut text = String::new();
+
+    for (i, t) in tokens.iter().enumerate() {
+        let line_start = i == 0 || tokens[i - 1].token.line_after().is_some();
+
+        if let Some(converted) = comments.get(&t.span.start) {
+            text.push_str(converted);
+            continue;
+        }
+
+        match t.token {
+            Token::Whitespace { .. } if is_line_end(tokens.get(i + 1)) => {}
+            Token::Whitespace { spaces, tabs } if line_start => match &reindenter {
+                Some(reindenter) => text.push_str(&reindenter.reindent(spaces, tabs)),
+                None => text.push_str(t.text),
+            },
+            Token::Other | Token::SingleLineComment if is_line_end(tokens.get(i + 1)) => {
+                text.push_str(t.text.trim_end_matches([' ', '\t']))
+            }
+            Token::StrLiteral => {
+                match strings.and_then(|style| raw_strings::convert(t.text, style)) {
+                    Some(converted) => text.push_str(&converted),
+                    None => text.push_str(t.text),
+                }
+            }
+            _ => text.push_str(t.text),
+        }
+    }
+
+    ensure_final_newline(&mut text);
+    text
+}
+
+pub fn ensure_final_newline(text: &mut String) {
+    if !text.is_empty() && !text.ends_with('\n') {
+        text.push('\n');
+    }
+}
+
+pub fn run(args: &FmtArgs, output: &mut TerminalOutput) {
+    let filenames = args.input.files_to_process(output);
+    let mut reformatted = 0;
+
+    crate::for_each_file(&filenames, output, |file, contents, output| {
+        let filename = file.display().to_string();
+        let result = crate::parse_file(&contents, output);
+
+        if crate::report_errors(&result, output) > 0 {
+            output.writeln_error(Message::FileNotFormattedDueToErrors(filename));
+            return;
+        }
+
+        let formatted = format(
+            &result,
+            args.fix_indent,
+            args.rewrite_strings,
+            args.comment_style,
+        );
+
+        if formatted != contents {
+            reformatted += 1;
+
+            if args.check {
+                output.writeln_error(Message::FileNotFormatted(filename));
+            } else if fs::write(file, formatted).is_err() {
+                output.writeln_error(Message::FileWriteFailed(filename));
+            }
+        }
+    });
+
+    if args.check {
+        output.writeln_success(Message::FilesNeedFormatting(reformatted, filenames.len()));
+    } else {
+        output.writeln_success(Message::FilesFormatted(reformatted, filenames.len()));
+    }
+}
@@ -0,0 +1,91 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use crate::messages::Message;
+
+const GIT: &str = "git";
+
+// Runs git in the given directory, returning the paths it prints separated by NUL characters
+fn paths(dir: &Path, args: &[&str]) -> Result<Vec<String>, Message> {
+    let result = Command::new(GIT)
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .map_err(|e| Message::ProgramNotRun(GIT.to_string(), e.to_string()))?;
+
+    if !result.status.success() {
+        let stderr = String::from_utf8_lossy(&result.stderr);
+        let reason = stderr.lines().next().unwrap_or_default().to_string();
+        return Err(Message::GitFailed(reason));
+    }
+
+    Ok(String::from_utf8_lossy(&result.stdout)
+        .split('\0')
+        .map(|s| s.trim_end_matches('\n').to_string())
+        .filter(|s| !s.is_empty())
+        .collect())
+}
+
+// Directory of the hooks of the repository containing `dir`, which may be configured with
+// core.hooksPath
+pub fn hooks_dir(dir: &Path) -> Result<PathBuf, Message> {
+    let hooks = paths(dir, &["rev-parse", "--git-path", "hooks"])?
+        .pop()
+        .ok_or_else(|| Message::GitFailed(String::new()))?;
+
+    Ok(dir.join(hooks))
+}
+
+// Canonical paths of the files of the repository containing `dir` that were changed since
+// `since` (HEAD by default), whether staged or not, and of the untracked files. Deleted files
+// are left out, as there is nothing to process.
+pub fn changed_files(dir: &Path, since: Option<&str>) -> Result<HashSet<PathBuf>, Message> {
+    let top_level = paths(dir, &["rev-parse", "--show-toplevel"])?
+        .pop()
+        .map(PathBuf::from)
+        .ok_or_else(|| Message::GitFailed(String::new()))?;
+
+    let since = since.unwrap_or("HEAD");
+    let changed = paths(
+        &top_level,
+        &["diff", "--name-only", "-z", "--diff-filter=d", since, "--"],
+    )?;
+    let untracked = paths(
+        &top_level,
+        &["ls-files", "-z", "--others", "--exclude-standard"],
+    )?;
+
+    Ok(changed
+        .iter()
+        .chain(&untracked)
+        .map(|file| {
+            let path = top_level.join(file);
+            path.canonicalize().unwrap_or(path)
+        })
+        .collect())
+}
@@ -0,0 +1,290 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use std::{
+    borrow::Cow,
+    fs, io,
+    path::Path,
+    str,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+const TIMESTAMP_PREFIX: &str = "// generated on ";
+
+const BOM: &[u8] = "\u{feff}".as_bytes();
+
+#[derive(Clone)]
+pub struct Header {
+    text: String,
+    only_comments: bool,
+    // text without the generation timestamp, which does not make generated files different
+    fingerprint: String,
+    // license that must begin every generated file, which is only inserted when missing
+    license: Option<String>,
+}
+
+impl Header {
+    pub fn none() -> Header {
+        Header {
+            text: String::new(),
+            only_comments: true,
+            fingerprint: String::new(),
+            license: None,
+        }
+    }
+
+    pub fn banner(timestamp: bool) -> Header {
+        let version = env!("CARGO_PKG_VERSION");
+        let mut text = format!("// @generated by xr v{version} — do not edit\n");
+        let fingerprint = text.clone();
+
+        if timestamp {
+            text.push_str(&format!("{TIMESTAMP_PREFIX}{}\n", utc_timestamp()));
+        }
+
+        Header {
+            text,
+            only_comments: true,
+            fingerprint,
+            license: None,
+        }
+    }
+
+    pub fn from_file(path: &Path) -> io::Result<Header> {
+        let text = read_text(path)?;
+
+        let only_comments = text.lines().all(|line| {
+            let line = line.trim();
+            line.is_empty() || line.starts_with("//")
+        });
+
+        Ok(Header {
+            fingerprint: text.clone(),
+            text,
+            only_comments,
+            license: None,
+        })
+    }
+
+    pub fn with_license_file(mut self, path: &Path) -> io::Result<Header> {
+        let license = read_text(path)?;
+        self.fingerprint.push_str(&license);
+        self.license = Some(license);
+        Ok(self)
+    }
+
+    pub fn fingerprint(&self) -> &str {
+        &self.fingerprint
+    }
+
+    // whether the contents begin with the license, when one is required
+    pub fn has_license(&self, contents: &str) -> bool {
+        match &self.license {
+            Some(license) => license_len(license, &contents[shebang_len(contents)..]).is_some(),
+            None => true,
+        }
+    }
+
+    pub fn apply(&self, contents: &str) -> String {
+        let pieces = self.pieces(contents);
+
+        let inserted: usize = pieces.iter().map(|(_, text)| text.len()).sum();
+        let mut result = String::with_capacity(contents.len() + inserted);
+        let mut copied = 0;
+
+        for (index, text) in pieces {
+            result.push_str(&contents[copied..index]);
+            result.push_str(text);
+            copied = index;
+        }
+
+        result.push_str(&contents[copied..]);
+        result
+    }
+
+    // byte offsets of the text where text is inserted and the length inserted at each one
+    pub fn insertions(&self, contents: &str) -> Vec<(usize, usize)> {
+        self.pieces(contents)
+            .into_iter()
+            .map(|(index, text)| (index, text.len()))
+            .collect()
+    }
+
+    // The shebang must remain the first line, followed by the license. Comments may precede
+    // inner attributes and inner doc comments, but any other code in the header must be
+    // placed after them.
+    fn pieces(&self, contents: &str) -> Vec<(usize, &str)> {
+        let shebang = shebang_len(contents);
+        let mut pieces = Vec::new();
+        let mut index = shebang;
+
+        if let Some(license) = &self.license {
+            match license_len(license, &contents[shebang..]) {
+                Some(len) => index += len,
+                None => pieces.push((shebang, license.as_str())),
+            }
+        }
+
+        if !self.only_comments {
+            index += inner_attributes_len(&contents[index..]);
+        }
+
+        if !self.text.is_empty() {
+            pieces.push((index, self.text.as_str()));
+        }
+
+        pieces
+    }
+
+    // a generated file without the timestamp line of the banner, which changes on every
+    // generation; the banner follows the byte order mark, the shebang and the license
+    pub fn without_timestamp<'a>(&self, contents: &'a [u8]) -> Cow<'a, [u8]> {
+        let bom = if contents.starts_with(BOM) {
+            BOM.len()
+        } else {
+            0
+        };
+        let Ok(text) = str::from_utf8(&contents[bom..]) else {
+            return Cow::Borrowed(contents);
+        };
+
+        let mut start = shebang_len(text);
+        if let Some(license) = &self.license {
+            start += license_len(license, &text[start..]).unwrap_or(0);
+        }
+
+        while text[start..].starts_with("//") {
+            let end = start + line_len(&text[start..]);
+
+            if text[start..].starts_with(TIMESTAMP_PREFIX) {
+                return Cow::Owned([&contents[..bom + start], &contents[bom + end..]].concat());
+            }
+            start = end;
+        }
+
+        Cow::Borrowed(contents)
+    }
+}
+
+fn read_text(path: &Path) -> io::Result<String> {
+    let mut text = fs::read_to_string(path)?;
+
+    if !text.is_empty() && !text.ends_with('\n') {
+        text.push('\n');
+    }
+
+    Ok(text)
+}
+
+// length of the license at the beginning of the text, whose lines may differ from the ones
+// of the license in trailing whitespace
+fn license_len(license: &str, text: &str) -> Option<usize> {
+    let mut index = 0;
+
+    for expected in license.lines() {
+        let len = line_len(&text[index..]);
+        if len == 0 || text[index..index + len].trim_end() != expected.trim_end() {
+            return None;
+        }
+        index += len;
+    }
+
+    Some(index)
+}
+
+fn line_len(text: &str) -> usize {
+    match text.find('\n') {
+        Some(i) => i + 1,
+        None => text.len(),
+    }
+}
+
+fn shebang_len(contents: &str) -> usize {
+    if contents.starts_with("#!") && !contents[2..].trim_start().starts_with('[') {
+        line_len(contents)
+    } else {
+        0
+    }
+}
+
+fn inner_attributes_len(contents: &str) -> usize {
+    let mut index = 0;
+    let mut depth: usize = 0;
+
+    while index < contents.len() {
+        let line = &contents[index..index + line_len(&contents[index..])];
+        let trimmed = line.trim();
+
+        let is_inner =
+            trimmed.is_empty() || trimmed.starts_with("//!") || trimmed.starts_with("#![");
+
+        if depth == 0 && !is_inner {
+            break;
+        }
+
+        if depth > 0 || trimmed.starts_with("#![") {
+            for c in trimmed.chars() {
+                match c {
+                    '[' => depth += 1,
+                    ']' => depth = depth.saturating_sub(1),
+                    _ => {}
+                }
+            }
+        }
+
+        index += line.len();
+    }
+
+    index
+}
+
+fn utc_timestamp() -> String {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+
+    let (year, month, day) = civil_from_days((seconds / 86400) as i64);
+    let time = seconds % 86400;
+
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02} UTC",
+        time / 3600,
+        (time % 3600) / 60,
+        time % 60
+    )
+}
+
+// Howard Hinnant's days-to-civil algorithm
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    (year, month, day)
+}
@@ -0,0 +1,84 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use crate::parser::{Sequence, Token};
+
+pub fn css_class(token: &Token) -> Option<&'static str> {
+    match token {
+        Token::CharLiteral => Some("char"),
+        Token::Invalid(_) => Some("invalid"),
+        Token::Lifetime => Some("lifetime"),
+        Token::LoopLabel => Some("label"),
+        Token::MultiLineComment | Token::SingleLineComment => Some("comment"),
+        Token::StrLiteral => Some("string"),
+        Token::BlankLine(_) | Token::NewLine(_) | Token::Other | Token::Whitespace { .. } => None,
+    }
+}
+
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+// One string of HTML per line of the source, each with its own balanced <span> elements, so
+// that any line can be shown on its own even if a token spans several lines.
+pub fn html_lines(tokens: &[Sequence<Token>]) -> Vec<String> {
+    let mut lines = vec![String::new()];
+
+    for t in tokens {
+        let class = css_class(&t.token);
+
+        for (i, part) in t.text.split('\n').enumerate() {
+            if i > 0 {
+                lines.push(String::new());
+            }
+
+            let part = part.trim_end_matches('\r');
+            if part.is_empty() {
+                continue;
+            }
+
+            let line = lines.last_mut().expect("there is always a line");
+            match class {
+                Some(class) => line.push_str(&format!(
+                    "<span class=\"{class}\">{}</span>",
+                    escape_html(part)
+                )),
+                None => line.push_str(&escape_html(part)),
+            }
+        }
+    }
+
+    lines
+}
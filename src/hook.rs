@@ -0,0 +1,102 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use std::{env, fs, path::Path};
+
+use crate::{arguments::InstallHookArgs, git, messages::Message, terminal_helper::TerminalOutput};
+
+const HOOK_NAME: &str = "pre-commit";
+
+// identifies the hooks written by xr, which are the only ones it replaces or removes
+const MARKER: &str = "# installed by xr install-hook";
+
+fn script() -> String {
+    let program = env::current_exe()
+        .map(|p| p.display().to_string())
+        .unwrap_or(String::from("xr"));
+
+    // exec makes the exit status of xr the exit status of the hook, so the commit is aborted
+    // when errors are found
+    format!(
+        "#!/bin/sh\n{MARKER}\nexec '{}' check --quiet --changed-only\n",
+        program.replace('\'', r"'\''")
+    )
+}
+
+fn is_ours(path: &Path) -> bool {
+    fs::read_to_string(path).is_ok_and(|contents| contents.contains(MARKER))
+}
+
+pub fn run(args: &InstallHookArgs, output: &mut TerminalOutput) {
+    let dir = env::current_dir().unwrap_or_default();
+    let hooks = match git::hooks_dir(&dir) {
+        Ok(hooks) => hooks,
+        Err(e) => {
+            output.writeln_error(e);
+            return;
+        }
+    };
+
+    let path = hooks.join(HOOK_NAME);
+    let filename = path.display().to_string();
+
+    if args.uninstall {
+        if !path.exists() {
+            output.writeln_info(Message::HookNotInstalled(filename));
+        } else if !is_ours(&path) {
+            output.writeln_error(Message::HookNotOurs(filename));
+        } else if fs::remove_file(&path).is_err() {
+            output.writeln_error(Message::FileWriteFailed(filename));
+        } else {
+            output.writeln_success(Message::HookRemoved(filename));
+        }
+        return;
+    }
+
+    if path.exists() && !is_ours(&path) && !args.force {
+        output.writeln_error(Message::HookNotOurs(filename));
+        return;
+    }
+
+    if fs::create_dir_all(&hooks)
+        .and_then(|_| fs::write(&path, script()))
+        .and_then(|_| make_executable(&path))
+        .is_err()
+    {
+        output.writeln_error(Message::FileWriteFailed(filename));
+        return;
+    }
+
+    output.writeln_success(Message::HookInstalled(filename));
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755))
+}
+
+#[cfg(not(unix))]
+fn make_executable(_: &Path) -> std::io::Result<()> {
+    Ok(())
+}
@@ -0,0 +1,181 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use std::{cell::RefCell, collections::BTreeMap, fs, path::Path};
+
+use crate::{
+    diagnostics::Diagnostic,
+    error_codes::{ErrorCode, Severity},
+    highlight::{self, escape_html},
+    lint_levels,
+    messages::Message,
+    parser::{Sequence, Token},
+    terminal_helper::TerminalOutput,
+};
+
+const STYLE: &str = "body { font-family: sans-serif; margin: 2em; color: #222; }
+table { border-collapse: collapse; }
+td, th { border: 1px solid #ccc; padding: 0.2em 0.6em; text-align: left; }
+h2 { margin-top: 1.5em; font-size: 1.1em; }
+.diagnostic { margin: 0.5em 0 1em 0; }
+.error { color: #b00; font-weight: bold; }
+.warning { color: #a60; font-weight: bold; }
+pre { background: #f6f6f6; padding: 0.5em; overflow-x: auto; }
+.line-number { color: #999; user-select: none; }
+.comment { color: #6a737d; }
+.string, .char { color: #22863a; }
+.lifetime { color: #6f42c1; }
+.label { color: #6f42c1; font-style: italic; }
+.invalid { background: #fdd; text-decoration: underline wavy #b00; }
+.clean { color: #22863a; }";
+
+struct DiagnosticEntry {
+    code: ErrorCode,
+    line: usize,
+    message: String,
+    snippet: String,
+}
+
+struct FileEntry {
+    name: String,
+    diagnostics: Vec<DiagnosticEntry>,
+}
+
+thread_local! {
+    static FILES: RefCell<Option<Vec<FileEntry>>> = const { RefCell::new(None) };
+}
+
+pub fn enable() {
+    FILES.with(|f| *f.borrow_mut() = Some(Vec::new()));
+}
+
+pub fn begin_file(file: &Path) {
+    FILES.with(|f| {
+        if let Some(files) = f.borrow_mut().as_mut() {
+            files.push(FileEntry {
+                name: file.display().to_string(),
+                diagnostics: Vec::new(),
+            });
+        }
+    });
+}
+
+pub fn record(tokens: &[Sequence<Token>], diagnostics: &[Diagnostic]) {
+    FILES.with(|f| {
+        let mut files = f.borrow_mut();
+        let Some(file) = files.as_mut().and_then(|files| files.last_mut()) else {
+            return;
+        };
+
+        let lines = highlight::html_lines(tokens);
+
+        for d in diagnostics {
+            let snippet = lines
+                .get(d.line.saturating_sub(1))
+                .cloned()
+                .unwrap_or_default();
+
+            file.diagnostics.push(DiagnosticEntry {
+                code: d.code,
+                line: d.line,
+                message: d.message.clone(),
+                snippet,
+            });
+        }
+    });
+}
+
+fn render(files: &[FileEntry]) -> String {
+    let all = || files.iter().flat_map(|f| &f.diagnostics);
+    let errors = all()
+        .filter(|d| lint_levels::severity(d.code) == Severity::Error)
+        .count();
+    let warnings = all().count() - errors;
+
+    let mut by_code = BTreeMap::<ErrorCode, usize>::new();
+    all().for_each(|d| *by_code.entry(d.code).or_default() += 1);
+
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!("<title>{}</title>\n", Message::ReportTitle));
+    html.push_str(&format!("<style>\n{STYLE}\n</style>\n</head>\n<body>\n"));
+    html.push_str(&format!("<h1>{}</h1>\n", Message::ReportTitle));
+    html.push_str(&format!(
+        "<p>{}</p>\n",
+        escape_html(&Message::ReportSummary(files.len(), errors, warnings).to_string())
+    ));
+
+    if !by_code.is_empty() {
+        html.push_str(&format!(
+            "<table>\n<tr><th>{}</th><th>{}</th></tr>\n",
+            Message::ReportCode,
+            Message::ReportCount
+        ));
+        for (code, count) in by_code {
+            html.push_str(&format!("<tr><td>{code}</td><td>{count}</td></tr>\n"));
+        }
+        html.push_str("</table>\n");
+    }
+
+    for file in files {
+        html.push_str(&format!("<h2>{}</h2>\n", escape_html(&file.name)));
+
+        if file.diagnostics.is_empty() {
+            html.push_str(&format!(
+                "<p class=\"clean\">{}</p>\n",
+                Message::ReportNoDiagnostics
+            ));
+        }
+
+        for d in &file.diagnostics {
+            let class = match lint_levels::severity(d.code) {
+                Severity::Error => "error",
+                Severity::Warning => "warning",
+            };
+            html.push_str(&format!(
+                "<div class=\"diagnostic\"><span class=\"{class}\">{}</span> {}\n",
+                d.code,
+                escape_html(&Message::ErrorAtLine(d.line, d.message.clone()).to_string())
+            ));
+            html.push_str(&format!(
+                "<pre><span class=\"line-number\">{:>5} | </span>{}</pre></div>\n",
+                d.line, d.snippet
+            ));
+        }
+    }
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+pub fn write(path: &Path, output: &mut TerminalOutput) {
+    let Some(files) = FILES.with(|f| f.borrow_mut().take()) else {
+        return;
+    };
+
+    let filename = path.display().to_string();
+
+    match fs::write(path, render(&files)) {
+        Ok(_) => output.writeln_info(Message::ReportWritten(filename)),
+        Err(_) => output.writeln_error(Message::FileWriteFailed(filename)),
+    }
+}
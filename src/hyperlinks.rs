@@ -0,0 +1,79 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use std::{env, path::Path};
+
+// terminals known to support OSC 8 hyperlinks, as identified by TERM_PROGRAM or TERM
+const TERMINAL_PROGRAMS: &[&str] = &["iTerm.app", "WezTerm", "vscode", "Hyper", "ghostty"];
+const TERMINALS: &[&str] = &[
+    "xterm-kitty",
+    "alacritty",
+    "foot",
+    "wezterm",
+    "xterm-ghostty",
+];
+
+// the first version of VTE (GNOME Terminal and others) with hyperlinks, 0.50
+const MIN_VTE_VERSION: u32 = 5000;
+
+// whether the terminal is known to show hyperlinks, instead of printing their escape sequences
+pub fn supported() -> bool {
+    let var = |name| env::var(name).unwrap_or_default();
+
+    let term = var("TERM");
+    if term == "dumb" {
+        return false;
+    }
+
+    TERMINAL_PROGRAMS.contains(&var("TERM_PROGRAM").as_str())
+        || TERMINALS.contains(&term.as_str())
+        || var("VTE_VERSION")
+            .parse::<u32>()
+            .is_ok_and(|v| v >= MIN_VTE_VERSION)
+        || env::var_os("WT_SESSION").is_some()
+        || env::var_os("KITTY_WINDOW_ID").is_some()
+}
+
+// the text, which the terminal shows as a link to the file
+pub fn link(file: &Path, text: &str) -> String {
+    format!("\x1b]8;;{}\x1b\\{text}\x1b]8;;\x1b\\", file_url(file))
+}
+
+fn file_url(file: &Path) -> String {
+    let path = std::path::absolute(file).unwrap_or_else(|_| file.to_path_buf());
+    let path = path.to_string_lossy().replace('\\', "/");
+
+    let mut url = String::from("file://");
+    // C:/dir on Windows becomes file:///C:/dir
+    if !path.starts_with('/') {
+        url.push('/');
+    }
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' | b':' => {
+                url.push(char::from(byte))
+            }
+            _ => url.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    url
+}
@@ -0,0 +1,163 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use std::str::FromStr;
+
+use crate::{
+    diagnostics::Diagnostic,
+    error_codes::ErrorCode,
+    messages::Message,
+    parser::{Sequence, Token},
+};
+
+const DEFAULT_WIDTH: usize = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum IndentStyle {
+    Spaces(usize),
+    Tabs,
+}
+
+impl FromStr for IndentStyle {
+    type Err = String;
+
+    // "tabs", "spaces" or "spaces:<width>"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            None if s == "tabs" => Ok(Self::Tabs),
+            None if s == "spaces" => Ok(Self::Spaces(DEFAULT_WIDTH)),
+            Some(("spaces", width)) => match width.parse() {
+                Ok(width) if width > 0 => Ok(Self::Spaces(width)),
+                _ => Err(format!("invalid indent width '{width}'")),
+            },
+            _ => Err("expected tabs, spaces or spaces:<width>".to_string()),
+        }
+    }
+}
+
+struct Indentation<'a, 'b> {
+    line: usize,
+    spaces: usize,
+    tabs: usize,
+    sequence: &'b Sequence<'a, Token>,
+}
+
+// whitespace at the beginning of the lines that have code or comments
+fn indentations<'a, 'b>(tokens: &'b [Sequence<'a, Token>]) -> Vec<Indentation<'a, 'b>> {
+    let mut indentations = Vec::new();
+    let mut line_number = 1;
+
+    for (i, t) in tokens.iter().enumerate() {
+        if let Some(number) = t.token.line_after() {
+            line_number = number;
+        }
+
+        let Token::Whitespace { spaces, tabs } = t.token else {
+            continue;
+        };
+
+        let at_line_start = i == 0 || tokens[i - 1].token.line_after().is_some();
+        let has_content = tokens
+            .get(i + 1)
+            .is_some_and(|next| next.token.line_after().is_none());
+
+        if at_line_start && has_content {
+            indentations.push(Indentation {
+                line: line_number,
+                spaces,
+                tabs,
+                sequence: t,
+            });
+        }
+    }
+
+    indentations
+}
+
+// indentation of the first line indented only with spaces
+fn width(indentations: &[Indentation]) -> usize {
+    indentations
+        .iter()
+        .find(|i| i.tabs == 0)
+        .map(|i| i.spaces)
+        .unwrap_or(DEFAULT_WIDTH)
+}
+
+pub fn lint<'a>(tokens: &[Sequence<'a, Token>]) -> Vec<Diagnostic<'a>> {
+    let indentations = indentations(tokens);
+    let width = width(&indentations);
+    let uses_tabs = indentations.first().is_some_and(|i| i.tabs > 0);
+
+    let mut diagnostics = Vec::new();
+
+    for i in &indentations {
+        let mixed = if i.tabs > 0 {
+            i.spaces > 0 || !uses_tabs
+        } else {
+            uses_tabs
+        };
+
+        let (code, message) = if mixed {
+            (ErrorCode::W0002, Message::IndentationMixed)
+        } else if i.tabs == 0 && i.spaces % width != 0 {
+            (ErrorCode::W0003, Message::IndentationWidth(width))
+        } else {
+            continue;
+        };
+
+        diagnostics.push(Diagnostic {
+            code,
+            message: message.to_string(),
+            line: i.line,
+            span: i.sequence.span,
+            text: i.sequence.text,
+        });
+    }
+
+    diagnostics
+}
+
+// Rewrites the indentation of the lines of a file in the given style. Each tab, and each
+// run of spaces as wide as the indent width of the file, is one level of indentation.
+pub struct Reindenter {
+    style: IndentStyle,
+    width: usize,
+}
+
+impl Reindenter {
+    pub fn new(tokens: &[Sequence<Token>], style: IndentStyle) -> Reindenter {
+        Reindenter {
+            style,
+            width: width(&indentations(tokens)),
+        }
+    }
+
+    pub fn reindent(&self, spaces: usize, tabs: usize) -> String {
+        let levels = tabs + spaces / self.width;
+        let alignment = spaces % self.width;
+
+        match self.style {
+            IndentStyle::Spaces(width) => " ".repeat(levels * width + alignment),
+            IndentStyle::Tabs => "\t".repeat(levels) + &" ".repeat(alignment),
+        }
+    }
+}
@@ -0,0 +1,114 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use std::{collections::HashMap, mem, rc::Rc};
+
+use crate::parser::{Sequence, Span, Token};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Symbol(u32);
+
+// Stores each distinct string once, so that repeated identifiers and literals share memory
+#[derive(Default)]
+pub struct Interner {
+    symbols: HashMap<Rc<str>, Symbol>,
+    strings: Vec<Rc<str>>,
+    bytes: usize,
+}
+
+impl Interner {
+    pub fn new() -> Interner {
+        Interner::default()
+    }
+
+    pub fn intern(&mut self, text: &str) -> Symbol {
+        if let Some(symbol) = self.symbols.get(text) {
+            return *symbol;
+        }
+
+        let symbol = Symbol(self.strings.len() as u32);
+        let text: Rc<str> = Rc::from(text);
+
+        self.bytes += text.len();
+        self.strings.push(text.clone());
+        self.symbols.insert(text, symbol);
+
+        symbol
+    }
+
+    pub fn resolve(&self, symbol: Symbol) -> &str {
+        &self.strings[symbol.0 as usize]
+    }
+
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+
+    // bytes of text stored, not counting the bookkeeping of the interner
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+}
+
+// token that does not borrow the parsed text
+#[derive(Clone, Debug, PartialEq)]
+pub struct OwnedSequence {
+    pub token: Token,
+    pub text: Symbol,
+    pub span: Span,
+}
+
+impl OwnedSequence {
+    pub fn text<'a>(&self, interner: &'a Interner) -> &'a str {
+        interner.resolve(self.text)
+    }
+}
+
+pub fn to_owned(tokens: &[Sequence<Token>], interner: &mut Interner) -> Vec<OwnedSequence> {
+    tokens
+        .iter()
+        .map(|t| OwnedSequence {
+            token: t.token.clone(),
+            text: interner.intern(t.text),
+            span: t.span,
+        })
+        .collect()
+}
+
+// memory that the text of the tokens would need if each one owned a copy of it
+pub fn uninterned_bytes(tokens: &[Sequence<Token>]) -> usize {
+    tokens
+        .iter()
+        .map(|t| t.text.len() + mem::size_of::<String>())
+        .sum()
+}
+
+// memory needed by the text of the tokens when they are interned
+pub fn interned_bytes(tokens: usize, interner: &Interner) -> usize {
+    tokens * mem::size_of::<Symbol>()
+        + interner.bytes()
+        + interner.len() * mem::size_of::<Rc<str>>() * 2
+}
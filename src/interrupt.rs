@@ -0,0 +1,52 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use std::{
+    process,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+// exit code of a process interrupted by SIGINT (128 + 2)
+pub const EXIT_CODE: u8 = 130;
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+// There are no signals to handle in wasm32.
+#[cfg(target_arch = "wasm32")]
+pub fn install() {}
+
+// The first Ctrl-C lets the file being processed finish and skips the rest, so that the
+// summary of the files processed so far is still printed. A second one exits at once.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn install() {
+    let handler = || {
+        if INTERRUPTED.swap(true, Ordering::SeqCst) {
+            process::exit(EXIT_CODE.into());
+        }
+    };
+
+    ctrlc::set_handler(handler).ok();
+}
+
+pub fn requested() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
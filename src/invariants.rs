@@ -0,0 +1,78 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::{
+    messages::Message,
+    parser::{Sequence, Token},
+};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+// The tokens must cover the whole input, in order and without overlapping, their spans must
+// match their text, only the last one may be empty and the line numbers must increase.
+pub fn check(input: &[u8], tokens: &[Sequence<Token>]) -> Result<(), Message> {
+    let mut offset = 0;
+    let mut line_number = 1;
+
+    for (index, t) in tokens.iter().enumerate() {
+        if t.span.start != offset {
+            return Err(Message::TokenGap(offset));
+        }
+
+        if input.get(t.span.start..t.span.end) != Some(t.text.as_bytes()) {
+            return Err(Message::SpanMismatch(t.span.start, t.span.end));
+        }
+
+        if t.text.is_empty() && index + 1 < tokens.len() {
+            return Err(Message::EmptyToken(offset));
+        }
+
+        if let Some(number) = t.token.line_after() {
+            if number <= line_number {
+                return Err(Message::LineNumberNotIncreasing(
+                    offset,
+                    number,
+                    line_number,
+                ));
+            }
+            line_number = number;
+        }
+
+        offset += t.text.len();
+    }
+
+    if offset != input.len() {
+        return Err(Message::TokensIncomplete(offset, input.len()));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,145 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use std::{
+    cell::RefCell,
+    collections::HashSet,
+    fs::{self, File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use crate::{messages::Message, terminal_helper::TerminalOutput};
+
+const STARTED: &str = "started";
+const COMPLETED: &str = "completed";
+
+// Progress of a batch run, written before and after processing each file, so that a run that
+// dies can be resumed and the file being processed at that moment identified.
+struct Journal {
+    file: File,
+    path: PathBuf,
+    completed: HashSet<PathBuf>,
+    crashed: Vec<PathBuf>,
+    skipped: usize,
+}
+
+thread_local! {
+    static JOURNAL: RefCell<Option<Journal>> = const { RefCell::new(None) };
+}
+
+fn read_previous_run(path: &Path) -> (HashSet<PathBuf>, Vec<PathBuf>) {
+    let mut completed = HashSet::new();
+    let mut started = Vec::new();
+
+    for line in fs::read_to_string(path).unwrap_or_default().lines() {
+        match line.split_once('\t') {
+            Some((STARTED, file)) => started.push(PathBuf::from(file)),
+            Some((COMPLETED, file)) => {
+                completed.insert(PathBuf::from(file));
+            }
+            _ => {}
+        }
+    }
+
+    let crashed = started
+        .into_iter()
+        .filter(|f| !completed.contains(f))
+        .collect();
+
+    (completed, crashed)
+}
+
+pub fn open(path: &Path, resume: bool) -> Result<(), Message> {
+    let (completed, crashed) = match resume {
+        true => read_previous_run(path),
+        false => Default::default(),
+    };
+
+    let file = OpenOptions::new()
+        .create(true)
+        .append(resume)
+        .write(true)
+        .truncate(!resume)
+        .open(path)
+        .map_err(|e| Message::JournalUnwritable(path.display().to_string(), e.to_string()))?;
+
+    JOURNAL.with(|j| {
+        *j.borrow_mut() = Some(Journal {
+            file,
+            path: path.to_path_buf(),
+            completed,
+            crashed,
+            skipped: 0,
+        })
+    });
+
+    Ok(())
+}
+
+// files completed by the previous run, or that were being processed when it died, are skipped
+pub fn skip(file: &Path) -> bool {
+    JOURNAL.with(|j| match j.borrow_mut().as_mut() {
+        Some(journal)
+            if journal.completed.contains(file) || journal.crashed.iter().any(|f| f == file) =>
+        {
+            journal.skipped += 1;
+            true
+        }
+        _ => false,
+    })
+}
+
+fn record(state: &str, file: &Path) {
+    JOURNAL.with(|j| {
+        if let Some(journal) = j.borrow_mut().as_mut() {
+            // flushed at once, as the process may not live to do it later
+            writeln!(journal.file, "{state}\t{}", file.display()).ok();
+            journal.file.flush().ok();
+        }
+    });
+}
+
+pub fn start(file: &Path) {
+    record(STARTED, file);
+}
+
+pub fn complete(file: &Path) {
+    record(COMPLETED, file);
+}
+
+pub fn report(output: &mut TerminalOutput) {
+    JOURNAL.with(|j| {
+        if let Some(journal) = j.borrow().as_ref() {
+            if journal.skipped > 0 {
+                output.writeln_info(Message::JournalSkipped(
+                    journal.skipped,
+                    journal.path.display().to_string(),
+                ));
+            }
+
+            for file in &journal.crashed {
+                output.writeln_error(Message::AbnormalTermination(file.display().to_string()));
+            }
+        }
+    });
+}
@@ -0,0 +1,54 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use std::time::Duration;
+
+// Bounds on the inputs accepted, so that pathological files fail with a diagnostic instead of
+// exhausting the memory or the time of a batch run
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Limits {
+    pub max_file_size: u64,
+    pub max_tokens: usize,
+    pub max_comment_depth: usize,
+    pub max_raw_string_hashes: usize,
+    pub timeout: Option<Duration>,
+}
+
+impl Limits {
+    pub const DEFAULT_MAX_FILE_SIZE: u64 = 64 * 1024 * 1024;
+    pub const DEFAULT_MAX_TOKENS: usize = 10_000_000;
+    pub const DEFAULT_MAX_COMMENT_DEPTH: usize = 1024;
+    // same limit as rustc
+    pub const DEFAULT_MAX_RAW_STRING_HASHES: usize = 255;
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_file_size: Self::DEFAULT_MAX_FILE_SIZE,
+            max_tokens: Self::DEFAULT_MAX_TOKENS,
+            max_comment_depth: Self::DEFAULT_MAX_COMMENT_DEPTH,
+            max_raw_string_hashes: Self::DEFAULT_MAX_RAW_STRING_HASHES,
+            timeout: None,
+        }
+    }
+}
@@ -0,0 +1,75 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use crate::parser::{Sequence, Token};
+
+/// Offsets where the lines of a text begin, to find the line of a span in logarithmic time.
+/// Lines are numbered from 1, and a line break is a line feed (optionally preceded by a carriage
+/// return), as for the line numbers of the parsed tokens.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LineIndex {
+    starts: Vec<usize>,
+}
+
+impl LineIndex {
+    pub fn new(text: &str) -> LineIndex {
+        let starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+
+        LineIndex { starts }
+    }
+
+    /// Index of the text of the parsed tokens, whose spans are offsets in that text.
+    pub fn from_tokens(tokens: &[Sequence<Token>]) -> LineIndex {
+        let mut starts = vec![0];
+
+        for t in tokens {
+            starts.extend(
+                t.text
+                    .match_indices('\n')
+                    .map(|(i, _)| t.span.start + i + 1),
+            );
+        }
+
+        LineIndex { starts }
+    }
+
+    /// Line of the byte at the offset. Offsets past the end of the text are in the last line.
+    pub fn line(&self, offset: usize) -> usize {
+        self.starts.partition_point(|&start| start <= offset)
+    }
+
+    /// Offset where the line begins, `None` for lines past the end of the text.
+    pub fn line_start(&self, line: usize) -> Option<usize> {
+        self.starts.get(line.checked_sub(1)?).copied()
+    }
+
+    /// Offsets where each line begins, in order.
+    pub fn line_starts(&self) -> &[usize] {
+        &self.starts
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.starts.len()
+    }
+}
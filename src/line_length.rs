@@ -0,0 +1,92 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use crate::{
+    diagnostics::Diagnostic,
+    error_codes::ErrorCode,
+    messages::Message,
+    parser::{Sequence, Span, Token},
+};
+
+// Lines longer than `max` characters, each reported with the span of the part that exceeds
+// the maximum. With `allow_long_literals`, lines that exceed it inside a string literal or a
+// URL in a comment are allowed, as they cannot be broken.
+pub fn lint<'a>(
+    text: &'a str,
+    tokens: &[Sequence<'a, Token>],
+    max: usize,
+    allow_long_literals: bool,
+) -> Vec<Diagnostic<'a>> {
+    let mut diagnostics = Vec::new();
+    let mut line_start = 0;
+
+    for (n, line) in text.split_inclusive('\n').enumerate() {
+        let start = line_start;
+        line_start += line.len();
+
+        let content = line.trim_end_matches(['\n', '\r']);
+        let width = content.chars().count();
+        let Some((overflow, _)) = content.char_indices().nth(max) else {
+            continue;
+        };
+
+        if allow_long_literals && is_unbreakable(tokens, start + overflow) {
+            continue;
+        }
+
+        diagnostics.push(Diagnostic {
+            code: ErrorCode::W0009,
+            message: Message::LineTooLong(width, max).to_string(),
+            line: n + 1,
+            span: Span {
+                start: start + overflow,
+                end: start + content.len(),
+            },
+            text: &content[overflow..],
+        });
+    }
+
+    diagnostics
+}
+
+fn is_unbreakable(tokens: &[Sequence<Token>], offset: usize) -> bool {
+    let Some(t) = tokens.get(tokens.partition_point(|t| t.span.end <= offset)) else {
+        return false;
+    };
+
+    match t.token {
+        Token::StrLiteral => true,
+        Token::SingleLineComment | Token::MultiLineComment => {
+            // word of the comment where the line exceeds the maximum
+            let at = offset - t.span.start;
+            let begin = t.text[..at].rfind(char::is_whitespace).map_or(0, |i| {
+                i + t.text[i..].chars().next().map_or(1, char::len_utf8)
+            });
+            let end = t.text[at..]
+                .find(char::is_whitespace)
+                .map_or(t.text.len(), |i| at + i);
+
+            t.text[begin..end].contains("://")
+        }
+        _ => false,
+    }
+}
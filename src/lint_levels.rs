@@ -0,0 +1,57 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+use serde::Deserialize;
+use std::{collections::HashMap, sync::OnceLock};
+
+use crate::error_codes::{ErrorCode, Severity};
+
+// the level of a code set in the [lints] table of xr.toml, which replaces its own severity
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LintLevel {
+    Allow,
+    Warn,
+    Deny,
+}
+
+static LEVELS: OnceLock<HashMap<ErrorCode, LintLevel>> = OnceLock::new();
+
+pub fn set(levels: HashMap<ErrorCode, LintLevel>) {
+    LEVELS.set(levels).ok();
+}
+
+fn level(code: ErrorCode) -> Option<LintLevel> {
+    LEVELS.get()?.get(&code).copied()
+}
+
+// allowed codes are not reported at all
+pub fn is_allowed(code: ErrorCode) -> bool {
+    level(code) == Some(LintLevel::Allow)
+}
+
+pub fn severity(code: ErrorCode) -> Severity {
+    match level(code) {
+        Some(LintLevel::Warn) => Severity::Warning,
+        Some(LintLevel::Deny) => Severity::Error,
+        _ => code.severity(),
+    }
+}
@@ -0,0 +1,289 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use std::{borrow::Cow, fmt};
+
+use crate::parser::{Sequence, Token};
+
+/// Kind of a string literal, given by its prefix.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum StringKind {
+    /// `"..."` or `r"..."`
+    Str,
+    /// `b"..."` or `br"..."`
+    Byte,
+    /// `c"..."` or `cr"..."`
+    C,
+}
+
+impl StringKind {
+    /// Prefix of the literals of this kind, without the `r` of raw strings.
+    pub fn prefix(self) -> &'static str {
+        match self {
+            Self::Str => "",
+            Self::Byte => "b",
+            Self::C => "c",
+        }
+    }
+}
+
+/// A string literal split into its delimiters and contents, such as `br#"a"b"#`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StringLiteral<'a> {
+    pub kind: StringKind,
+    /// Number of hashes of a raw string, `None` for strings that are not raw
+    pub hashes: Option<usize>,
+    /// Text between the quotes, as written (with its escapes)
+    pub contents: &'a str,
+}
+
+/// Escape of a string literal that does not have a value. Offsets are in bytes from the start of
+/// the contents of the literal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum UnescapeError {
+    /// Backslash followed by a character that does not begin an escape, or by nothing
+    UnknownEscape { offset: usize },
+    /// `\x` escape without two hexadecimal digits, or above `\x7f` in a string that is neither a
+    /// byte string nor a C string
+    InvalidHexEscape { offset: usize },
+    /// `\u{...}` escape that is malformed or not a Unicode scalar value, or in a byte string
+    InvalidUnicodeEscape { offset: usize },
+    /// The value of a byte string or C string is not valid UTF-8
+    NotUtf8,
+}
+
+impl fmt::Display for UnescapeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownEscape { offset } => write!(f, "unknown escape at offset {offset}"),
+            Self::InvalidHexEscape { offset } => write!(f, "invalid \\x escape at offset {offset}"),
+            Self::InvalidUnicodeEscape { offset } => {
+                write!(f, "invalid \\u escape at offset {offset}")
+            }
+            Self::NotUtf8 => f.write_str("the value is not valid UTF-8"),
+        }
+    }
+}
+
+impl std::error::Error for UnescapeError {}
+
+impl<'a> StringLiteral<'a> {
+    /// Splits the text of a string literal, or returns `None` when it is not a complete string
+    /// literal.
+    pub fn parse(text: &'a str) -> Option<StringLiteral<'a>> {
+        let quote = text.find('"')?;
+        let (prefix, rest) = text.split_at(quote);
+
+        let without_hashes = prefix.trim_end_matches('#');
+        let hash_count = prefix.len() - without_hashes.len();
+
+        let (kind, hashes) = match without_hashes.strip_suffix('r') {
+            Some(kind) => (kind, Some(hash_count)),
+            None if hash_count == 0 => (without_hashes, None),
+            None => return None,
+        };
+
+        let kind = match kind {
+            "" => StringKind::Str,
+            "b" => StringKind::Byte,
+            "c" => StringKind::C,
+            _ => return None,
+        };
+
+        let end = rest
+            .len()
+            .checked_sub(hash_count + 1)
+            .filter(|&end| end > 0)?;
+        let closing = &rest[end..];
+        if !closing.starts_with('"') || closing[1..].bytes().any(|b| b != b'#') {
+            return None;
+        }
+
+        Some(StringLiteral {
+            kind,
+            hashes,
+            contents: &rest[1..end],
+        })
+    }
+
+    pub fn is_raw(&self) -> bool {
+        self.hashes.is_some()
+    }
+
+    /// Value of the literal as bytes, with the escapes of strings that are not raw resolved.
+    /// The contents are borrowed when they have no escapes.
+    pub fn unescape_bytes(&self) -> Result<Cow<'a, [u8]>, UnescapeError> {
+        if self.is_raw() || !self.contents.contains('\\') {
+            return Ok(Cow::Borrowed(self.contents.as_bytes()));
+        }
+
+        unescape(self.contents, self.kind).map(Cow::Owned)
+    }
+
+    /// Value of the literal as text, which for byte strings and C strings is only available
+    /// when it is valid UTF-8.
+    pub fn unescape(&self) -> Result<Cow<'a, str>, UnescapeError> {
+        match self.unescape_bytes()? {
+            Cow::Borrowed(_) => Ok(Cow::Borrowed(self.contents)),
+            Cow::Owned(bytes) => String::from_utf8(bytes)
+                .map(Cow::Owned)
+                .map_err(|_| UnescapeError::NotUtf8),
+        }
+    }
+}
+
+fn unescape(contents: &str, kind: StringKind) -> Result<Vec<u8>, UnescapeError> {
+    let mut value = Vec::with_capacity(contents.len());
+    let mut chars = contents.char_indices().peekable();
+
+    while let Some((offset, c)) = chars.next() {
+        if c != '\\' {
+            value.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+            continue;
+        }
+
+        let Some((_, escape)) = chars.next() else {
+            return Err(UnescapeError::UnknownEscape { offset });
+        };
+
+        match escape {
+            'n' => value.push(b'\n'),
+            'r' => value.push(b'\r'),
+            't' => value.push(b'\t'),
+            '0' => value.push(0),
+            '\\' | '\'' | '"' => value.push(escape as u8),
+            'x' => {
+                let invalid = UnescapeError::InvalidHexEscape { offset };
+                let digits = contents
+                    .get(offset + 2..offset + 4)
+                    .filter(|d| d.bytes().all(|b| b.is_ascii_hexdigit()))
+                    .ok_or(invalid)?;
+                let byte = u8::from_str_radix(digits, 16).map_err(|_| invalid)?;
+
+                if byte > 0x7f && kind == StringKind::Str {
+                    return Err(invalid);
+                }
+
+                value.push(byte);
+                chars.nth(1);
+            }
+            'u' if kind != StringKind::Byte => {
+                let invalid = UnescapeError::InvalidUnicodeEscape { offset };
+                let rest = contents[offset + 2..].strip_prefix('{').ok_or(invalid)?;
+                let len = rest.find('}').ok_or(invalid)?;
+
+                let digits = rest[..len].replace('_', "");
+                if digits.is_empty() || digits.len() > 6 || rest.starts_with('_') {
+                    return Err(invalid);
+                }
+                let c = u32::from_str_radix(&digits, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or(invalid)?;
+
+                value.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+
+                // the braces and the digits
+                let end = offset + 3 + len;
+                while chars.next_if(|&(i, _)| i <= end).is_some() {}
+            }
+            'u' => return Err(UnescapeError::InvalidUnicodeEscape { offset }),
+            // a line continuation skips the line break and the whitespace that follows it
+            '\n' | '\r' => while chars.next_if(|(_, c)| c.is_ascii_whitespace()).is_some() {},
+            _ => return Err(UnescapeError::UnknownEscape { offset }),
+        }
+    }
+
+    Ok(value)
+}
+
+/// Smallest number of hashes of a raw string with the given contents, so that no quote in them
+/// ends the literal.
+pub fn raw_hashes(text: &str) -> usize {
+    let mut hashes = 0;
+
+    for (i, _) in text.match_indices('"') {
+        let run = text[i + 1..].bytes().take_while(|&b| b == b'#').count();
+        hashes = hashes.max(run + 1);
+    }
+
+    hashes
+}
+
+// characters that a raw string cannot have (carriage returns) or that would be invisible in it
+fn needs_escape(c: char) -> bool {
+    c.is_control() && !matches!(c, '\n' | '\t')
+}
+
+/// Raw string literal with the given value, or `None` when the text has control characters
+/// other than line breaks and tabs, which only an escape can show.
+pub fn raw_string_literal(text: &str) -> Option<String> {
+    if text.chars().any(needs_escape) {
+        return None;
+    }
+
+    let hashes = "#".repeat(raw_hashes(text));
+    Some(format!("r{hashes}\"{text}\"{hashes}"))
+}
+
+/// String literal with the given value, with escapes for backslashes, quotes and control
+/// characters other than line breaks and tabs.
+pub fn escaped_string_literal(text: &str) -> String {
+    let mut literal = String::with_capacity(text.len() + 2);
+    literal.push('"');
+
+    for c in text.chars() {
+        match c {
+            '\\' => literal.push_str("\\\\"),
+            '"' => literal.push_str("\\\""),
+            '\r' => literal.push_str("\\r"),
+            '\0' => literal.push_str("\\0"),
+            c if needs_escape(c) => literal.push_str(&format!("\\u{{{:x}}}", c as u32)),
+            c => literal.push(c),
+        }
+    }
+
+    literal.push('"');
+    literal
+}
+
+/// String literal with the given value, in the form that needs no escapes when there is one: a
+/// raw string for text with backslashes or quotes, a plain string otherwise.
+pub fn string_literal(text: &str) -> String {
+    match text.contains(['\\', '"']) {
+        true => raw_string_literal(text).unwrap_or_else(|| escaped_string_literal(text)),
+        false => escaped_string_literal(text),
+    }
+}
+
+impl<'a> Sequence<'a, Token> {
+    /// Delimiters and contents of a `StrLiteral` token, `None` for other tokens and for string
+    /// literals that are not terminated.
+    pub fn string_literal(&self) -> Option<StringLiteral<'a>> {
+        match self.token {
+            Token::StrLiteral => StringLiteral::parse(self.text),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,59 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use std::{borrow::Cow, path::Path};
+
+// Windows refuses to open paths longer than MAX_PATH, unless they are given in the verbatim
+// form (\\?\C:\dir\file or \\?\UNC\server\share\file), which must be absolute and normalized.
+#[cfg(windows)]
+pub fn extended(path: &Path) -> Cow<'_, Path> {
+    use std::{ffi::OsString, path::absolute};
+
+    const MAX_PATH: usize = 260;
+    const VERBATIM: &str = r"\\?\";
+
+    let text = path.as_os_str().to_string_lossy();
+    if text.len() < MAX_PATH || text.starts_with(VERBATIM) {
+        return Cow::Borrowed(path);
+    }
+
+    let Ok(absolute) = absolute(path) else {
+        return Cow::Borrowed(path);
+    };
+
+    let absolute = absolute.into_os_string();
+    let mut verbatim = OsString::from(VERBATIM);
+    match absolute.to_str().and_then(|a| a.strip_prefix(r"\\")) {
+        Some(share) => {
+            verbatim.push(r"UNC\");
+            verbatim.push(share);
+        }
+        None => verbatim.push(&absolute),
+    }
+
+    Cow::Owned(verbatim.into())
+}
+
+#[cfg(not(windows))]
+pub fn extended(path: &Path) -> Cow<'_, Path> {
+    Cow::Borrowed(path)
+}
@@ -21,97 +21,1019 @@
 */
 
 use std::{
-    fs::{self, File},
-    io::Write,
-    path::PathBuf,
-    time::Instant,
+    collections::HashSet,
+    fs,
+    io::{self, IsTerminal},
+    path::{Path, PathBuf},
+    process::ExitCode,
+    sync::OnceLock,
+    time::{Duration, Instant},
 };
 
+use xr::{
+    comments,
+    error_codes::{self, ErrorCode},
+    highlight, interner, invariants,
+    limits::{self, Limits},
+    messages::{self, Message},
+    panic_message,
+    parser::{self, Sequence, Token},
+    visitor::{self, Rewritten},
+};
+
+mod amalgamate;
+
+mod analyze;
+
+mod archive_input;
+use archive_input::Entry;
+
 mod arguments;
-use arguments::Args;
+use arguments::{
+    Args, BuildArgs, CheckArgs, Command, FailOn, GenerateArgs, OutputEncoding, ReportFormat,
+};
+
+mod backup;
+
+mod baseline;
+use baseline::Baseline;
+
+mod bidi;
+
+mod binary;
+
+mod cache;
+
+mod comment_style;
+
+mod config;
+
+mod confusables;
+
+mod control_chars;
+
+#[cfg(unix)]
+mod daemon;
+
+mod diagnostics;
+
+mod diff_preview;
+use diagnostics::{Diagnostic, OwnedDiagnostic};
+
+mod duplicates;
+
+mod file_result;
+use file_result::FileResult;
+
+mod formatter;
+
+mod git;
+
+mod header;
+use header::Header;
+
+mod hook;
+
+mod html_report;
+
+mod hyperlinks;
+
+mod indentation;
+use indentation::Reindenter;
+
+mod interrupt;
+
+mod isolation;
+
+mod journal;
+
+mod long_paths;
+
+mod line_length;
+
+mod lint_levels;
+
+mod manifest;
+
+mod markers;
 
-mod parser;
-use parser::Token;
+mod mod_files;
+
+mod plan;
+use plan::Plan;
+
+mod preflight;
+
+mod profile;
+
+mod project;
+use project::Project;
+
+mod raw_strings;
+
+mod remote_input;
+
+mod replay;
+
+mod reporter;
+
+mod reproducible;
+
+mod run_lock;
+
+mod rustfmt;
+
+mod secrets;
+
+mod sink;
+use sink::Outcome;
+
+mod snapshots;
+
+mod spelling;
+use spelling::Dictionary;
+
+mod split;
+
+mod string_lints;
+
+mod summary;
+
+mod suppressions;
 
 mod terminal_helper;
-use terminal_helper::TerminalOutput;
+use terminal_helper::{TerminalOutput, Verbosity};
+
+mod terminal_width;
+
+mod text_diff;
+
+mod token_dump;
+
+mod trace;
 
-fn main() {
-    let mut output = TerminalOutput::new();
+mod verify;
+use verify::SourceMap;
 
-    let args = Args::obtain();
+mod verify_generated;
+
+mod watch;
+
+fn main() -> ExitCode {
+    let mut args = Args::obtain();
+
+    messages::set_language(args.language());
+    invariants::set_enabled(args.validate());
+
+    if args.profile() {
+        profile::enable(args.stats_format());
+    }
+
+    let mut output = TerminalOutput::new(args.color(), args.verbosity());
+    output.set_reporters(args.reporters());
+    output.set_width(args.width());
+    output.set_hyperlinks(args.hyperlinks(io::stdout().is_terminal()));
+
+    if let Command::Explain(explain) = args.command() {
+        explain_error_code(&explain.code, &mut output);
+        return exit_code(&output, args.fail_on());
+    }
 
     output.writeln("XR Parser");
-    let version = env!("CARGO_PKG_VERSION");
-    output.writeln(format!("version {version}"));
+    output.writeln(Message::Version(env!("CARGO_PKG_VERSION")));
 
-    let filenames = args.files_to_process();
+    let config = match args.apply_config() {
+        Ok(config) => config,
+        Err(e) => {
+            output.writeln_error(e);
+            return ExitCode::FAILURE;
+        }
+    };
+    if let Some(path) = &config {
+        output.writeln_verbose(Message::ConfigurationUsed(path.display().to_string()));
+    }
 
-    for f in &filenames {
-        process_file(f, &mut output);
+    LIMITS.set(args.limits()).ok();
+    lint_levels::set(args.lint_levels());
+
+    if let Some(generate) = args.command().generate_args() {
+        if generate.cache_dir().is_some() || generate.cache_url().is_some() {
+            cache::enable(
+                generate.cache_dir(),
+                generate.cache_url(),
+                generate.cache_token(),
+                generate.cache_read_only(),
+            );
+        }
     }
 
-    output.writeln_success(format!("{} file(s) processed", filenames.len()));
+    if args.report().is_some() {
+        html_report::enable();
+    }
+
+    if let Some(path) = args.journal() {
+        if let Err(e) = journal::open(path, args.resume()) {
+            output.writeln_error(e);
+            return ExitCode::FAILURE;
+        }
+    }
+
+    if let Some(path) = args.trace_parser() {
+        if let Err(e) = trace::open(path) {
+            output.writeln_error(e);
+            return ExitCode::FAILURE;
+        }
+    }
+
+    isolation::install();
+
+    // the daemon is stopped by Ctrl-C as usual, as it waits for requests
+    if !matches!(args.command(), Command::Daemon(_)) {
+        interrupt::install();
+    }
+
+    match args.command() {
+        Command::Build(build) => {
+            // a plan, a verification and diffs alone do not write the generated files
+            let _lock = match build.plan || build.verify_generated || build.diff_only {
+                true => None,
+                false => match run_lock::acquire(&build.lock_dir(), build.wait, &mut output) {
+                    Ok(lock) => Some(lock),
+                    Err(e) => {
+                        output.writeln_error(e);
+                        return ExitCode::FAILURE;
+                    }
+                },
+            };
+
+            if let Some(header) = obtain_header(&build.generate, &mut output) {
+                // unlike xr check, the build needs every file before the first one is generated,
+                // because the project and the mod files depend on the whole set
+                let filenames = build.input.files_to_process(&mut output);
+                let project = Project::new(build, &filenames);
+
+                if let Some(path) = &build.emit_manifest {
+                    manifest::enable(path);
+                }
+                if build.verify_generated {
+                    verify_generated::enable(&header);
+                }
+                if !enable_backups(&build.generate, &header, &mut output) {
+                    return ExitCode::FAILURE;
+                }
+                if build.interactive {
+                    diff_preview::enable(diff_preview::Mode::Confirm, &header);
+                } else if build.diff_only {
+                    diff_preview::enable(diff_preview::Mode::PrintOnly, &header);
+                } else if build.diff {
+                    diff_preview::enable(diff_preview::Mode::Print, &header);
+                }
+
+                match &build.archive {
+                    Some(archive) => {
+                        match sink::open_archive(archive, build.generate.reproducible()) {
+                            Ok(_) => {
+                                build_files(build, &project, &filenames, &header, &mut output);
+                                sink::finish(archive, &mut output);
+                            }
+                            Err(e) => output.writeln_error(e),
+                        }
+                    }
+                    None => {
+                        build_files(build, &project, &filenames, &header, &mut output);
+                    }
+                }
+
+                manifest::write(&mut output);
+                if build.verify_generated {
+                    verify_generated::report(&mut output);
+                }
+            }
+        }
+        Command::Check(check) => check_files(check, &mut output),
+        Command::Fmt(fmt) => formatter::run(fmt, &mut output),
+        Command::Tokens(tokens) => token_dump::run(tokens, &mut output),
+        Command::Explain(_) => {}
+        Command::Replay(replay) => replay::run(replay, &mut output),
+        Command::Test(test) => snapshots::run(test, &mut output),
+        Command::InstallHook(install) => hook::run(install, &mut output),
+        Command::ScanSecrets(input) => secrets::run(input, &mut output),
+        Command::Analyze(analyze) => analyze::run(analyze, &mut output),
+        Command::VerifyManifest(verify) => manifest::verify(&verify.manifest, &mut output),
+        Command::Watch(watch) => {
+            if let Some(header) = obtain_header(&watch.build.generate, &mut output) {
+                watch::run(watch, &header, config.as_deref(), &mut output);
+            }
+        }
+        #[cfg(unix)]
+        Command::Daemon(daemon) => {
+            if let Some(header) = obtain_header(&daemon.generate, &mut output) {
+                if enable_backups(&daemon.generate, &header, &mut output) {
+                    daemon::run(daemon, &header, &mut output);
+                }
+            }
+        }
+        #[cfg(not(unix))]
+        Command::Daemon(_) => output.writeln_error(Message::DaemonUnsupported),
+    }
+
+    if let Some((ReportFormat::Html, path)) = args.report() {
+        html_report::write(&path, &mut output);
+    }
+
+    summary::report(&mut output);
+    cache::report(&mut output);
+    journal::report(&mut output);
+    profile::report(&mut output);
+    output.run_finished();
+
+    if interrupt::requested() {
+        output.writeln_warning(Message::Interrupted);
+        return ExitCode::from(interrupt::EXIT_CODE);
+    }
+
+    exit_code(&output, args.fail_on())
 }
 
-fn process_file(file: &PathBuf, output: &mut TerminalOutput) {
-    let filename = file.to_str().unwrap_or_default();
+static LIMITS: OnceLock<Limits> = OnceLock::new();
+
+fn configured_limits() -> &'static Limits {
+    LIMITS.get_or_init(Limits::default)
+}
 
-    output.writeln_info(format!("Processing file '{filename}'"));
+// problems below the threshold are still reported, but do not fail the run
+fn exit_code(output: &TerminalOutput, fail_on: FailOn) -> ExitCode {
+    let failed = match fail_on {
+        FailOn::Error => output.error_count() > 0,
+        FailOn::Warning => output.error_count() + output.warning_count() > 0,
+        FailOn::Never => false,
+    };
 
-    match fs::read_to_string(file) {
-        Ok(contents) => generate_file(file, contents, output),
-        Err(_) => output.writeln_error(format!("Could not read file '{filename}'")),
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
     }
 }
 
-fn generate_file(original_file: &PathBuf, contents: String, output: &mut TerminalOutput) {
-    let mut new_file = original_file.clone();
+fn explain_error_code(code: &str, output: &mut TerminalOutput) {
+    match ErrorCode::parse(code) {
+        Some(code) => output.writeln_result(code.explanation()),
+        None => output.writeln_error(Message::UnknownErrorCode(code.to_string())),
+    }
+}
 
-    if !new_file.set_extension("rs") {
-        output.writeln_error("Failed to generate output file");
+fn obtain_header(args: &GenerateArgs, output: &mut TerminalOutput) -> Option<Header> {
+    let header = if !args.header_enabled() {
+        Header::none()
+    } else if let Some(path) = args.header_file() {
+        match Header::from_file(path) {
+            Ok(header) => header,
+            Err(_) => {
+                output.writeln_error(Message::HeaderUnreadable);
+                return None;
+            }
+        }
     } else {
-        let filename = new_file.to_str().unwrap_or_default();
+        Header::banner(args.timestamp_enabled())
+    };
 
-        let start = Instant::now();
-        let result = parser::parse(&contents);
-        let duration = start.elapsed();
-        output.writeln(format!("file parsed in {:?}", duration));
+    match args.license_file() {
+        Some(path) => match header.with_license_file(path) {
+            Ok(header) => Some(header),
+            Err(_) => {
+                output.writeln_error(Message::LicenseUnreadable(path.display().to_string()));
+                None
+            }
+        },
+        None => Some(header),
+    }
+}
+
+// returns the number of files visited, which is smaller than the number of files when the run
+// is interrupted
+fn for_each_file<F: FnMut(&Path, String, &mut TerminalOutput)>(
+    filenames: &[PathBuf],
+    output: &mut TerminalOutput,
+    mut process: F,
+) -> usize {
+    preflight::report(filenames, output);
+
+    for (i, file) in filenames.iter().enumerate() {
+        if interrupt::requested() {
+            return i;
+        }
+        visit_file(file, || read_input(file), output, &mut process);
+    }
+
+    filenames.len()
+}
+
+// the contents are read lazily, so that a file skipped by the journal is not read
+fn visit_file<F: FnMut(&Path, String, &mut TerminalOutput)>(
+    file: &Path,
+    read: impl FnOnce() -> Result<String, InputError>,
+    output: &mut TerminalOutput,
+    process: &mut F,
+) {
+    if journal::skip(file) {
+        return;
+    }
+
+    journal::start(file);
+    html_report::begin_file(file);
+    summary::begin_file(file);
+    profile::begin_file(file);
+    trace::begin_file(file);
+    output.file_started(file);
+    process_file(file, read, output, process);
+    output.file_finished(file);
+    diff_preview::end_file(output);
+    manifest::end_file();
+    journal::complete(file);
+}
+
+fn process_file<F: FnMut(&Path, String, &mut TerminalOutput)>(
+    file: &Path,
+    read: impl FnOnce() -> Result<String, InputError>,
+    output: &mut TerminalOutput,
+    process: &mut F,
+) {
+    let filename = file.display().to_string();
 
-        if let Ok(file) = File::create(&new_file) {
-            if !write_output_to_file(file, result, output) {
-                output.writeln_error(format!("Failed to write to file '{filename}'"));
+    output.writeln_info(output.fit_path(&filename, Message::ProcessingFile));
+
+    let contents = match read() {
+        Ok(contents) => contents,
+        Err(InputError::Skipped(warning)) => {
+            output.writeln_warning(warning);
+            return;
+        }
+        Err(InputError::Failed(error)) => {
+            output.writeln_error(error);
+            return;
+        }
+    };
+
+    manifest::begin_file(file, contents.as_bytes());
+
+    let result = isolation::run(|| process(file, contents, output));
+
+    if let Err(payload) = result {
+        output.writeln_error(Message::InternalError(
+            file.display().to_string(),
+            panic_message(payload.as_ref()).to_string(),
+        ));
+    }
+}
+
+// why an input is not processed: a file that is too large or binary is skipped, while the
+// others failed to be read
+enum InputError {
+    Skipped(Message),
+    Failed(Message),
+}
+
+fn read_input(file: &Path) -> Result<String, InputError> {
+    let filename = file.display().to_string();
+
+    let path = long_paths::extended(file);
+    let max_size = configured_limits().max_file_size;
+    match fs::metadata(&path) {
+        Ok(metadata) if metadata.len() > max_size => {
+            return Err(InputError::Skipped(Message::FileTooLarge(
+                filename,
+                metadata.len(),
+                max_size,
+            )));
+        }
+        _ => {}
+    }
+
+    let unreadable = |_| Message::FileUnreadable(filename.clone());
+    let bytes = match archive_input::split(file) {
+        _ if remote_input::is_url(file) => remote_input::fetch(file, max_size),
+        Some((archive, entry)) => match archive_input::read(&archive, &entry) {
+            Ok(Entry::Contents(bytes)) => Ok(bytes),
+            Ok(Entry::TooLarge(size)) => {
+                return Err(InputError::Skipped(Message::FileTooLarge(
+                    filename, size, max_size,
+                )))
             }
+            Err(e) => Err(unreadable(e)),
+        },
+        None => fs::read(&path).map_err(unreadable),
+    }
+    .map_err(InputError::Failed)?;
+
+    // the size of a remote file is only known once it is read
+    if bytes.len() as u64 > max_size {
+        return Err(InputError::Skipped(Message::FileTooLarge(
+            filename,
+            bytes.len() as u64,
+            max_size,
+        )));
+    }
+    if binary::looks_binary(&bytes) {
+        return Err(InputError::Skipped(Message::FileBinary(filename)));
+    }
+
+    String::from_utf8(bytes).map_err(|_| InputError::Failed(Message::FileUnreadable(filename)))
+}
+
+fn parse_file<'a>(contents: &'a str, output: &mut TerminalOutput) -> Vec<Sequence<'a, Token>> {
+    let (result, duration) = parse_timed(contents, output);
+    output.writeln(Message::FileParsed(duration));
+    result
+}
+
+fn parse_timed<'a>(
+    contents: &'a str,
+    output: &mut TerminalOutput,
+) -> (Vec<Sequence<'a, Token>>, Duration) {
+    let start = Instant::now();
+    let result = parser::parse_with_limits(contents, configured_limits());
+    let duration = start.elapsed();
+    profile::record(&result, duration);
+    trace::record(output);
+
+    if invariants::enabled() {
+        if let Err(e) = invariants::check(contents.as_bytes(), &result) {
+            // reported by for_each_file as an internal error of the file
+            panic!("{e}");
+        }
+    }
+
+    (result, duration)
+}
+
+// returns the files for which errors or warnings were reported
+fn build_files(
+    args: &BuildArgs,
+    project: &Project,
+    filenames: &[PathBuf],
+    header: &Header,
+    output: &mut TerminalOutput,
+) -> Vec<PathBuf> {
+    if let Some(path) = &args.amalgamate {
+        amalgamate::build(args, path, filenames, header, output);
+        return Vec::new();
+    }
+
+    let plan = Plan::new(args, project, filenames, output);
+
+    if args.plan {
+        plan.print(output);
+        return Vec::new();
+    }
+
+    let inputs = plan.inputs();
+    let mut empty = 0;
+    let mut with_problems = Vec::new();
+
+    let processed = for_each_file(&inputs, output, |file, contents, output| {
+        let Some(new_file) = plan.output_of(file) else {
+            return;
+        };
+
+        let problems = output.error_count() + output.warning_count();
+
+        let result = if contents.trim().is_empty() {
+            empty += 1;
+            generate_empty_file(file, new_file, &contents, &args.generate, output)
         } else {
-            output.writeln_error(format!("Failed to create file '{filename}'"));
+            generate_file(file, new_file, contents, header, &args.generate, output)
+        };
+        result.report(output);
+
+        if output.error_count() + output.warning_count() > problems {
+            with_problems.push(file.to_path_buf());
+        }
+    });
+
+    // the mod files of an interrupted run would declare modules that were not generated
+    if !interrupt::requested() {
+        for mod_file in plan.mod_files() {
+            mod_files::write(mod_file, header, args.generate.output_encoding(), output);
+        }
+    }
+
+    output.writeln_success(Message::FilesProcessed(processed));
+    if empty > 0 {
+        output.writeln_info(Message::EmptyFiles(empty));
+    }
+
+    with_problems
+}
+
+// name of the file relative to the directory of another file, such as the baseline, so that it
+// does not depend on the directory from which xr is run
+fn relative_file_name(file: &Path, anchor: &Path) -> String {
+    let file = file.canonicalize().unwrap_or(file.to_path_buf());
+    let dir = match anchor.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.canonicalize().ok(),
+        _ => Path::new(".").canonicalize().ok(),
+    };
+
+    let name = match dir {
+        Some(dir) => file.strip_prefix(dir).unwrap_or(&file).to_path_buf(),
+        None => file,
+    };
+
+    name.to_string_lossy().replace('\\', "/")
+}
+
+fn check_files(args: &CheckArgs, output: &mut TerminalOutput) {
+    let recording = match &args.baseline {
+        Some(path) => args.update_baseline || !path.exists(),
+        None => false,
+    };
+
+    let mut baseline = match &args.baseline {
+        Some(path) if !recording => match Baseline::load(path) {
+            Ok(baseline) => Some(baseline),
+            Err(e) => {
+                output.writeln_error(e);
+                return;
+            }
+        },
+        Some(_) => Some(Baseline::default()),
+        None => None,
+    };
+
+    let mut known_errors = 0;
+    let mut todos = markers::Report::default();
+    let string_lints = args.string_lints();
+
+    let dictionary = match args.spell_check {
+        true => match Dictionary::load(&args.dictionaries, &args.spelling_words) {
+            Ok(dictionary) => Some(dictionary),
+            Err(e) => {
+                output.writeln_error(e);
+                return;
+            }
+        },
+        false => None,
+    };
+    let mut words_checked = 0;
+
+    let mut check = |file: &Path, contents: String, output: &mut TerminalOutput| {
+        let result = parse_file(&contents, output);
+        let mut diagnostics = diagnostics::lint(&result);
+        let found = comments::markers(&result);
+        if args.todos {
+            todos.record(file, &found);
+        }
+        if args.warn_todos {
+            diagnostics.extend(found.iter().map(|m| markers::diagnostic(&result, m)));
+        }
+        if string_lints.is_enabled() {
+            diagnostics.extend(string_lints.lint(&result));
+        }
+        if let Some(max) = args.max_line_length {
+            diagnostics.extend(line_length::lint(
+                &contents,
+                &result,
+                max,
+                args.allow_long_literals,
+            ));
+        }
+        if let Some(dictionary) = &dictionary {
+            let (misspelled, checked) = dictionary.lint(&result);
+            diagnostics.extend(misspelled);
+            words_checked += checked;
+        }
+        // applied once to the diagnostics of every lint, which are sorted by it
+        let mut diagnostics = diagnostics::apply_suppressions(&result, diagnostics);
+
+        if let (Some(baseline), Some(path)) = (&mut baseline, &args.baseline) {
+            let name = relative_file_name(file, path);
+
+            if recording {
+                diagnostics.iter().for_each(|d| baseline.record(&name, d));
+                known_errors += diagnostics.len();
+                diagnostics.clear();
+            } else {
+                let before = diagnostics.len();
+                diagnostics.retain(|d| !baseline.suppress(&name, d));
+                known_errors += before - diagnostics.len();
+            }
+        }
+
+        diagnostics::report(&diagnostics, output);
+        html_report::record(&result, &diagnostics);
+    };
+
+    // the summary of the files found by the preflight needs all of them before the first one is
+    // checked
+    let checked = if args.input.can_stream() && !output.is_verbose() {
+        args.input
+            .stream_files(output, read_input, |file, contents, output| {
+                visit_file(file, || contents, output, &mut check)
+            })
+    } else {
+        let filenames = args.input.files_to_process(output);
+        for_each_file(&filenames, output, &mut check)
+    };
+
+    if args.todos {
+        todos.print(output);
+    }
+    if dictionary.is_some() {
+        output.writeln_info(Message::WordsChecked(words_checked));
+    }
+
+    match (&baseline, &args.baseline) {
+        (Some(baseline), Some(path)) if recording => match baseline.save(path) {
+            Ok(_) => output.writeln_info(Message::BaselineCreated(
+                path.display().to_string(),
+                known_errors,
+            )),
+            Err(e) => output.writeln_error(e),
+        },
+        (Some(_), _) if known_errors > 0 => {
+            output.writeln_info(Message::BaselineSuppressed(known_errors))
+        }
+        _ => {}
+    }
+
+    output.writeln_success(Message::FilesChecked(checked));
+}
+
+fn generate_file(
+    input: &Path,
+    new_file: &Path,
+    contents: String,
+    header: &Header,
+    options: &GenerateArgs,
+    output: &mut TerminalOutput,
+) -> FileResult {
+    let filename = new_file.display().to_string();
+    let mut file_result = FileResult::new(&contents);
+
+    if !header.has_license(&contents) {
+        output.diagnostic(
+            ErrorCode::W0005,
+            None,
+            Message::LicenseMissing(input.display().to_string()),
+        );
+    }
+
+    let key = cache::enabled().then(|| cache::key(&contents, &options.fingerprint(header)));
+    if let Some(text) = key.as_deref().and_then(cache::get) {
+        output.writeln_verbose(Message::FileFromCache(filename.clone()));
+        if write_generated(new_file, &text, options, output) == Some(Outcome::Written) {
+            file_result.written(new_file, &text);
         }
+        return file_result;
+    }
+
+    let errors = output.error_count();
+    let warnings = output.warning_count();
+
+    let (result, duration) = parse_timed(&contents, output);
+    file_result.duration = duration;
+    file_result.diagnostics = collect_errors(&result);
+    if is_rejected(input, &result, options, output) {
+        return file_result;
+    }
+
+    let (text, source_map) = generate_text(&result, &contents, header, options, &filename, output);
+
+    if options.reproducible() {
+        check_reproducible(&text, &contents, header, options, &filename, output);
+    }
+
+    if write_generated(new_file, &text, options, output) != Some(Outcome::Written) {
+        return file_result;
     }
+    file_result.written(new_file, &text);
+
+    if options.verify_output() {
+        verify::verify(new_file, input, source_map.as_ref(), output);
+    }
+
+    #[cfg(feature = "syn")]
+    if options.verify_syntax() {
+        verify::verify_syntax(&text, new_file, input, source_map.as_ref(), output);
+    }
+
+    let clean = file_result.diagnostics.is_empty()
+        && output.error_count() == errors
+        && output.warning_count() == warnings;
+    if let (Some(key), true) = (key, clean) {
+        cache::put(&key, &text);
+    }
+
+    file_result
 }
 
-fn write_output_to_file(
-    mut file: File,
-    result: Vec<parser::Sequence<Token>>,
+// an empty input has nothing to parse, nor a license to check, so its output is empty as well,
+// without the header
+fn generate_empty_file(
+    input: &Path,
+    new_file: &Path,
+    contents: &str,
+    options: &GenerateArgs,
+    output: &mut TerminalOutput,
+) -> FileResult {
+    let mut file_result = FileResult::new(contents);
+
+    if options.skip_empty() {
+        output.writeln_info(Message::EmptyFileSkipped(input.display().to_string()));
+    } else if write_generated(new_file, "", options, output) == Some(Outcome::Written) {
+        file_result.written(new_file, "");
+    }
+
+    file_result
+}
+
+fn generate_text(
+    result: &[Sequence<Token>],
+    contents: &str,
+    header: &Header,
+    options: &GenerateArgs,
+    filename: &str,
+    output: &mut TerminalOutput,
+) -> (String, Option<SourceMap>) {
+    let rendered = render_output(result, options);
+    let mut source_map = Some(SourceMap::new(header, &rendered, contents));
+
+    let mut text = header.apply(&rendered.text);
+    if options.ensure_final_newline() {
+        formatter::ensure_final_newline(&mut text);
+    }
+
+    if let Some(program) = options.rustfmt() {
+        match rustfmt::format(&text, program) {
+            Ok(formatted) => {
+                // the lines of the formatted file no longer match the lines of the input
+                source_map = None;
+                text = formatted;
+            }
+            Err(e) => output.writeln_warning(Message::GeneratedFileNotFormatted(
+                filename.to_string(),
+                e.to_string(),
+            )),
+        }
+    }
+
+    (text, source_map)
+}
+
+// The file is generated a second time, to detect output that changes from run to run, besides
+// looking for content that depends on when or where it was generated.
+fn check_reproducible(
+    text: &str,
+    contents: &str,
+    header: &Header,
+    options: &GenerateArgs,
+    filename: &str,
+    output: &mut TerminalOutput,
+) {
+    for finding in reproducible::audit(text) {
+        output.writeln_error(Message::NondeterministicContent(
+            filename.to_string(),
+            finding.line,
+            finding.description.to_string(),
+        ));
+    }
+
+    let mut silent = TerminalOutput::new(termcolor::ColorChoice::Never, Verbosity::Silent);
+    let result = parser::parse_with_limits(contents, configured_limits());
+    let (again, _) = generate_text(&result, contents, header, options, filename, &mut silent);
+
+    if again != text {
+        output.writeln_error(Message::OutputNotReproducible(filename.to_string()));
+    }
+}
+
+// false when the backups of the files overwritten are misconfigured
+fn enable_backups(options: &GenerateArgs, header: &Header, output: &mut TerminalOutput) -> bool {
+    if options.backup().is_none() && options.backup_dir().is_none() {
+        return true;
+    }
+
+    match backup::enable(
+        options.backup(),
+        options.backup_dir(),
+        options.backup_count(),
+        header,
+    ) {
+        Ok(_) => true,
+        Err(e) => {
+            output.writeln_error(e);
+            false
+        }
+    }
+}
+
+fn write_generated(
+    new_file: &Path,
+    text: &str,
+    options: &GenerateArgs,
+    output: &mut TerminalOutput,
+) -> Option<Outcome> {
+    let encoding = options.output_encoding();
+
+    match options.split_output() {
+        Some(max_lines) => split::write(new_file, text, max_lines, encoding, output),
+        None => write_output(new_file, text, encoding, output),
+    }
+}
+
+fn write_output(
+    new_file: &Path,
+    text: &str,
+    encoding: OutputEncoding,
+    output: &mut TerminalOutput,
+) -> Option<Outcome> {
+    let path = long_paths::extended(new_file);
+
+    match sink::write(&path, &encoding.encode(text)) {
+        Ok(outcome) => Some(outcome),
+        Err(_) => {
+            output.writeln_error(Message::FileWriteFailed(new_file.display().to_string()));
+            None
+        }
+    }
+}
+
+fn is_rejected(
+    input: &Path,
+    result: &[Sequence<Token>],
+    options: &GenerateArgs,
     output: &mut TerminalOutput,
 ) -> bool {
-    let mut line_number = 1;
+    let rejected = options.reject_bidi() && bidi::contains_bidi_control(result);
+    if rejected {
+        output.writeln_error(Message::FileRejectedBidi(input.display().to_string()));
+    }
+    rejected
+}
+
+fn report_errors(result: &[Sequence<Token>], output: &mut TerminalOutput) -> usize {
+    let diagnostics = diagnostics::collect(result);
+    diagnostics::report(&diagnostics, output);
+    html_report::record(result, &diagnostics);
+    diagnostics::error_count(&diagnostics)
+}
 
-    for t in result {
-        let text = match t.token {
-            Token::NewLine(number) => {
-                line_number = number;
-                t.text.to_string()
+fn collect_errors(result: &[Sequence<Token>]) -> Vec<OwnedDiagnostic> {
+    let diagnostics = diagnostics::collect(result);
+    html_report::record(result, &diagnostics);
+    diagnostics.iter().map(Diagnostic::to_owned).collect()
+}
+
+fn render_output(result: &[Sequence<Token>], options: &GenerateArgs) -> Rewritten {
+    let reindenter = options
+        .fix_indent()
+        .map(|style| Reindenter::new(result, style));
+
+    // offsets of the tokens whose trailing whitespace is removed
+    let mut line_ends = HashSet::new();
+    if options.trim_trailing_whitespace() {
+        line_ends.extend(
+            (0..result.len())
+                .filter(|&i| formatter::is_line_end(result.get(i + 1)))
+                .map(|i| result[i].span.start),
+        );
+    }
+
+    let comments = options
+        .comment_style()
+        .map(|style| comment_style::conversions(result, style))
+        .unwrap_or_default();
+
+    visitor::rewrite_with_provenance(result, |token, text, position| {
+        let replaced = match token {
+            _ if comments.contains_key(&position.offset) => comments.get(&position.offset).cloned(),
+            Token::StrLiteral => {
+                let converted = options
+                    .rewrite_strings()
+                    .and_then(|style| raw_strings::convert(text, style));
+                Some(format!("😀{}😀", converted.as_deref().unwrap_or(text)))
             }
-            Token::Invalid(s) => {
-                output.writeln_error(format!("(line {}) {}", line_number, s));
-                t.text.to_string()
+            Token::Whitespace { .. } if line_ends.contains(&position.offset) => Some(String::new()),
+            Token::Other | Token::SingleLineComment if line_ends.contains(&position.offset) => {
+                Some(text.trim_end_matches([' ', '\t']).to_string())
             }
-            Token::StrLiteral => format!("😀{}😀", t.text),
-            _ => t.text.to_string(),
+            Token::Whitespace { spaces, tabs } if position.column == 1 => reindenter
+                .as_ref()
+                .map(|reindenter| reindenter.reindent(*spaces, *tabs)),
+            _ => None,
         };
 
-        if file.write_all(text.as_bytes()).is_err() {
-            return false;
-        }
-    }
-    true
+        let Some(control) = options.control_characters() else {
+            return replaced;
+        };
+        let escape = matches!(
+            token,
+            Token::StrLiteral
+                | Token::CharLiteral
+                | Token::SingleLineComment
+                | Token::MultiLineComment
+        );
+        control_chars::clean(replaced.as_deref().unwrap_or(text), escape, control).or(replaced)
+    })
 }
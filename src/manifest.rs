@@ -0,0 +1,205 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use serde::{Deserialize, Serialize};
+use std::{
+    cell::RefCell,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    archive_input, cache::sha256, messages::Message, remote_input, terminal_helper::TerminalOutput,
+};
+
+const MANIFEST_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct ManifestEntry {
+    output: String,
+    sha256: String,
+    // mod.rs files are generated from the directory rather than from an XR file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    input: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    input_sha256: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ManifestFile {
+    version: u32,
+    files: Vec<ManifestEntry>,
+}
+
+// Checksums of the files generated by a build and of the XR files they were generated from,
+// with paths relative to the manifest, so that the generated files committed to a repository
+// can be proven to match their sources.
+struct Manifest {
+    path: PathBuf,
+    input: Option<(String, String)>,
+    entries: Vec<ManifestEntry>,
+}
+
+thread_local! {
+    static MANIFEST: RefCell<Option<Manifest>> = const { RefCell::new(None) };
+}
+
+pub fn enable(path: &Path) {
+    MANIFEST.with(|m| {
+        *m.borrow_mut() = Some(Manifest {
+            path: path.to_path_buf(),
+            input: None,
+            entries: Vec::new(),
+        })
+    });
+}
+
+// the XR file whose outputs are written next
+pub fn begin_file(file: &Path, contents: &[u8]) {
+    MANIFEST.with(|m| {
+        if let Some(manifest) = m.borrow_mut().as_mut() {
+            let name = crate::relative_file_name(file, &manifest.path);
+            manifest.input = Some((name, sha256(contents)));
+        }
+    });
+}
+
+pub fn end_file() {
+    MANIFEST.with(|m| {
+        if let Some(manifest) = m.borrow_mut().as_mut() {
+            manifest.input = None;
+        }
+    });
+}
+
+pub fn record(output: &Path, contents: &[u8]) {
+    MANIFEST.with(|m| {
+        if let Some(manifest) = m.borrow_mut().as_mut() {
+            let (input, input_sha256) = manifest.input.clone().unzip();
+
+            manifest.entries.push(ManifestEntry {
+                output: crate::relative_file_name(output, &manifest.path),
+                sha256: sha256(contents),
+                input,
+                input_sha256,
+            });
+        }
+    });
+}
+
+pub fn write(output: &mut TerminalOutput) {
+    MANIFEST.with(|m| {
+        let Some(manifest) = m.borrow_mut().take() else {
+            return;
+        };
+
+        let filename = manifest.path.display().to_string();
+        let count = manifest.entries.len();
+        let file = ManifestFile {
+            version: MANIFEST_VERSION,
+            files: manifest.entries,
+        };
+
+        let written = serde_json::to_string_pretty(&file)
+            .map_err(|e| e.to_string())
+            .and_then(|json| fs::write(&manifest.path, json + "\n").map_err(|e| e.to_string()));
+
+        match written {
+            Ok(_) => output.writeln_info(Message::ManifestWritten(filename, count)),
+            Err(e) => output.writeln_error(Message::ManifestUnwritable(filename, e)),
+        }
+    });
+}
+
+fn load(path: &Path) -> Result<ManifestFile, Message> {
+    let filename = path.display().to_string();
+
+    let contents = fs::read_to_string(path)
+        .map_err(|e| Message::ManifestInvalid(filename.clone(), e.to_string()))?;
+
+    let file: ManifestFile = serde_json::from_str(&contents)
+        .map_err(|e| Message::ManifestInvalid(filename.clone(), e.to_string()))?;
+
+    if file.version != MANIFEST_VERSION {
+        return Err(Message::ManifestInvalid(
+            filename,
+            format!("unsupported version {}", file.version),
+        ));
+    }
+
+    Ok(file)
+}
+
+fn checksum(path: &Path) -> Option<String> {
+    fs::read(path).ok().map(|bytes| sha256(&bytes))
+}
+
+// generated files that were edited after being generated are reported as modified, and those
+// whose XR file changed since as stale
+pub fn verify(path: &Path, output: &mut TerminalOutput) {
+    let manifest = match load(path) {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            output.writeln_error(e);
+            return;
+        }
+    };
+
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let mut problems = 0;
+
+    for entry in &manifest.files {
+        let problem = match checksum(&dir.join(&entry.output)) {
+            None => Some(Message::GeneratedFileMissing(entry.output.clone())),
+            Some(actual) if actual != entry.sha256 => {
+                Some(Message::GeneratedFileModified(entry.output.clone()))
+            }
+            Some(_) => match (&entry.input, &entry.input_sha256) {
+                // the files of archives and remote files cannot be read again to compare them
+                (Some(input), _)
+                    if remote_input::is_url(Path::new(input))
+                        || archive_input::split(Path::new(input)).is_some() =>
+                {
+                    None
+                }
+                (Some(input), Some(expected)) => match checksum(&dir.join(input)) {
+                    Some(actual) if actual == *expected => None,
+                    _ => Some(Message::GeneratedFileStale(
+                        entry.output.clone(),
+                        input.clone(),
+                    )),
+                },
+                _ => None,
+            },
+        };
+
+        if let Some(problem) = problem {
+            output.writeln_error(problem);
+            problems += 1;
+        }
+    }
+
+    output.writeln_success(Message::GeneratedFilesVerified(
+        manifest.files.len(),
+        problems,
+    ));
+}
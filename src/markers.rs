@@ -0,0 +1,82 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use std::path::Path;
+
+use crate::{
+    comments::{Marker, MARKERS},
+    diagnostics::Diagnostic,
+    error_codes::ErrorCode,
+    messages::Message,
+    parser::{Sequence, Token},
+    terminal_helper::TerminalOutput,
+};
+
+struct Entry {
+    file: String,
+    line: usize,
+    word: &'static str,
+    text: String,
+}
+
+// TODO, FIXME and HACK comments of all the files checked, printed after the diagnostics
+#[derive(Default)]
+pub struct Report {
+    entries: Vec<Entry>,
+}
+
+impl Report {
+    pub fn record(&mut self, file: &Path, markers: &[Marker]) {
+        self.entries.extend(markers.iter().map(|m| Entry {
+            file: file.display().to_string(),
+            line: m.line,
+            word: m.word,
+            text: m.text.to_string(),
+        }));
+    }
+
+    pub fn print(&self, output: &mut TerminalOutput) {
+        for e in &self.entries {
+            output.writeln_result(format!("{}:{}: {}", e.file, e.line, e.text));
+        }
+
+        let counts: Vec<String> = MARKERS
+            .iter()
+            .map(|word| {
+                let n = self.entries.iter().filter(|e| e.word == *word).count();
+                format!("{n} {word}")
+            })
+            .collect();
+
+        output.writeln_info(Message::MarkersFound(counts.join(", ")));
+    }
+}
+
+pub fn diagnostic<'a>(tokens: &[Sequence<'a, Token>], marker: &Marker<'a>) -> Diagnostic<'a> {
+    Diagnostic {
+        code: ErrorCode::W0004,
+        message: Message::UnfinishedWork(marker.text.to_string()).to_string(),
+        line: marker.line,
+        span: tokens[marker.comment].span,
+        text: marker.text,
+    }
+}
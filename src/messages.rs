@@ -0,0 +1,731 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use clap::ValueEnum;
+use std::{env, fmt, sync::OnceLock, time::Duration};
+
+use crate::error_codes::ErrorCode;
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+#[non_exhaustive]
+pub enum Language {
+    En,
+    Pt,
+}
+
+static LANGUAGE: OnceLock<Language> = OnceLock::new();
+
+impl Language {
+    pub fn from_environment() -> Language {
+        let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|v| env::var(v).ok())
+            .find(|v| !v.is_empty())
+            .unwrap_or_default();
+
+        if locale.to_ascii_lowercase().starts_with("pt") {
+            Language::Pt
+        } else {
+            Language::En
+        }
+    }
+}
+
+pub fn set_language(language: Language) {
+    LANGUAGE.set(language).ok();
+}
+
+pub fn language() -> Language {
+    *LANGUAGE.get().unwrap_or(&Language::En)
+}
+
+#[non_exhaustive]
+pub enum Message {
+    AbnormalTermination(String),
+    AmalgamatedModuleTaken(String, String),
+    AmalgamationWritten(String, usize),
+    AnotherInstanceRunning(String),
+    ArchiveUnreadable(String, String),
+    ArchiveUnwritable(String, String),
+    ArchiveWritten(String),
+    BackupSuffixEmpty,
+    BaselineCreated(String, usize),
+    BaselineInvalid(String, String),
+    BaselineSuppressed(usize),
+    BaselineUnreadable(String, String),
+    BidiInComment(char),
+    BidiInLiteral(char),
+    CacheHits(usize),
+    ChecksumMismatch(String, String, String),
+    CodeSummary(ErrorCode, usize, usize),
+    CommentTooDeep(usize),
+    ConfigurationInvalid(String, String),
+    ConfigurationUnreadable(String, String),
+    ConfigurationUsed(String),
+    ConfirmOverwrite(String),
+    ConfusableIdentifier(String, String),
+    ContainsAbsolutePath(String),
+    ContainsMachineName(String),
+    ContainsTimestamp,
+    ControlCharacter(char),
+    DaemonAlreadyRunning(String),
+    DaemonListening(String),
+    DaemonRequestTooLarge(usize, usize),
+    DaemonSocketFailed(String, String),
+    DaemonStopped,
+    DaemonUnsupported,
+    DiagnosticsByCode,
+    DictionaryMissing,
+    DictionaryUnreadable(String, String),
+    DisallowedInString(String),
+    DuplicateModule(String, String),
+    DuplicateRegion(String, String, usize, usize),
+    DuplicatesFound(usize),
+    EmptyFileSkipped(String),
+    EmptyFiles(usize),
+    EmptyToken(usize),
+    ErrorAtLine(usize, String),
+    ErrorCodePrefix(ErrorCode),
+    ErrorPrefix,
+    ExcludePatternIgnored(String),
+    ExtensionMissing(String),
+    FileBinary(String),
+    FileCreateFailed(String),
+    FileFromCache(String),
+    FileGenerated(String, usize, usize),
+    FileNotFormatted(String),
+    FileNotFormattedDueToErrors(String),
+    FileParsed(Duration),
+    FileRejectedBidi(String),
+    FileSize(String, u64),
+    FileTooLarge(String, u64, u64),
+    FileUnreadable(String),
+    FileWriteFailed(String),
+    FilesAnalyzed(usize),
+    FilesChecked(usize),
+    FilesFormatted(usize, usize),
+    FilesFound(usize, u64),
+    FilesInvolved(String, String),
+    FilesNeedFormatting(usize, usize),
+    FilesProcessed(usize),
+    GeneratedFileChanges(String),
+    GeneratedFileDiffers(String),
+    GeneratedFileMissing(String),
+    GeneratedFileModified(String),
+    GeneratedFileNotFormatted(String, String),
+    GeneratedFileStale(String, String),
+    GeneratedFilesVerified(usize, usize),
+    GitFailed(String),
+    HeaderUnreadable,
+    HistogramWritten(String, usize),
+    HookInstalled(String),
+    HookNotInstalled(String),
+    HookNotOurs(String),
+    HookRemoved(String),
+    IndentationMixed,
+    IndentationWidth(usize),
+    InputsReplayed(usize, usize),
+    InternalError(String, String),
+    Interrupted,
+    InvalidCharLiteral,
+    InvalidDaemonRequest(String),
+    InvalidGeneratedCode(String, usize, String, String),
+    InvalidGeneratedCodeAt(String, usize, String),
+    InvalidModuleName(String),
+    InvalidRawStringLiteral,
+    InvariantViolated(String, String),
+    JournalSkipped(usize, String),
+    JournalUnwritable(String, String),
+    LargestFiles,
+    LicenseMissing(String),
+    LicenseUnreadable(String),
+    LineNumberNotIncreasing(usize, usize, usize),
+    LineTooLong(usize, usize),
+    LockFailed(String, String),
+    MacroExpansionOf(String),
+    ManifestInvalid(String, String),
+    ManifestUnwritable(String, String),
+    ManifestWritten(String, usize),
+    MarkersFound(String),
+    Misspelled(String),
+    MixedScriptIdentifier(String, String),
+    ModFileGenerated(String),
+    ModFileIsGenerated(String),
+    ModFileNotGenerated(String),
+    ModuleNotAmalgamated(String),
+    MultiLineCommentWithoutBeginning,
+    NonPrintableInString(char),
+    NondeterministicContent(String, usize, String),
+    NotUtf8(String, usize),
+    OutputCollision(String),
+    OutputFileFailed,
+    OutputNotReproducible(String),
+    OutputSplit(String, usize),
+    OverwriteDeclined(String),
+    ParserPanicked(String, String),
+    ParsingTimedOut(Duration),
+    PlanGenerate(String, String),
+    PlanSkip(String),
+    PlanSummary(usize, usize, usize),
+    PlanWriteModFile(String),
+    ProcessingFile(String),
+    ProfileMemory(usize, usize, usize),
+    ProfileParsing(usize, usize, Duration),
+    ProgramNotRun(String, String),
+    RemoteCacheFailed(String, String),
+    RemoteFileUnreadable(String, String),
+    RemoteNotAllowed(String),
+    RemoteUnsupported,
+    ReportCode,
+    ReportCount,
+    ReportNoDiagnostics,
+    ReportSummary(usize, usize, usize),
+    ReportTitle,
+    ReportWritten(String),
+    RustfmtFailed(String),
+    SecretInComment(String),
+    SecretInString(String),
+    SecretsFound(usize),
+    SharedFileChanged(String),
+    Skipped(String),
+    SkippedIo(String, String),
+    SkippedLoop(String, String),
+    SnapshotMismatch(String, usize, String, String),
+    SnapshotMissing(String),
+    SnapshotUpdated(String),
+    SnapshotsPassed(usize, usize),
+    SpanMismatch(usize, usize),
+    StrayQuote,
+    StringTooLong(usize, usize),
+    TokenGap(usize),
+    TokensIncomplete(usize, usize),
+    TooManyRawStringHashes(usize),
+    TooManyTokens(usize),
+    TraceUnwritable(String, String),
+    UnclosedCharLiteral,
+    UnclosedMultiLineComment(usize),
+    UnclosedStringLiteral,
+    UnfinishedWork(String),
+    UnknownErrorCode(String),
+    UnknownSuppressionCode(String),
+    UnusedSuppression,
+    Version(&'static str),
+    WaitingForChanges,
+    WaitingForLock(String),
+    WarningCodePrefix(ErrorCode),
+    WarningPrefix,
+    WordsChecked(usize),
+}
+
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match language() {
+            Language::En => self.fmt_en(f),
+            Language::Pt => self.fmt_pt(f),
+        }
+    }
+}
+
+impl Message {
+    fn fmt_en(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AbnormalTermination(path) => write!(f, "The previous run terminated abnormally while processing '{path}'; the file was skipped"),
+            Self::AmalgamatedModuleTaken(path, module) => write!(f, "The module '{module}' of '{path}' was already generated by another file; not included in the amalgamated file"),
+            Self::AmalgamationWritten(path, n) => write!(f, "{n} file(s) amalgamated into '{path}'"),
+            Self::AnotherInstanceRunning(pid) => write!(f, "Another xr instance is running (pid {pid}); use --wait to wait for it to finish"),
+            Self::ArchiveUnreadable(path, e) => write!(f, "Could not read the archive '{path}': {e}"),
+            Self::ArchiveUnwritable(path, e) => write!(f, "Could not write the archive '{path}': {e}"),
+            Self::ArchiveWritten(path) => write!(f, "Generated files written to the archive '{path}'"),
+            Self::BackupSuffixEmpty => write!(f, "The backup suffix cannot be empty without a backup directory, as the backups would replace the generated files"),
+            Self::BaselineCreated(path, n) => {
+                write!(f, "Baseline '{path}' written with {n} known error(s)")
+            }
+            Self::BaselineInvalid(path, e) => write!(f, "Invalid baseline file '{path}': {e}"),
+            Self::BaselineSuppressed(n) => {
+                write!(f, "{n} known error(s) ignored because of the baseline")
+            }
+            Self::BaselineUnreadable(path, e) => {
+                write!(f, "Could not read baseline file '{path}': {e}")
+            }
+            Self::BidiInComment(c) => write!(f, "Comment contains the bidirectional control character U+{:04X}", *c as u32),
+            Self::BidiInLiteral(c) => write!(f, "Literal contains the bidirectional control character U+{:04X}", *c as u32),
+            Self::CacheHits(hits) => write!(f, "{hits} file(s) taken from the cache"),
+            Self::ChecksumMismatch(url, expected, actual) => write!(f, "The contents of '{url}' do not match their checksum: expected {expected}, got {actual}"),
+            Self::CodeSummary(code, n, files) => write!(f, "  {code}: {n} occurrence(s) in {files} file(s) - {}", code.title()),
+            Self::CommentTooDeep(max) => write!(f, "Multiline comments nested more than {max} levels deep; the rest of the file was not parsed"),
+            Self::ConfigurationInvalid(path, e) => {
+                write!(f, "Invalid configuration file '{path}': {e}")
+            }
+            Self::ConfigurationUnreadable(path, e) => {
+                write!(f, "Could not read configuration file '{path}': {e}")
+            }
+            Self::ConfigurationUsed(path) => write!(f, "using configuration '{path}'"),
+            Self::ConfirmOverwrite(path) => write!(f, "Overwrite '{path}'? [y]es, [n]o, [a]ll, [q]uit: "),
+            Self::ConfusableIdentifier(identifier, other) => write!(f, "Identifier '{identifier}' can be confused with '{other}'"),
+            Self::ContainsAbsolutePath(path) => write!(f, "the absolute path '{path}'"),
+            Self::ContainsMachineName(name) => write!(f, "the name of this machine ('{name}')"),
+            Self::ContainsTimestamp => write!(f, "a date and time"),
+            Self::ControlCharacter(c) => write!(f, "Control character U+{:04X}", *c as u32),
+            Self::DaemonAlreadyRunning(socket) => write!(f, "Another daemon is already listening on '{socket}'"),
+            Self::DaemonListening(socket) => write!(f, "Listening on '{socket}'"),
+            Self::DaemonRequestTooLarge(size, max) => write!(f, "Request of {size} bytes exceeds the maximum of {max} bytes"),
+            Self::DaemonSocketFailed(socket, e) => write!(f, "Failed to listen on '{socket}': {e}"),
+            Self::DaemonStopped => write!(f, "Daemon stopped"),
+            Self::DaemonUnsupported => write!(f, "The daemon is only available on systems with unix sockets"),
+            Self::DiagnosticsByCode => write!(f, "Diagnostics by code:"),
+            Self::DictionaryMissing => write!(f, "Spell-checking needs a dictionary, given with --dictionary or dictionaries in xr.toml"),
+            Self::DictionaryUnreadable(path, e) => write!(f, "Could not read dictionary '{path}': {e}"),
+            Self::DisallowedInString(text) => write!(f, "String literal contains '{text}', which is not allowed"),
+            Self::DuplicateModule(name, dir) => write!(f, "Several input files generate the module '{name}' in '{dir}'"),
+            Self::DuplicateRegion(first, second, tokens, similarity) => write!(f, "{second} repeats {first} ({tokens} tokens, {similarity}% identical)"),
+            Self::DuplicatesFound(n) => write!(f, "{n} repeated region(s) found"),
+            Self::EmptyFileSkipped(path) => write!(f, "The file '{path}' is empty and was skipped"),
+            Self::EmptyFiles(n) => write!(f, "{n} of them empty or with only whitespace"),
+            Self::EmptyToken(offset) => write!(f, "empty token at byte {offset}"),
+            Self::ErrorAtLine(line, e) => write!(f, "(line {line}) {e}"),
+            Self::ErrorCodePrefix(code) => write!(f, "Error[{code}]: "),
+            Self::ErrorPrefix => write!(f, "Error: "),
+            Self::ExcludePatternIgnored(e) => write!(f, "Ignored exclude pattern: {e}"),
+            Self::ExtensionMissing(path) => write!(f, "Skipped '{path}': it has no extension (use --assume-xr to process it)"),
+            Self::FileBinary(path) => write!(f, "Skipped '{path}': it looks like a binary file"),
+            Self::FileCreateFailed(path) => write!(f, "Failed to create file '{path}'"),
+            Self::FileFromCache(filename) => write!(f, "'{filename}' taken from the cache"),
+            Self::FileGenerated(path, bytes_in, bytes_out) => write!(f, "'{path}' generated ({bytes_in} bytes read, {bytes_out} bytes written)"),
+            Self::FileNotFormatted(path) => write!(f, "File '{path}' is not formatted"),
+            Self::FileNotFormattedDueToErrors(path) => {
+                write!(f, "File '{path}' was not formatted")
+            }
+            Self::FileParsed(duration) => write!(f, "file parsed in {duration:?}"),
+            Self::FileRejectedBidi(file) => write!(f, "File '{file}' was not generated because it contains bidirectional control characters"),
+            Self::FileSize(path, bytes) => write!(f, "{bytes:>12} bytes  {path}"),
+            Self::FileTooLarge(path, size, max) => write!(f, "Skipped '{path}': its size ({size} bytes) exceeds the maximum of {max} bytes"),
+            Self::FileUnreadable(path) => write!(f, "Could not read file '{path}'"),
+            Self::FileWriteFailed(path) => write!(f, "Failed to write to file '{path}'"),
+            Self::FilesAnalyzed(n) => write!(f, "{n} file(s) analyzed"),
+            Self::FilesChecked(n) => write!(f, "{n} file(s) checked"),
+            Self::FilesFormatted(n, total) => write!(f, "{n} of {total} file(s) formatted"),
+            Self::FilesFound(n, bytes) => write!(f, "{n} file(s) found, with {bytes} bytes in total"),
+            Self::FilesInvolved(message, files) => write!(f, "{message}: {files}"),
+            Self::FilesNeedFormatting(n, total) => {
+                write!(f, "{n} of {total} file(s) need formatting")
+            }
+            Self::FilesProcessed(n) => write!(f, "{n} file(s) processed"),
+            Self::GeneratedFileChanges(path) => write!(f, "Changes to '{path}':"),
+            Self::GeneratedFileDiffers(path) => write!(f, "'{path}' differs from the file generated from its XR file"),
+            Self::GeneratedFileMissing(path) => write!(f, "The generated file '{path}' is missing"),
+            Self::GeneratedFileModified(path) => write!(f, "The generated file '{path}' was modified after being generated"),
+            Self::GeneratedFileNotFormatted(filename, reason) => write!(f, "'{filename}' is written without formatting. {reason}"),
+            Self::GeneratedFileStale(path, input) => write!(f, "The generated file '{path}' is stale, as '{input}' changed since it was generated"),
+            Self::GeneratedFilesVerified(n, problems) => write!(f, "{n} generated file(s) verified, {problems} with problems"),
+            Self::GitFailed(e) => write!(f, "Could not obtain the changed files from git: {e}"),
+            Self::HeaderUnreadable => write!(f, "Could not read the header file"),
+            Self::HistogramWritten(path, entries) => write!(f, "Histogram with {entries} entries written to '{path}'"),
+            Self::HookInstalled(path) => write!(f, "Hook installed in '{path}'"),
+            Self::HookNotInstalled(path) => write!(f, "There is no hook in '{path}'"),
+            Self::HookNotOurs(path) => write!(f, "The hook in '{path}' was not installed by xr, so it was left unchanged"),
+            Self::HookRemoved(path) => write!(f, "Hook removed from '{path}'"),
+            Self::IndentationMixed => write!(f, "Indentation mixes tabs and spaces"),
+            Self::IndentationWidth(width) => write!(f, "Indentation is not a multiple of {width} spaces"),
+            Self::InputsReplayed(n, skipped) => {
+                write!(f, "{n} input(s) replayed, {skipped} skipped (not UTF-8)")
+            }
+            Self::InternalError(path, e) => {
+                write!(f, "Internal error while processing file '{path}': {e}")
+            }
+            Self::Interrupted => write!(f, "Interrupted: the remaining files were not processed"),
+            Self::InvalidCharLiteral => write!(f, "Invalid char literal"),
+            Self::InvalidDaemonRequest(e) => write!(f, "Invalid request: {e}"),
+            Self::InvalidGeneratedCode(input, line, generated, message) => write!(f, "'{input}' (line {line}) generates invalid Rust in '{generated}': {message}"),
+            Self::InvalidGeneratedCodeAt(generated, line, message) => write!(f, "'{generated}' (line {line}) is not valid Rust: {message}"),
+            Self::InvalidModuleName(path) => write!(
+                f,
+                "'{path}' is not a valid module name; not declared in mod.rs"
+            ),
+            Self::InvalidRawStringLiteral => write!(f, "Invalid raw string literal"),
+            Self::InvariantViolated(path, e) => write!(f, "Invariant violated on '{path}': {e}"),
+            Self::JournalSkipped(n, path) => write!(f, "{n} file(s) skipped, as recorded in the journal '{path}'"),
+            Self::JournalUnwritable(path, e) => write!(f, "Could not write the journal '{path}': {e}"),
+            Self::LargestFiles => write!(f, "Largest files:"),
+            Self::LicenseMissing(path) => write!(f, "'{path}' does not begin with the license"),
+            Self::LicenseUnreadable(path) => write!(f, "Could not read the license file '{path}'"),
+            Self::LineNumberNotIncreasing(offset, line, previous) => write!(
+                f,
+                "new line at byte {offset} starts line {line} after line {previous}"
+            ),
+            Self::LineTooLong(width, max) => write!(f, "Line of {width} characters, longer than the maximum of {max}"),
+            Self::LockFailed(path, reason) => write!(f, "Could not lock '{path}': {reason}"),
+            Self::MacroExpansionOf(name) => write!(f, "<macro expansion of {name}>"),
+            Self::ManifestInvalid(path, e) => write!(f, "Could not read the manifest '{path}': {e}"),
+            Self::ManifestUnwritable(path, e) => write!(f, "Could not write the manifest '{path}': {e}"),
+            Self::ManifestWritten(path, n) => write!(f, "Manifest '{path}' written with {n} file(s)"),
+            Self::MarkersFound(counts) => write!(f, "Comments marking unfinished work: {counts}"),
+            Self::Misspelled(word) => write!(f, "Possibly misspelled word '{word}' in comment"),
+            Self::MixedScriptIdentifier(identifier, scripts) => write!(f, "Identifier '{identifier}' mixes scripts ({scripts})"),
+            Self::ModFileGenerated(path) => write!(f, "Generated module file '{path}'"),
+            Self::ModFileIsGenerated(path) => write!(
+                f,
+                "'{path}' is generated from an XR file and will not be overwritten"
+            ),
+            Self::ModFileNotGenerated(path) => write!(
+                f,
+                "'{path}' was not generated by xr and will not be overwritten"
+            ),
+            Self::ModuleNotAmalgamated(path) => write!(f, "'{path}' is not in a valid module path; not included in the amalgamated file"),
+            Self::MultiLineCommentWithoutBeginning => {
+                write!(f, "Multiline end comment detected without a beginning.")
+            }
+            Self::NonPrintableInString(c) => write!(f, "String literal contains the non-printable character U+{:04X}", *c as u32),
+            Self::NondeterministicContent(path, line, what) => write!(f, "'{path}' is not reproducible: line {line} contains {what}"),
+            Self::NotUtf8(path, offset) => {
+                write!(f, "Skipped '{path}': invalid UTF-8 at byte {offset}")
+            }
+            Self::OutputCollision(output) => write!(f, "Several input files are generated into '{output}', which is not written"),
+            Self::OutputFileFailed => write!(f, "Failed to generate output file"),
+            Self::OutputNotReproducible(path) => write!(f, "'{path}' is not reproducible: generating it twice gave different contents"),
+            Self::OutputSplit(path, parts) => write!(f, "'{path}' was split into {parts} parts"),
+            Self::OverwriteDeclined(path) => write!(f, "'{path}' was not overwritten"),
+            Self::ParserPanicked(path, e) => write!(f, "The parser panicked on '{path}': {e}"),
+            Self::ParsingTimedOut(timeout) => write!(f, "Parsing took longer than {timeout:?}; the file was not parsed"),
+            Self::PlanGenerate(input, output) => write!(f, "generate '{output}' from '{input}'"),
+            Self::PlanSkip(input) => write!(f, "skip '{input}'"),
+            Self::PlanSummary(generated, skipped, mod_files) => write!(f, "Plan: {generated} file(s) to generate, {skipped} to skip and {mod_files} mod file(s) to write"),
+            Self::PlanWriteModFile(path) => write!(f, "write '{path}'"),
+            Self::ProcessingFile(path) => write!(f, "Processing file '{path}'"),
+            Self::ProfileMemory(owned, interned, distinct) => write!(f, "Token text: {owned} byte(s) owned, {interned} byte(s) interned ({distinct} distinct string(s), {} byte(s) saved)", owned.saturating_sub(*interned)),
+            Self::ProfileParsing(files, tokens, duration) => write!(f, "Parsed {files} file(s) into {tokens} token(s) in {duration:?}"),
+            Self::ProgramNotRun(program, e) => write!(f, "Unable to run '{program}': {e}"),
+            Self::RemoteCacheFailed(url, e) => write!(f, "The remote cache '{url}' stopped being used after failing ({e}); the remaining files were generated locally"),
+            Self::RemoteFileUnreadable(url, e) => write!(f, "Could not download '{url}': {e}"),
+            Self::RemoteNotAllowed(url) => write!(f, "Skipped '{url}': remote files are only processed with --allow-remote"),
+            Self::RemoteUnsupported => write!(
+                f,
+                "xr was built without the remote feature, which reads files over HTTP"
+            ),
+            Self::ReportCode => write!(f, "Code"),
+            Self::ReportCount => write!(f, "Count"),
+            Self::ReportNoDiagnostics => write!(f, "No diagnostics"),
+            Self::ReportSummary(files, errors, warnings) => write!(f, "{files} file(s) processed, {errors} error(s), {warnings} warning(s)"),
+            Self::ReportTitle => write!(f, "XR report"),
+            Self::ReportWritten(path) => write!(f, "Report written to '{path}'"),
+            Self::RustfmtFailed(reason) => write!(f, "rustfmt failed: {reason}"),
+            Self::SecretInComment(kind) => write!(f, "Possible {kind} in a comment"),
+            Self::SecretInString(kind) => write!(f, "Possible {kind} in a string literal"),
+            Self::SecretsFound(n) => write!(f, "{n} possible secret(s) found"),
+            Self::SharedFileChanged(path) => write!(f, "'{path}' changed, so every file is generated again"),
+            Self::Skipped(path) => write!(f, "Skipped '{path}'"),
+            Self::SkippedIo(path, e) => write!(f, "Skipped '{path}': {e}"),
+            Self::SkippedLoop(path, ancestor) => write!(
+                f,
+                "Skipped '{path}': symbolic link loop back to '{ancestor}'"
+            ),
+            Self::SnapshotMismatch(path, line, expected, actual) => write!(f, "'{path}' differs from its snapshot at line {line}\n  expected: {expected}\n  actual:   {actual}"),
+            Self::SnapshotMissing(path) => write!(f, "Snapshot '{path}' does not exist (run with --bless to create it)"),
+            Self::SnapshotUpdated(path) => write!(f, "Updated snapshot '{path}'"),
+            Self::SnapshotsPassed(n, total) => write!(f, "{n} of {total} snapshot(s) passed"),
+            Self::SpanMismatch(start, end) => write!(f, "the span {start}..{end} does not match the text of its token"),
+            Self::StrayQuote => write!(f, "Stray quote: the char literal is not closed on the same line"),
+            Self::StringTooLong(length, max) => write!(f, "String literal of {length} characters, longer than the maximum of {max}"),
+            Self::TokenGap(offset) => write!(
+                f,
+                "token at byte {offset} does not start where the previous token ended"
+            ),
+            Self::TokensIncomplete(covered, len) => {
+                write!(f, "tokens cover {covered} of {len} byte(s) of the input")
+            }
+            Self::TooManyRawStringHashes(max) => write!(f, "Raw string delimited by more than {max} hashes"),
+            Self::TooManyTokens(max) => write!(f, "The file has more than {max} tokens; the rest of the file was not parsed"),
+            Self::TraceUnwritable(path, e) => write!(f, "Could not write the parser trace '{path}': {e}"),
+            Self::UnclosedCharLiteral => write!(f, "Unclosed char or lifetime elision"),
+            Self::UnclosedMultiLineComment(levels) => write!(
+                f,
+                "Multiline comment not closed ({levels} level(s) unclosed)."
+            ),
+            Self::UnclosedStringLiteral => write!(f, "Unclosed string literal"),
+            Self::UnfinishedWork(text) => write!(f, "Unfinished work: {text}"),
+            Self::UnknownErrorCode(code) => write!(f, "Unknown error code '{code}'"),
+            Self::UnknownSuppressionCode(code) => {
+                write!(f, "'{code}' is not a code of xr and suppresses nothing")
+            }
+            Self::UnusedSuppression => write!(f, "Suppression comment did not suppress anything"),
+            Self::Version(version) => write!(f, "version {version}"),
+            Self::WaitingForChanges => write!(f, "Waiting for changes..."),
+            Self::WaitingForLock(pid) => write!(f, "Waiting for another xr instance (pid {pid}) to finish..."),
+            Self::WarningCodePrefix(code) => write!(f, "Warning[{code}]: "),
+            Self::WarningPrefix => write!(f, "Warning: "),
+            Self::WordsChecked(n) => write!(f, "{n} word(s) of comments spell-checked"),
+        }
+    }
+
+    fn fmt_pt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AbnormalTermination(path) => write!(f, "A execução anterior terminou de forma anormal ao processar '{path}'; o ficheiro foi ignorado"),
+            Self::AmalgamatedModuleTaken(path, module) => write!(f, "O módulo '{module}' de '{path}' já foi gerado por outro ficheiro; não foi incluído no ficheiro amalgamado"),
+            Self::AmalgamationWritten(path, n) => write!(f, "{n} ficheiro(s) amalgamado(s) em '{path}'"),
+            Self::AnotherInstanceRunning(pid) => write!(f, "Outra instância do xr está em execução (pid {pid}); use --wait para esperar que termine"),
+            Self::ArchiveUnreadable(path, e) => write!(f, "Não foi possível ler o arquivo '{path}': {e}"),
+            Self::ArchiveUnwritable(path, e) => write!(f, "Não foi possível escrever o arquivo '{path}': {e}"),
+            Self::ArchiveWritten(path) => write!(f, "Ficheiros gerados escritos no arquivo '{path}'"),
+            Self::BackupSuffixEmpty => write!(f, "O sufixo das cópias de segurança não pode ser vazio sem um diretório de cópias, pois as cópias substituiriam os ficheiros gerados"),
+            Self::BaselineCreated(path, n) => write!(
+                f,
+                "Ficheiro de referência '{path}' escrito com {n} erro(s) conhecido(s)"
+            ),
+            Self::BaselineInvalid(path, e) => {
+                write!(f, "Ficheiro de referência inválido '{path}': {e}")
+            }
+            Self::BaselineSuppressed(n) => write!(
+                f,
+                "{n} erro(s) conhecido(s) ignorado(s) devido ao ficheiro de referência"
+            ),
+            Self::BaselineUnreadable(path, e) => write!(
+                f,
+                "Não foi possível ler o ficheiro de referência '{path}': {e}"
+            ),
+            Self::BidiInComment(c) => write!(f, "O comentário contém o carácter de controlo bidirecional U+{:04X}", *c as u32),
+            Self::BidiInLiteral(c) => write!(f, "O literal contém o carácter de controlo bidirecional U+{:04X}", *c as u32),
+            Self::CacheHits(hits) => write!(f, "{hits} ficheiro(s) obtido(s) da cache"),
+            Self::ChecksumMismatch(url, expected, actual) => {
+                write!(f, "O conteúdo de '{url}' não corresponde ao seu checksum: esperado {expected}, obtido {actual}")
+            }
+            Self::CodeSummary(code, n, files) => write!(f, "  {code}: {n} ocorrência(s) em {files} ficheiro(s) - {}", code.title()),
+            Self::CommentTooDeep(max) => write!(f, "Comentários multilinha aninhados em mais de {max} níveis; o resto do ficheiro não foi analisado"),
+            Self::ConfigurationInvalid(path, e) => {
+                write!(f, "Ficheiro de configuração inválido '{path}': {e}")
+            }
+            Self::ConfigurationUnreadable(path, e) => write!(
+                f,
+                "Não foi possível ler o ficheiro de configuração '{path}': {e}"
+            ),
+            Self::ConfigurationUsed(path) => write!(f, "a usar a configuração '{path}'"),
+            Self::ConfirmOverwrite(path) => write!(f, "Substituir '{path}'? [y] sim, [n] não, [a] todos, [q] sair: "),
+            Self::ConfusableIdentifier(identifier, other) => write!(f, "O identificador '{identifier}' pode ser confundido com '{other}'"),
+            Self::ContainsAbsolutePath(path) => write!(f, "o caminho absoluto '{path}'"),
+            Self::ContainsMachineName(name) => write!(f, "o nome desta máquina ('{name}')"),
+            Self::ContainsTimestamp => write!(f, "uma data e hora"),
+            Self::ControlCharacter(c) => write!(f, "Carácter de controlo U+{:04X}", *c as u32),
+            Self::DaemonAlreadyRunning(socket) => write!(f, "Já existe outro daemon à escuta em '{socket}'"),
+            Self::DaemonListening(socket) => write!(f, "À escuta em '{socket}'"),
+            Self::DaemonRequestTooLarge(size, max) => write!(f, "Pedido de {size} bytes excede o máximo de {max} bytes"),
+            Self::DaemonSocketFailed(socket, e) => write!(f, "Não foi possível escutar em '{socket}': {e}"),
+            Self::DaemonStopped => write!(f, "Daemon terminado"),
+            Self::DaemonUnsupported => write!(f, "O daemon só está disponível em sistemas com sockets unix"),
+            Self::DiagnosticsByCode => write!(f, "Diagnósticos por código:"),
+            Self::DictionaryMissing => write!(f, "A verificação ortográfica precisa de um dicionário, indicado com --dictionary ou dictionaries no xr.toml"),
+            Self::DictionaryUnreadable(path, e) => write!(f, "Não foi possível ler o dicionário '{path}': {e}"),
+            Self::DisallowedInString(text) => write!(f, "O literal de string contém '{text}', que não é permitido"),
+            Self::DuplicateModule(name, dir) => write!(f, "Vários ficheiros de entrada geram o módulo '{name}' em '{dir}'"),
+            Self::DuplicateRegion(first, second, tokens, similarity) => write!(f, "{second} repete {first} ({tokens} tokens, {similarity}% idênticos)"),
+            Self::DuplicatesFound(n) => write!(f, "{n} região(ões) repetida(s) encontrada(s)"),
+            Self::EmptyFileSkipped(path) => write!(f, "O ficheiro '{path}' está vazio e foi ignorado"),
+            Self::EmptyFiles(n) => write!(f, "{n} deles vazio(s) ou apenas com espaços"),
+            Self::EmptyToken(offset) => write!(f, "token vazio no byte {offset}"),
+            Self::ErrorAtLine(line, e) => write!(f, "(linha {line}) {e}"),
+            Self::ErrorCodePrefix(code) => write!(f, "Erro[{code}]: "),
+            Self::ErrorPrefix => write!(f, "Erro: "),
+            Self::ExcludePatternIgnored(e) => write!(f, "Padrão de exclusão ignorado: {e}"),
+            Self::ExtensionMissing(path) => write!(f, "'{path}' ignorado: não tem extensão (use --assume-xr para o processar)"),
+            Self::FileBinary(path) => write!(f, "'{path}' ignorado: parece ser um ficheiro binário"),
+            Self::FileCreateFailed(path) => write!(f, "Falha ao criar o ficheiro '{path}'"),
+            Self::FileFromCache(filename) => write!(f, "'{filename}' obtido da cache"),
+            Self::FileGenerated(path, bytes_in, bytes_out) => {
+                write!(f, "'{path}' gerado ({bytes_in} bytes lidos, {bytes_out} bytes escritos)")
+            }
+            Self::FileNotFormatted(path) => write!(f, "O ficheiro '{path}' não está formatado"),
+            Self::FileNotFormattedDueToErrors(path) => {
+                write!(f, "O ficheiro '{path}' não foi formatado")
+            }
+            Self::FileParsed(duration) => write!(f, "ficheiro analisado em {duration:?}"),
+            Self::FileRejectedBidi(file) => write!(f, "O ficheiro '{file}' não foi gerado porque contém caracteres de controlo bidirecionais"),
+            Self::FileSize(path, bytes) => write!(f, "{bytes:>12} bytes  {path}"),
+            Self::FileTooLarge(path, size, max) => write!(f, "'{path}' ignorado: o seu tamanho ({size} bytes) excede o máximo de {max} bytes"),
+            Self::FileUnreadable(path) => {
+                write!(f, "Não foi possível ler o ficheiro '{path}'")
+            }
+            Self::FileWriteFailed(path) => {
+                write!(f, "Falha ao escrever no ficheiro '{path}'")
+            }
+            Self::FilesAnalyzed(n) => write!(f, "{n} ficheiro(s) analisado(s)"),
+            Self::FilesChecked(n) => write!(f, "{n} ficheiro(s) verificado(s)"),
+            Self::FilesFormatted(n, total) => {
+                write!(f, "{n} de {total} ficheiro(s) formatado(s)")
+            }
+            Self::FilesFound(n, bytes) => write!(f, "{n} ficheiro(s) encontrado(s), com {bytes} bytes no total"),
+            Self::FilesInvolved(message, files) => write!(f, "{message}: {files}"),
+            Self::FilesNeedFormatting(n, total) => write!(
+                f,
+                "{n} de {total} ficheiro(s) precisa(m) de ser formatado(s)"
+            ),
+            Self::FilesProcessed(n) => write!(f, "{n} ficheiro(s) processado(s)"),
+            Self::GeneratedFileChanges(path) => write!(f, "Alterações a '{path}':"),
+            Self::GeneratedFileDiffers(path) => write!(f, "'{path}' é diferente do ficheiro gerado a partir do seu ficheiro XR"),
+            Self::GeneratedFileMissing(path) => write!(f, "O ficheiro gerado '{path}' não existe"),
+            Self::GeneratedFileModified(path) => write!(f, "O ficheiro gerado '{path}' foi modificado depois de ser gerado"),
+            Self::GeneratedFileNotFormatted(filename, reason) => write!(f, "'{filename}' é escrito sem ser formatado. {reason}"),
+            Self::GeneratedFileStale(path, input) => {
+                write!(f, "O ficheiro gerado '{path}' está desatualizado, pois '{input}' mudou desde que foi gerado")
+            }
+            Self::GeneratedFilesVerified(n, problems) => write!(f, "{n} ficheiro(s) gerado(s) verificado(s), {problems} com problemas"),
+            Self::GitFailed(e) => write!(f, "Não foi possível obter os ficheiros alterados do git: {e}"),
+            Self::HeaderUnreadable => {
+                write!(f, "Não foi possível ler o ficheiro de cabeçalho")
+            }
+            Self::HistogramWritten(path, entries) => write!(f, "Histograma com {entries} entradas escrito em '{path}'"),
+            Self::HookInstalled(path) => write!(f, "Hook instalado em '{path}'"),
+            Self::HookNotInstalled(path) => write!(f, "Não existe nenhum hook em '{path}'"),
+            Self::HookNotOurs(path) => write!(f, "O hook em '{path}' não foi instalado pelo xr, pelo que não foi alterado"),
+            Self::HookRemoved(path) => write!(f, "Hook removido de '{path}'"),
+            Self::IndentationMixed => write!(f, "A indentação mistura tabulações e espaços"),
+            Self::IndentationWidth(width) => write!(f, "A indentação não é um múltiplo de {width} espaços"),
+            Self::InputsReplayed(n, skipped) => write!(
+                f,
+                "{n} entrada(s) reproduzida(s), {skipped} ignorada(s) (não UTF-8)"
+            ),
+            Self::InternalError(path, e) => {
+                write!(f, "Erro interno ao processar o ficheiro '{path}': {e}")
+            }
+            Self::Interrupted => write!(f, "Interrompido: os restantes ficheiros não foram processados"),
+            Self::InvalidCharLiteral => write!(f, "Literal de carácter inválido"),
+            Self::InvalidDaemonRequest(e) => write!(f, "Pedido inválido: {e}"),
+            Self::InvalidGeneratedCode(input, line, generated, message) => write!(f, "'{input}' (linha {line}) gera Rust inválido em '{generated}': {message}"),
+            Self::InvalidGeneratedCodeAt(generated, line, message) => write!(f, "'{generated}' (linha {line}) não é Rust válido: {message}"),
+            Self::InvalidModuleName(path) => write!(
+                f,
+                "'{path}' não é um nome de módulo válido; não foi declarado em mod.rs"
+            ),
+            Self::InvalidRawStringLiteral => write!(f, "Literal de string raw inválido"),
+            Self::InvariantViolated(path, e) => write!(f, "Invariante violado em '{path}': {e}"),
+            Self::JournalSkipped(n, path) => write!(f, "{n} ficheiro(s) ignorado(s), conforme registado no diário '{path}'"),
+            Self::JournalUnwritable(path, e) => write!(f, "Não foi possível escrever o diário '{path}': {e}"),
+            Self::LargestFiles => write!(f, "Maiores ficheiros:"),
+            Self::LicenseMissing(path) => write!(f, "'{path}' não começa com a licença"),
+            Self::LicenseUnreadable(path) => write!(f, "Não foi possível ler o ficheiro de licença '{path}'"),
+            Self::LineNumberNotIncreasing(offset, line, previous) => write!(f, "a mudança de linha no byte {offset} inicia a linha {line} depois da linha {previous}"),
+            Self::LineTooLong(width, max) => write!(f, "Linha com {width} caracteres, mais do que o máximo de {max}"),
+            Self::LockFailed(path, reason) => write!(f, "Não foi possível bloquear '{path}': {reason}"),
+            Self::MacroExpansionOf(name) => write!(f, "<expansão da macro {name}>"),
+            Self::ManifestInvalid(path, e) => write!(f, "Não foi possível ler o manifesto '{path}': {e}"),
+            Self::ManifestUnwritable(path, e) => write!(f, "Não foi possível escrever o manifesto '{path}': {e}"),
+            Self::ManifestWritten(path, n) => write!(f, "Manifesto '{path}' escrito com {n} ficheiro(s)"),
+            Self::MarkersFound(counts) => write!(f, "Comentários que assinalam trabalho por terminar: {counts}"),
+            Self::Misspelled(word) => write!(f, "Palavra '{word}' possivelmente mal escrita num comentário"),
+            Self::MixedScriptIdentifier(identifier, scripts) => write!(f, "O identificador '{identifier}' mistura sistemas de escrita ({scripts})"),
+            Self::ModFileGenerated(path) => write!(f, "Ficheiro de módulo '{path}' gerado"),
+            Self::ModFileIsGenerated(path) => write!(
+                f,
+                "'{path}' é gerado a partir de um ficheiro XR e não será substituído"
+            ),
+            Self::ModFileNotGenerated(path) => write!(
+                f,
+                "'{path}' não foi gerado pelo xr e não será substituído"
+            ),
+            Self::ModuleNotAmalgamated(path) => write!(f, "'{path}' não está num caminho de módulo válido; não foi incluído no ficheiro amalgamado"),
+            Self::MultiLineCommentWithoutBeginning => {
+                write!(f, "Fim de comentário multilinha detetado sem início.")
+            }
+            Self::NonPrintableInString(c) => write!(f, "O literal de string contém o carácter não imprimível U+{:04X}", *c as u32),
+            Self::NondeterministicContent(path, line, what) => write!(f, "'{path}' não é reprodutível: a linha {line} contém {what}"),
+            Self::NotUtf8(path, offset) => {
+                write!(f, "'{path}' ignorado: UTF-8 inválido no byte {offset}")
+            }
+            Self::OutputCollision(output) => write!(f, "Vários ficheiros de entrada são gerados para '{output}', que não é escrito"),
+            Self::OutputFileFailed => write!(f, "Falha ao gerar o ficheiro de saída"),
+            Self::OutputNotReproducible(path) => write!(f, "'{path}' não é reprodutível: gerá-lo duas vezes deu conteúdos diferentes"),
+            Self::OutputSplit(path, parts) => write!(f, "'{path}' foi dividido em {parts} partes"),
+            Self::OverwriteDeclined(path) => write!(f, "'{path}' não foi substituído"),
+            Self::ParserPanicked(path, e) => {
+                write!(f, "O parser entrou em pânico com '{path}': {e}")
+            }
+            Self::ParsingTimedOut(timeout) => write!(f, "A análise demorou mais de {timeout:?}; o ficheiro não foi analisado"),
+            Self::PlanGenerate(input, output) => write!(f, "gerar '{output}' a partir de '{input}'"),
+            Self::PlanSkip(input) => write!(f, "ignorar '{input}'"),
+            Self::PlanSummary(generated, skipped, mod_files) => write!(f, "Plano: {generated} ficheiro(s) a gerar, {skipped} a ignorar e {mod_files} ficheiro(s) mod a escrever"),
+            Self::PlanWriteModFile(path) => write!(f, "escrever '{path}'"),
+            Self::ProcessingFile(path) => write!(f, "A processar o ficheiro '{path}'"),
+            Self::ProfileMemory(owned, interned, distinct) => write!(f, "Texto dos tokens: {owned} byte(s) com cópias, {interned} byte(s) com interning ({distinct} string(s) distinta(s), {} byte(s) poupado(s))", owned.saturating_sub(*interned)),
+            Self::ProfileParsing(files, tokens, duration) => write!(f, "{files} ficheiro(s) analisado(s) em {tokens} token(s) em {duration:?}"),
+            Self::ProgramNotRun(program, e) => write!(f, "Não foi possível executar '{program}': {e}"),
+            Self::RemoteCacheFailed(url, e) => write!(f, "A cache remota '{url}' deixou de ser usada depois de falhar ({e}); os restantes ficheiros foram gerados localmente"),
+            Self::RemoteFileUnreadable(url, e) => write!(f, "Não foi possível descarregar '{url}': {e}"),
+            Self::RemoteNotAllowed(url) => write!(f, "'{url}' ignorado: os ficheiros remotos só são processados com --allow-remote"),
+            Self::RemoteUnsupported => write!(
+                f,
+                "o xr foi compilado sem a funcionalidade remote, que lê ficheiros por HTTP"
+            ),
+            Self::ReportCode => write!(f, "Código"),
+            Self::ReportCount => write!(f, "Ocorrências"),
+            Self::ReportNoDiagnostics => write!(f, "Sem diagnósticos"),
+            Self::ReportSummary(files, errors, warnings) => write!(f, "{files} ficheiro(s) processado(s), {errors} erro(s), {warnings} aviso(s)"),
+            Self::ReportTitle => write!(f, "Relatório XR"),
+            Self::ReportWritten(path) => write!(f, "Relatório escrito em '{path}'"),
+            Self::RustfmtFailed(reason) => write!(f, "O rustfmt falhou: {reason}"),
+            Self::SecretInComment(kind) => write!(f, "Possível {kind} num comentário"),
+            Self::SecretInString(kind) => write!(f, "Possível {kind} num literal de string"),
+            Self::SecretsFound(n) => write!(f, "{n} possível(is) segredo(s) encontrado(s)"),
+            Self::SharedFileChanged(path) => write!(f, "'{path}' foi alterado, pelo que todos os ficheiros são gerados novamente"),
+            Self::Skipped(path) => write!(f, "Ignorado '{path}'"),
+            Self::SkippedIo(path, e) => write!(f, "Ignorado '{path}': {e}"),
+            Self::SkippedLoop(path, ancestor) => write!(
+                f,
+                "Ignorado '{path}': ligação simbólica em ciclo para '{ancestor}'"
+            ),
+            Self::SnapshotMismatch(path, line, expected, actual) => write!(f, "'{path}' difere do seu snapshot na linha {line}\n  esperado: {expected}\n  obtido:   {actual}"),
+            Self::SnapshotMissing(path) => write!(f, "O snapshot '{path}' não existe (execute com --bless para o criar)"),
+            Self::SnapshotUpdated(path) => write!(f, "Snapshot '{path}' atualizado"),
+            Self::SnapshotsPassed(n, total) => write!(f, "{n} de {total} snapshot(s) passaram"),
+            Self::SpanMismatch(start, end) => write!(f, "o intervalo {start}..{end} não corresponde ao texto do seu token"),
+            Self::StrayQuote => write!(f, "Aspa solta: o literal de carácter não é fechado na mesma linha"),
+            Self::StringTooLong(length, max) => write!(f, "Literal de string com {length} caracteres, mais do que o máximo de {max}"),
+            Self::TokenGap(offset) => write!(
+                f,
+                "o token no byte {offset} não começa onde o token anterior terminou"
+            ),
+            Self::TokensIncomplete(covered, len) => {
+                write!(f, "os tokens cobrem {covered} de {len} byte(s) da entrada")
+            }
+            Self::TooManyRawStringHashes(max) => write!(f, "Literal de string raw delimitado por mais de {max} cardinais"),
+            Self::TooManyTokens(max) => write!(f, "O ficheiro tem mais de {max} tokens; o resto do ficheiro não foi analisado"),
+            Self::TraceUnwritable(path, e) => {
+                write!(f, "Não foi possível escrever o registo do parser '{path}': {e}")
+            }
+            Self::UnclosedCharLiteral => {
+                write!(f, "Literal de carácter ou lifetime não fechado")
+            }
+            Self::UnclosedMultiLineComment(levels) => write!(
+                f,
+                "Comentário multilinha não fechado ({levels} nível(is) por fechar)."
+            ),
+            Self::UnclosedStringLiteral => write!(f, "Literal de string não fechado"),
+            Self::UnfinishedWork(text) => write!(f, "Trabalho por terminar: {text}"),
+            Self::UnknownErrorCode(code) => write!(f, "Código de erro desconhecido '{code}'"),
+            Self::UnknownSuppressionCode(code) => {
+                write!(f, "'{code}' não é um código do xr e não suprime nada")
+            }
+            Self::UnusedSuppression => write!(f, "O comentário de supressão não suprimiu nada"),
+            Self::Version(version) => write!(f, "versão {version}"),
+            Self::WaitingForChanges => write!(f, "À espera de alterações..."),
+            Self::WaitingForLock(pid) => write!(f, "À espera que outra instância do xr (pid {pid}) termine..."),
+            Self::WarningCodePrefix(code) => write!(f, "Aviso[{code}]: "),
+            Self::WarningPrefix => write!(f, "Aviso: "),
+            Self::WordsChecked(n) => write!(f, "Ortografia de {n} palavra(s) de comentários verificada"),
+        }
+    }
+}
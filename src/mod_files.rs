@@ -0,0 +1,160 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    arguments::OutputEncoding,
+    header::Header,
+    long_paths,
+    messages::Message,
+    sink::{self, Outcome},
+    terminal_helper::TerminalOutput,
+};
+
+const MOD_FILE_NAME: &str = "mod.rs";
+
+// first line of every mod.rs written by xr, whatever its header, which tells it apart from a
+// mod.rs written by hand
+const MARKER: &str = "// @generated by xr --mod-files";
+
+// module roots that must never be declared as child modules
+pub const RESERVED_STEMS: [&str; 3] = ["mod", "lib", "main"];
+
+pub fn is_valid_module_name(name: &str) -> bool {
+    let mut chars = name.chars();
+
+    match chars.next() {
+        Some(c) if c == '_' || c.is_ascii_alphabetic() => {
+            name != "_" && chars.all(|c| c == '_' || c.is_ascii_alphanumeric())
+        }
+        _ => false,
+    }
+}
+
+fn collect_modules(
+    root: &Path,
+    files: &[PathBuf],
+    output: &mut TerminalOutput,
+) -> BTreeMap<PathBuf, BTreeSet<String>> {
+    let mut modules = BTreeMap::<PathBuf, BTreeSet<String>>::new();
+
+    for file in files {
+        let Some(stem) = file.file_stem().and_then(|s| s.to_str()) else {
+            continue;
+        };
+
+        if RESERVED_STEMS.contains(&stem) {
+            continue;
+        }
+
+        if !is_valid_module_name(stem) {
+            output.writeln_warning(Message::InvalidModuleName(file.display().to_string()));
+            continue;
+        }
+
+        let mut name = stem.to_string();
+        let mut dir = file.parent().unwrap_or(root).to_path_buf();
+
+        loop {
+            modules.entry(dir.clone()).or_default().insert(name);
+
+            if dir == root || !dir.starts_with(root) {
+                break;
+            }
+
+            name = match dir.file_name().and_then(|s| s.to_str()) {
+                Some(n) if is_valid_module_name(n) => n.to_string(),
+                _ => break,
+            };
+
+            match dir.parent() {
+                Some(parent) => dir = parent.to_path_buf(),
+                None => break,
+            }
+        }
+    }
+
+    modules
+}
+
+// mod.rs file declaring the generated modules of a directory
+pub struct ModFile {
+    pub path: PathBuf,
+    names: BTreeSet<String>,
+}
+
+fn mod_file_contents(names: &BTreeSet<String>) -> String {
+    let declarations: String = names
+        .iter()
+        .map(|name| format!("pub mod {name};\n"))
+        .collect();
+
+    format!("{MARKER}\n{declarations}")
+}
+
+fn is_generated(contents: &[u8]) -> bool {
+    String::from_utf8_lossy(contents)
+        .lines()
+        .any(|line| line == MARKER)
+}
+
+pub fn plan(root: &Path, generated: &[PathBuf], output: &mut TerminalOutput) -> Vec<ModFile> {
+    let mut mod_files = Vec::new();
+
+    for (dir, names) in collect_modules(root, generated, output) {
+        let path = dir.join(MOD_FILE_NAME);
+
+        if generated.contains(&path) {
+            output.writeln_error(Message::ModFileIsGenerated(path.display().to_string()));
+        } else if fs::read(long_paths::extended(&path)).is_ok_and(|c| !is_generated(&c)) {
+            output.writeln_warning(Message::ModFileNotGenerated(path.display().to_string()));
+        } else {
+            mod_files.push(ModFile { path, names });
+        }
+    }
+
+    mod_files
+}
+
+pub fn write(
+    mod_file: &ModFile,
+    header: &Header,
+    encoding: OutputEncoding,
+    output: &mut TerminalOutput,
+) {
+    let filename = mod_file.path.display().to_string();
+    let text = header.apply(&mod_file_contents(&mod_file.names));
+
+    match sink::write(
+        &long_paths::extended(&mod_file.path),
+        &encoding.encode(&text),
+    ) {
+        Ok(Outcome::Written) => output.writeln_info(Message::ModFileGenerated(filename)),
+        Ok(Outcome::HeldBack) => {}
+        Err(_) => output.writeln_error(Message::FileWriteFailed(filename)),
+    }
+}
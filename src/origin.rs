@@ -0,0 +1,207 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use std::{fmt, path::PathBuf};
+
+use crate::{
+    error_codes::ErrorCode,
+    limits::Limits,
+    messages::Message,
+    parser::{self, InvalidKind, Sequence, Span, Token},
+    visitor::{self, Position, Rewritten, TokenVisitor},
+};
+
+/// Identity of a parsed text, reported with its diagnostics instead of a guess made from the
+/// filesystem.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum FileOrigin {
+    /// File read from disk
+    Path(PathBuf),
+    /// Standard input, shown as `<stdin>`
+    Stdin,
+    /// Text produced by expanding the named macro
+    MacroExpansion(String),
+    /// Any other source, shown with the given name
+    Virtual(String),
+}
+
+impl fmt::Display for FileOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Path(path) => write!(f, "{}", path.display()),
+            Self::Stdin => write!(f, "<stdin>"),
+            Self::MacroExpansion(name) => write!(f, "{}", Message::MacroExpansionOf(name.clone())),
+            Self::Virtual(name) => write!(f, "{name}"),
+        }
+    }
+}
+
+/// Error or warning of the parser, located in the text it was found in.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NamedDiagnostic {
+    pub origin: FileOrigin,
+    pub code: ErrorCode,
+    pub message: String,
+    pub position: Position,
+}
+
+impl fmt::Display for NamedDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}: {}: {}",
+            self.origin, self.position.line, self.position.column, self.code, self.message
+        )
+    }
+}
+
+/// Text produced by rewriting a [`ParsedFile`], whose provenance refers to the text of `origin`.
+#[derive(Clone, Debug)]
+pub struct NamedRewritten {
+    pub origin: FileOrigin,
+    pub rewritten: Rewritten,
+}
+
+/// Tokens of a text together with its identity.
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ParsedFile<'a> {
+    pub origin: FileOrigin,
+    pub tokens: Vec<Sequence<'a, Token>>,
+}
+
+/// Token of an [`OwnedParsedFile`], whose text is the part of the text of the file in its span.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OwnedToken {
+    pub token: Token,
+    pub span: Span,
+}
+
+/// [`ParsedFile`] that owns its text, so that it can be kept after the parsed text is dropped,
+/// or persisted and loaded again.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OwnedParsedFile {
+    pub origin: FileOrigin,
+    pub text: String,
+    pub tokens: Vec<OwnedToken>,
+}
+
+impl OwnedParsedFile {
+    /// Borrows the text again as a [`ParsedFile`].
+    ///
+    /// # Panics
+    ///
+    /// If a span is outside of the text or not on a char boundary, which only happens when the
+    /// tokens were changed after [`ParsedFile::to_owned`].
+    pub fn as_parsed(&self) -> ParsedFile<'_> {
+        ParsedFile {
+            origin: self.origin.clone(),
+            tokens: self
+                .tokens
+                .iter()
+                .map(|t| Sequence {
+                    token: t.token.clone(),
+                    text: &self.text[t.span.start..t.span.end],
+                    span: t.span,
+                })
+                .collect(),
+        }
+    }
+}
+
+struct DiagnosticCollector<'o> {
+    origin: &'o FileOrigin,
+    diagnostics: Vec<NamedDiagnostic>,
+}
+
+impl<'a> TokenVisitor<'a> for DiagnosticCollector<'_> {
+    fn visit_invalid(&mut self, kind: &InvalidKind, _: &'a str, position: Position) {
+        self.diagnostics.push(NamedDiagnostic {
+            origin: self.origin.clone(),
+            code: kind.code(),
+            message: kind.to_string(),
+            position,
+        });
+    }
+}
+
+impl ParsedFile<'_> {
+    /// Copies the text of the tokens, which cover the whole parsed text, into an
+    /// [`OwnedParsedFile`].
+    pub fn to_owned(&self) -> OwnedParsedFile {
+        OwnedParsedFile {
+            origin: self.origin.clone(),
+            text: self.tokens.iter().map(|t| t.text).collect(),
+            tokens: self
+                .tokens
+                .iter()
+                .map(|t| OwnedToken {
+                    token: t.token.clone(),
+                    span: t.span,
+                })
+                .collect(),
+        }
+    }
+
+    /// Errors found by the parser, in the order they appear in the text.
+    pub fn diagnostics(&self) -> Vec<NamedDiagnostic> {
+        let mut collector = DiagnosticCollector {
+            origin: &self.origin,
+            diagnostics: Vec::new(),
+        };
+
+        visitor::walk(&self.tokens, &mut collector);
+        collector.diagnostics
+    }
+
+    /// Same as [`visitor::rewrite_with_provenance`], keeping the origin with the provenance.
+    pub fn rewrite<F>(&self, replace: F) -> NamedRewritten
+    where
+        F: FnMut(&Token, &str, Position) -> Option<String>,
+    {
+        NamedRewritten {
+            origin: self.origin.clone(),
+            rewritten: visitor::rewrite_with_provenance(&self.tokens, replace),
+        }
+    }
+}
+
+/// Parses a text that does not necessarily come from a file, reporting its diagnostics
+/// against the given origin.
+pub fn parse_named(text: &str, origin: FileOrigin) -> ParsedFile<'_> {
+    parse_named_with_limits(text, origin, &Limits::default())
+}
+
+pub fn parse_named_with_limits<'a>(
+    text: &'a str,
+    origin: FileOrigin,
+    limits: &Limits,
+) -> ParsedFile<'a> {
+    ParsedFile {
+        origin,
+        tokens: parser::parse_with_limits(text, limits),
+    }
+}
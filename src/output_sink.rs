@@ -0,0 +1,150 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Destination of the generated files, so that a generation run can write them somewhere other
+/// than the filesystem.
+pub trait OutputSink {
+    /// Writes the contents of a generated file, replacing it if it was already written.
+    fn write(&mut self, path: &Path, contents: &[u8]) -> io::Result<()>;
+
+    /// Completes the output once every file is written, such as the end of an archive.
+    fn finish(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Writes the files to the filesystem, creating their directories as needed.
+#[derive(Default)]
+pub struct FileSystem;
+
+impl OutputSink for FileSystem {
+    fn write(&mut self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        // written under a temporary name and then renamed, so that a run that is stopped while
+        // writing does not leave a truncated file
+        let mut temporary = path.as_os_str().to_owned();
+        temporary.push(format!(".{}.tmp", process::id()));
+
+        let written = fs::write(&temporary, contents).and_then(|_| fs::rename(&temporary, path));
+        if written.is_err() {
+            fs::remove_file(&temporary).ok();
+        }
+        written
+    }
+}
+
+/// Keeps the files in memory, by path.
+#[derive(Default)]
+pub struct Memory {
+    pub files: BTreeMap<PathBuf, Vec<u8>>,
+}
+
+impl OutputSink for Memory {
+    fn write(&mut self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        self.files.insert(path.to_path_buf(), contents.to_vec());
+        Ok(())
+    }
+}
+
+/// Prints the files to the standard output, each after a comment line with its path.
+#[derive(Default)]
+pub struct Stdout;
+
+impl OutputSink for Stdout {
+    fn write(&mut self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        let mut stdout = io::stdout().lock();
+
+        writeln!(stdout, "// {}", path.display())?;
+        stdout.write_all(contents)?;
+        if !contents.ends_with(b"\n") {
+            writeln!(stdout)?;
+        }
+
+        stdout.flush()
+    }
+}
+
+/// Writes the files into a tar archive, so that a whole run is captured as a single file.
+pub struct TarArchive<W: Write> {
+    builder: tar::Builder<W>,
+    mtime: u64,
+}
+
+impl TarArchive<File> {
+    pub fn create(path: &Path) -> io::Result<TarArchive<File>> {
+        Ok(TarArchive::new(File::create(path)?))
+    }
+}
+
+impl<W: Write> TarArchive<W> {
+    pub fn new(writer: W) -> TarArchive<W> {
+        let mtime = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+
+        TarArchive {
+            builder: tar::Builder::new(writer),
+            mtime,
+        }
+    }
+
+    /// Uses the given modification time for every file, instead of the current time, so that
+    /// archives of the same files are identical.
+    pub fn with_mtime(mut self, mtime: u64) -> TarArchive<W> {
+        self.mtime = mtime;
+        self
+    }
+}
+
+impl<W: Write> OutputSink for TarArchive<W> {
+    fn write(&mut self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(self.mtime);
+
+        // archive paths are relative, whatever the location of the files
+        let relative: PathBuf = path
+            .components()
+            .filter(|c| matches!(c, std::path::Component::Normal(_)))
+            .collect();
+
+        self.builder.append_data(&mut header, relative, contents)
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.builder.finish()?;
+        self.builder.get_mut().flush()
+    }
+}
@@ -20,13 +20,24 @@
     DEALINGS IN THE SOFTWARE.
 */
 
-use std::{slice::Iter, str::CharIndices};
-
-#[derive(Copy, Clone, PartialEq)]
+use std::{
+    cell::{Cell, RefCell},
+    fmt, mem, panic,
+    slice::Iter,
+    str,
+    str::{CharIndices, FromStr},
+    time::{Duration, Instant},
+};
+
+use crate::{error_codes::ErrorCode, limits::Limits, messages::Message};
+
+#[derive(Copy, Clone, Debug, PartialEq)]
 enum LevelOneToken {
     Asterisc,
     BackSlash,
     CharDelimiter,
+    // C0 control character other than a tab or a line break, such as NUL
+    Control,
     Digit,
     ForwardSlash,
     Hash,
@@ -36,6 +47,7 @@ enum LevelOneToken {
     Other,
     StrDelimiter,
     UnderscoreLetter,
+    Whitespace,
 }
 
 impl From<char> for LevelOneToken {
@@ -52,32 +64,44 @@ impl From<char> for LevelOneToken {
             '0'..='9' => Self::Digit,
             '\r' | '\n' => Self::NewLine,
             '_' | 'a'..='z' | 'A'..='Z' => Self::UnderscoreLetter,
+            ' ' | '\t' => Self::Whitespace,
+            c if is_control(c) => Self::Control,
+            // identifiers may have letters other than ASCII, such as in café
+            c if c.is_alphabetic() => Self::UnderscoreLetter,
             _ => Self::Other,
         }
     }
 }
 
+// C0 control characters break the tools that read the generated files, except for tabs and
+// line breaks
+pub fn is_control(c: char) -> bool {
+    c.is_ascii_control() && c != '\x7f' && !matches!(c, '\t' | '\n' | '\r')
+}
+
 impl LevelOneToken {
     fn is_greedy(&self) -> bool {
         matches!(
             self,
-            Self::Digit | Self::Hash | Self::NewLine | Self::Other | Self::UnderscoreLetter
+            Self::Digit | Self::Hash | Self::Other | Self::UnderscoreLetter | Self::Whitespace
         )
     }
 }
 
-#[derive(Copy, Clone, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 enum LevelTwoToken {
     BackSlash,
     BeginMultiLineComment,
     BeginSingleLineComment,
     CharDelimiter,
+    Control,
     EndMultiLineComment,
     Hash,
     NewLine(usize),
     Other,
     StrDelimiter,
     StrPrefix,
+    Whitespace,
     Word,
 }
 
@@ -86,23 +110,35 @@ impl From<LevelOneToken> for LevelTwoToken {
         match value {
             LevelOneToken::BackSlash => Self::BackSlash,
             LevelOneToken::CharDelimiter => Self::CharDelimiter,
+            LevelOneToken::Control => Self::Control,
             LevelOneToken::Hash => Self::Hash,
             LevelOneToken::StrDelimiter => Self::StrDelimiter,
+            LevelOneToken::Whitespace => Self::Whitespace,
             _ => Self::Other,
         }
     }
 }
 
-#[derive(PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
 pub enum Token {
+    // line break ending a line that only has whitespace, with the number of the next line
+    BlankLine(usize),
     CharLiteral,
-    Invalid(String),
-    LifetimeElision,
+    Invalid(InvalidKind),
+    Lifetime,
+    // label of a loop or block, where it is declared ('outer: loop) and where it is used
+    // (break 'outer)
+    LoopLabel,
     MultiLineComment,
+    // line break, with the number of the next line
     NewLine(usize),
     Other,
     SingleLineComment,
     StrLiteral,
+    // run of spaces and tabs outside of literals and comments
+    Whitespace { spaces: usize, tabs: usize },
 }
 
 impl From<LevelTwoToken> for Token {
@@ -114,39 +150,251 @@ impl From<LevelTwoToken> for Token {
     }
 }
 
+// kind of a token without its data, named as in the exported tokens
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum TokenKind {
+    BlankLine,
+    CharLiteral,
+    Invalid,
+    Lifetime,
+    LoopLabel,
+    MultiLineComment,
+    NewLine,
+    Other,
+    SingleLineComment,
+    StrLiteral,
+    Whitespace,
+}
+
+impl TokenKind {
+    pub const ALL: [TokenKind; 11] = [
+        Self::BlankLine,
+        Self::CharLiteral,
+        Self::Invalid,
+        Self::Lifetime,
+        Self::LoopLabel,
+        Self::MultiLineComment,
+        Self::NewLine,
+        Self::Other,
+        Self::SingleLineComment,
+        Self::StrLiteral,
+        Self::Whitespace,
+    ];
+
+    // the names are part of the stable API (see the stability module)
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::BlankLine => "BlankLine",
+            Self::CharLiteral => "CharLiteral",
+            Self::Invalid => "Invalid",
+            Self::Lifetime => "Lifetime",
+            Self::LoopLabel => "LoopLabel",
+            Self::MultiLineComment => "MultiLineComment",
+            Self::NewLine => "NewLine",
+            Self::Other => "Other",
+            Self::SingleLineComment => "SingleLineComment",
+            Self::StrLiteral => "StrLiteral",
+            Self::Whitespace => "Whitespace",
+        }
+    }
+}
+
+impl fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnknownTokenKind(pub String);
+
+impl fmt::Display for UnknownTokenKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown token kind '{}'", self.0)
+    }
+}
+
+impl FromStr for TokenKind {
+    type Err = UnknownTokenKind;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.as_str() == name)
+            .ok_or_else(|| UnknownTokenKind(name.to_string()))
+    }
+}
+
 impl Token {
-    fn invalid_char_literal() -> Token {
-        Self::Invalid(String::from("Invalid char literal"))
+    pub fn kind(&self) -> TokenKind {
+        match self {
+            Self::BlankLine(_) => TokenKind::BlankLine,
+            Self::CharLiteral => TokenKind::CharLiteral,
+            Self::Invalid(_) => TokenKind::Invalid,
+            Self::Lifetime => TokenKind::Lifetime,
+            Self::LoopLabel => TokenKind::LoopLabel,
+            Self::MultiLineComment => TokenKind::MultiLineComment,
+            Self::NewLine(_) => TokenKind::NewLine,
+            Self::Other => TokenKind::Other,
+            Self::SingleLineComment => TokenKind::SingleLineComment,
+            Self::StrLiteral => TokenKind::StrLiteral,
+            Self::Whitespace { .. } => TokenKind::Whitespace,
+        }
     }
 
-    fn invalid_raw_string_literal() -> Token {
-        Self::Invalid(String::from("Invalid raw string literal"))
+    // number of the line that follows a line break
+    pub fn line_after(&self) -> Option<usize> {
+        match self {
+            Self::BlankLine(line_number) | Self::NewLine(line_number) => Some(*line_number),
+            _ => None,
+        }
     }
 
-    fn multi_line_comment_without_beggining() -> Self {
-        Self::Invalid(String::from(
-            "Multiline end comment detected without a beginning.",
-        ))
+    fn whitespace(text: &str) -> Token {
+        let tabs = text.matches('\t').count();
+
+        Self::Whitespace {
+            spaces: text.len() - tabs,
+            tabs,
+        }
     }
+}
 
-    fn unclosed_char_literal() -> Self {
-        Self::Invalid(String::from("Unclosed char or lifetime elision"))
+// why a part of the text could not be parsed, with the details needed to describe it
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum InvalidKind {
+    CommentTooDeep { max: usize },
+    // control character outside of literals and comments
+    ControlCharacter { ch: char },
+    InvalidCharLiteral,
+    InvalidRawStringLiteral,
+    // end of a multiline comment (*/) outside of any comment
+    MultiLineCommentWithoutBeginning,
+    // quote and backslash of an escaped char literal that is not closed on the same line
+    StrayQuote,
+    TimedOut { timeout: Duration },
+    TooManyRawStringHashes { max: usize },
+    TooManyTokens { max: usize },
+    UnclosedCharLiteral,
+    UnclosedMultiLineComment { levels: usize },
+    UnclosedStringLiteral,
+}
+
+impl InvalidKind {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::UnclosedStringLiteral => ErrorCode::E0001,
+            Self::UnclosedMultiLineComment { .. } => ErrorCode::E0002,
+            Self::UnclosedCharLiteral => ErrorCode::E0003,
+            Self::InvalidCharLiteral => ErrorCode::E0004,
+            Self::InvalidRawStringLiteral => ErrorCode::E0005,
+            Self::MultiLineCommentWithoutBeginning => ErrorCode::E0006,
+            Self::TooManyTokens { .. } => ErrorCode::E0007,
+            Self::CommentTooDeep { .. } => ErrorCode::E0008,
+            Self::TooManyRawStringHashes { .. } => ErrorCode::E0009,
+            Self::TimedOut { .. } => ErrorCode::E0010,
+            Self::StrayQuote => ErrorCode::E0013,
+            Self::ControlCharacter { .. } => ErrorCode::E0015,
+        }
+    }
+
+    fn message(&self) -> Message {
+        match self {
+            Self::CommentTooDeep { max } => Message::CommentTooDeep(*max),
+            Self::ControlCharacter { ch } => Message::ControlCharacter(*ch),
+            Self::InvalidCharLiteral => Message::InvalidCharLiteral,
+            Self::InvalidRawStringLiteral => Message::InvalidRawStringLiteral,
+            Self::MultiLineCommentWithoutBeginning => Message::MultiLineCommentWithoutBeginning,
+            Self::StrayQuote => Message::StrayQuote,
+            Self::TimedOut { timeout } => Message::ParsingTimedOut(*timeout),
+            Self::TooManyRawStringHashes { max } => Message::TooManyRawStringHashes(*max),
+            Self::TooManyTokens { max } => Message::TooManyTokens(*max),
+            Self::UnclosedCharLiteral => Message::UnclosedCharLiteral,
+            Self::UnclosedMultiLineComment { levels } => Message::UnclosedMultiLineComment(*levels),
+            Self::UnclosedStringLiteral => Message::UnclosedStringLiteral,
+        }
     }
+}
+
+impl fmt::Display for InvalidKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.message().fmt(f)
+    }
+}
+
+// byte range of a token in the parsed text
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
 
-    fn unclosed_multi_line_comment(unclosed_levels: usize) -> Self {
-        Self::Invalid(format!(
-            "Multiline comment not closed ({unclosed_levels} level(s) unclosed)."
-        ))
+impl Span {
+    pub fn len(&self) -> usize {
+        self.end - self.start
     }
 
-    fn unclosed_string_literal() -> Self {
-        Self::Invalid(String::from("Unclosed string literal"))
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
     }
 }
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Sequence<'a, T: PartialEq> {
     pub token: T,
     pub text: &'a str,
+    pub span: Span,
+}
+
+// Cooperative cancellation: once the deadline passes, the parsers behave as if they had
+// reached the end of the text, so that every loop finishes.
+struct Budget {
+    deadline: Option<Instant>,
+    steps: Cell<u32>,
+    expired: Cell<bool>,
+}
+
+impl Budget {
+    // checking the clock is expensive compared to a parsing step
+    const STEPS_BETWEEN_CHECKS: u32 = 4096;
+
+    fn new(timeout: Option<Duration>) -> Budget {
+        // there is no clock in the browser without bindings to javascript, so timeouts are
+        // not applied there
+        #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+        let timeout: Option<Duration> = timeout.and(None);
+
+        Budget {
+            deadline: timeout.map(|t| Instant::now() + t),
+            steps: Cell::new(0),
+            expired: Cell::new(false),
+        }
+    }
+
+    fn is_expired(&self) -> bool {
+        if self.expired.get() {
+            return true;
+        }
+
+        let Some(deadline) = self.deadline else {
+            return false;
+        };
+
+        let steps = self.steps.get() + 1;
+        if steps < Self::STEPS_BETWEEN_CHECKS {
+            self.steps.set(steps);
+            return false;
+        }
+
+        self.steps.set(0);
+        self.expired.set(Instant::now() >= deadline);
+        self.expired.get()
+    }
 }
 
 trait Parser {
@@ -170,15 +418,16 @@ trait Parser {
     }
 }
 
-struct StrParser<'a> {
+struct StrParser<'a, 'c> {
     iterator: CharIndices<'a>,
     current_item: Option<(usize, char)>,
     start_index: usize,
     text: &'a str,
+    budget: &'c Budget,
 }
 
-impl<'a> StrParser<'a> {
-    fn new(text: &'a str) -> StrParser {
+impl<'a, 'c> StrParser<'a, 'c> {
+    fn new(text: &'a str, budget: &'c Budget) -> StrParser<'a, 'c> {
         let mut iterator = text.char_indices();
         let current_item = iterator.next();
 
@@ -187,6 +436,7 @@ impl<'a> StrParser<'a> {
             current_item,
             start_index: 0,
             text,
+            budget,
         }
     }
 
@@ -200,31 +450,49 @@ impl<'a> StrParser<'a> {
         }
     }
 
-    fn parsed_str(&self) -> &'a str {
-        match self.current_item {
-            Some((end_index, _)) => &self.text[self.start_index..end_index],
-            None => &self.text[self.start_index..],
+    fn parsed_span(&self) -> Span {
+        let end = match self.current_item {
+            Some((end_index, _)) => end_index,
+            None => self.text.len(),
+        };
+
+        Span {
+            start: self.start_index,
+            end,
         }
     }
+
+    fn parsed_str(&self) -> &'a str {
+        let span = self.parsed_span();
+        &self.text[span.start..span.end]
+    }
 }
 
-impl<'a> Parser for StrParser<'a> {
+impl<'a, 'c> Parser for StrParser<'a, 'c> {
     fn next(&mut self) {
-        self.current_item = self.iterator.next();
+        self.current_item = match self.budget.is_expired() {
+            true => None,
+            false => self.iterator.next(),
+        };
     }
 }
 
-struct VecParser<'a, 'b, T: Copy + PartialEq> {
+struct VecParser<'a, 'b, 'c, T: Copy + PartialEq> {
     text: &'a str,
     iterator: Iter<'b, Sequence<'b, T>>,
     current_item: Option<&'b Sequence<'b, T>>,
     next_item: Option<&'b Sequence<'b, T>>,
     start_index: usize,
     end_index: usize,
+    budget: &'c Budget,
 }
 
-impl<'a, 'b, T: Copy + PartialEq> VecParser<'a, 'b, T> {
-    fn new(text: &'a str, vector: &'b Vec<Sequence<'b, T>>) -> VecParser<'a, 'b, T> {
+impl<'a, 'b, 'c, T: Copy + PartialEq> VecParser<'a, 'b, 'c, T> {
+    fn new(
+        text: &'a str,
+        vector: &'b Vec<Sequence<'b, T>>,
+        budget: &'c Budget,
+    ) -> VecParser<'a, 'b, 'c, T> {
         let mut iterator = vector.iter();
         let next_item = iterator.next();
 
@@ -235,10 +503,11 @@ impl<'a, 'b, T: Copy + PartialEq> VecParser<'a, 'b, T> {
             next_item,
             start_index: 0,
             end_index: 0,
+            budget,
         }
     }
 
-    fn begin_parsing(&mut self) -> Option<&Sequence<T>> {
+    fn begin_parsing(&mut self) -> Option<&Sequence<'_, T>> {
         self.start_index = self.end_index;
         self.next();
 
@@ -266,17 +535,35 @@ impl<'a, 'b, T: Copy + PartialEq> VecParser<'a, 'b, T> {
         Some(self.next_item?.token)
     }
 
+    // items after the current one, without consuming them
+    fn upcoming(&self) -> impl Iterator<Item = &'b Sequence<'b, T>> {
+        self.next_item.into_iter().chain(self.iterator.clone())
+    }
+
     fn next_token_is(&self, value: T) -> bool {
         matches!(self.next_token(), Some(token) if (token == value))
     }
 
+    fn parsed_span(&self) -> Span {
+        Span {
+            start: self.start_index,
+            end: self.end_index,
+        }
+    }
+
     fn parsed_str(&self) -> &'a str {
         &self.text[self.start_index..self.end_index]
     }
 }
 
-impl<'a, 'b, T: Copy + PartialEq> Parser for VecParser<'a, 'b, T> {
+impl<'a, 'b, 'c, T: Copy + PartialEq> Parser for VecParser<'a, 'b, 'c, T> {
     fn next(&mut self) {
+        if self.budget.is_expired() {
+            self.current_item = None;
+            self.next_item = None;
+            return;
+        }
+
         self.current_item = self.next_item;
         self.next_item = self.iterator.next();
 
@@ -286,18 +573,112 @@ impl<'a, 'b, T: Copy + PartialEq> Parser for VecParser<'a, 'b, T> {
     }
 }
 
-pub fn parse(text: &str) -> Vec<Sequence<Token>> {
-    let result: Vec<Sequence<LevelOneToken>> = parse_level_one_tokens(text);
-    let result: Vec<Sequence<LevelTwoToken>> = parse_level_two_tokens(text, result);
-    parse_level_three_tokens(text, result)
+pub enum ParseError {
+    InvalidUtf8(usize),
+    Panic(String),
+}
+
+// entry point for fuzzing: arbitrary bytes never make it panic
+pub fn parse_bytes(bytes: &[u8]) -> Result<Vec<Sequence<'_, Token>>, ParseError> {
+    let text = str::from_utf8(bytes).map_err(|e| ParseError::InvalidUtf8(e.valid_up_to()))?;
+
+    panic::catch_unwind(|| parse(text))
+        .map_err(|payload| ParseError::Panic(crate::panic_message(payload.as_ref()).to_string()))
+}
+
+pub fn parse(text: &str) -> Vec<Sequence<'_, Token>> {
+    parse_with_limits(text, &Limits::default())
+}
+
+fn named<T: PartialEq + fmt::Debug>(tokens: Vec<Sequence<T>>) -> Vec<Sequence<String>> {
+    tokens
+        .into_iter()
+        .map(|s| Sequence {
+            token: format!("{:?}", s.token),
+            text: s.text,
+            span: s.span,
+        })
+        .collect()
+}
+
+// tokens of the intermediate levels of the parser, named after their kind, for debugging it
+pub fn level_one_tokens(text: &str) -> Vec<Sequence<'_, String>> {
+    named(parse_level_one_tokens(text, &Budget::new(None)))
+}
+
+pub fn level_two_tokens(text: &str) -> Vec<Sequence<'_, String>> {
+    let budget = Budget::new(None);
+    let result = parse_level_one_tokens(text, &budget);
+    named(parse_level_two_tokens(text, result, &budget))
 }
 
-fn parse_level_one_tokens(text: &str) -> Vec<Sequence<LevelOneToken>> {
+pub fn parse_with_limits<'a>(text: &'a str, limits: &Limits) -> Vec<Sequence<'a, Token>> {
+    let budget = Budget::new(limits.timeout);
+
+    let result: Vec<Sequence<LevelOneToken>> = parse_level_one_tokens(text, &budget);
+    let result: Vec<Sequence<LevelTwoToken>> = parse_level_two_tokens(text, result, &budget);
+    let result = parse_level_three_tokens(text, result, limits, &budget);
+
+    if budget.is_expired() {
+        let timeout = limits.timeout.unwrap_or_default();
+
+        vec![Sequence {
+            token: Token::Invalid(InvalidKind::TimedOut { timeout }),
+            text,
+            span: Span {
+                start: 0,
+                end: text.len(),
+            },
+        }]
+    } else {
+        result
+    }
+}
+
+thread_local! {
+    // decisions of the parsers, recorded only while tracing
+    static TRACE: RefCell<Option<Vec<String>>> = const { RefCell::new(None) };
+}
+
+/// Starts recording the decisions of the parser in the current thread, such as the kind of
+/// token each branch produced and the token that followed it, to debug misparses.
+pub fn start_trace() {
+    TRACE.with(|t| *t.borrow_mut() = Some(Vec::new()));
+}
+
+/// Returns the decisions recorded since the trace started or was last taken.
+pub fn take_trace() -> Vec<String> {
+    TRACE.with(|t| t.borrow_mut().as_mut().map(mem::take).unwrap_or_default())
+}
+
+fn trace<F: FnOnce() -> String>(level: u8, span: Span, decision: F) {
+    TRACE.with(|t| {
+        if let Some(lines) = t.borrow_mut().as_mut() {
+            lines.push(format!(
+                "L{level} {}..{} {}",
+                span.start,
+                span.end,
+                decision()
+            ));
+        }
+    });
+}
+
+fn parse_level_one_tokens<'a>(text: &'a str, budget: &Budget) -> Vec<Sequence<'a, LevelOneToken>> {
     let mut result = Vec::<Sequence<LevelOneToken>>::new();
 
-    let mut parser = StrParser::new(&text);
+    let mut parser = StrParser::new(text, budget);
     while let Some(c) = parser.begin_parsing() {
-        let token = LevelOneToken::from(c);
+        let mut token = LevelOneToken::from(c);
+
+        // every line break is a token of its own; a carriage return only breaks a line if it
+        // is followed by a line feed
+        if c == '\r' {
+            match parser.current_item {
+                Some((_, '\n')) => parser.next(),
+                _ => token = LevelOneToken::Other,
+            }
+        }
 
         if token.is_greedy() {
             parser.parse_while(
@@ -306,7 +687,9 @@ fn parse_level_one_tokens(text: &str) -> Vec<Sequence<LevelOneToken>> {
         }
 
         let text = parser.parsed_str();
-        result.push(Sequence { token, text });
+        let span = parser.parsed_span();
+        trace(1, span, || format!("{token:?} {text:?}"));
+        result.push(Sequence { token, text, span });
     }
 
     result
@@ -315,21 +698,23 @@ fn parse_level_one_tokens(text: &str) -> Vec<Sequence<LevelOneToken>> {
 fn parse_level_two_tokens<'a>(
     text: &'a str,
     sequences: Vec<Sequence<LevelOneToken>>,
+    budget: &Budget,
 ) -> Vec<Sequence<'a, LevelTwoToken>> {
     let mut result = Vec::<Sequence<LevelTwoToken>>::new();
 
     let mut line_number: usize = 1;
 
-    let mut parser = VecParser::new(&text, &sequences);
+    let mut parser = VecParser::new(text, &sequences, budget);
     while let Some(s) = parser.begin_parsing() {
+        let first = s.token;
         let token = match s.token {
             LevelOneToken::Asterisc => parse_possible_end_multi_line_comment(&mut parser),
             LevelOneToken::ForwardSlash => parse_possible_comment_token(&mut parser),
             LevelOneToken::LowerCaseB => {
                 parser.next_if(|p| p.next_token_is(LevelOneToken::LowerCaseR));
-                LevelTwoToken::StrPrefix
+                parse_possible_str_prefix(&mut parser)
             }
-            LevelOneToken::LowerCaseR => LevelTwoToken::StrPrefix,
+            LevelOneToken::LowerCaseR => parse_possible_str_prefix(&mut parser),
             LevelOneToken::NewLine => {
                 line_number += cout_new_lines(s.text);
                 LevelTwoToken::NewLine(line_number)
@@ -339,7 +724,12 @@ fn parse_level_two_tokens<'a>(
         };
 
         let text = parser.parsed_str();
-        result.push(Sequence { token, text });
+        let span = parser.parsed_span();
+        trace(2, span, || {
+            let next = parser.next_token();
+            format!("{first:?} -> {token:?} {text:?} next {next:?}")
+        });
+        result.push(Sequence { token, text, span });
     }
 
     result
@@ -357,6 +747,25 @@ fn cout_new_lines(text: &str) -> usize {
     new_lines
 }
 
+// b, r and br are only prefixes when followed by a literal, otherwise they begin a word
+// (such as break or 'block)
+fn parse_possible_str_prefix(parser: &mut VecParser<LevelOneToken>) -> LevelTwoToken {
+    // only the raw prefixes (r and br) may be followed by hashes, and only b by a char literal
+    let raw = parser.parsed_str().ends_with('r');
+
+    let next = parser.next_token();
+    trace(2, parser.parsed_span(), || {
+        format!("possible str prefix, raw {raw}, next {next:?}")
+    });
+
+    match next {
+        Some(LevelOneToken::StrDelimiter) => LevelTwoToken::StrPrefix,
+        Some(LevelOneToken::Hash) if raw => LevelTwoToken::StrPrefix,
+        Some(LevelOneToken::CharDelimiter) if !raw => LevelTwoToken::StrPrefix,
+        _ => parse_word(parser),
+    }
+}
+
 fn parse_word(parser: &mut VecParser<LevelOneToken>) -> LevelTwoToken {
     parser.parse_while(|p| {
         matches!(
@@ -393,45 +802,120 @@ fn parse_possible_comment_token(parser: &mut VecParser<LevelOneToken>) -> LevelT
 fn parse_level_three_tokens<'a>(
     text: &'a str,
     sequences: Vec<Sequence<LevelTwoToken>>,
+    limits: &Limits,
+    budget: &Budget,
 ) -> Vec<Sequence<'a, Token>> {
     let mut result = Vec::<Sequence<Token>>::new();
 
-    let mut parser = VecParser::new(&text, &sequences);
+    let mut line_is_blank = true;
+
+    let mut parser = VecParser::new(text, &sequences, budget);
     while let Some(s) = parser.begin_parsing() {
+        let first = s.token;
         let token = match s.token {
-            LevelTwoToken::BeginMultiLineComment => parse_multi_line_comment(&mut parser),
+            _ if result.len() == limits.max_tokens => {
+                parse_until_end(&mut parser);
+                Token::Invalid(InvalidKind::TooManyTokens {
+                    max: limits.max_tokens,
+                })
+            }
+            LevelTwoToken::BeginMultiLineComment => {
+                parse_multi_line_comment(&mut parser, limits.max_comment_depth)
+            }
             LevelTwoToken::BeginSingleLineComment => parse_single_line_comment(&mut parser),
             LevelTwoToken::CharDelimiter => parse_char_literal_or_elison(&mut parser),
-            LevelTwoToken::EndMultiLineComment => Token::multi_line_comment_without_beggining(),
+            LevelTwoToken::Control => Token::Invalid(InvalidKind::ControlCharacter {
+                ch: parser.parsed_str().chars().next().unwrap_or_default(),
+            }),
+            LevelTwoToken::EndMultiLineComment => {
+                Token::Invalid(InvalidKind::MultiLineCommentWithoutBeginning)
+            }
             LevelTwoToken::StrDelimiter => parse_string_literal(&mut parser, false, 0),
-            LevelTwoToken::StrPrefix => parse_possible_string_literal(&mut parser),
+            LevelTwoToken::StrPrefix => {
+                parse_possible_string_literal(&mut parser, limits.max_raw_string_hashes)
+            }
+            LevelTwoToken::Whitespace => Token::whitespace(parser.parsed_str()),
+            LevelTwoToken::NewLine(line_number) if line_is_blank => Token::BlankLine(line_number),
             other => Token::from(other),
         };
 
-        let text = parser.parsed_str();
-        result.push(Sequence { token, text });
+        line_is_blank = match token {
+            Token::BlankLine(_) | Token::NewLine(_) => true,
+            Token::Whitespace { .. } => line_is_blank,
+            _ => false,
+        };
+
+        let span = parser.parsed_span();
+        let token = match token {
+            Token::Lifetime => lifetime_or_label(text, span, &result),
+            token => token,
+        };
+        trace(3, span, || {
+            let next = parser.next_token();
+            let text = parser.parsed_str();
+            format!("{first:?} -> {token:?} {text:?} next {next:?}")
+        });
+
+        result.push(Sequence {
+            token,
+            text: parser.parsed_str(),
+            span,
+        });
     }
 
     result
 }
 
-fn parse_possible_string_literal(parser: &mut VecParser<LevelTwoToken>) -> Token {
-    if let Some(s) = parser.next_item {
-        match s.token {
-            LevelTwoToken::Hash => parse_raw_string_literal(parser, s.text.len()),
-            LevelTwoToken::StrDelimiter => parse_raw_string_literal(parser, 0),
-            _ => Token::Other,
+fn parse_until_end(parser: &mut VecParser<LevelTwoToken>) {
+    parser.parse_until(|p| p.next_token().is_none());
+}
+
+fn parse_possible_string_literal(
+    parser: &mut VecParser<LevelTwoToken>,
+    max_hashes: usize,
+) -> Token {
+    let raw = parser.parsed_str().ends_with('r');
+
+    let Some(s) = parser.next_item else {
+        return Token::Other;
+    };
+
+    trace(3, parser.parsed_span(), || {
+        format!(
+            "possible string literal, raw {raw}, next {:?} {:?}",
+            s.token, s.text
+        )
+    });
+
+    match s.token {
+        // r#type is a raw identifier
+        LevelTwoToken::Hash
+            if s.text.len() == 1 && starts_identifier(&parser.text[s.span.end..]) =>
+        {
+            Token::Other
         }
-    } else {
-        Token::Other
+        LevelTwoToken::Hash if s.text.len() > max_hashes => {
+            parser.next();
+            Token::Invalid(InvalidKind::TooManyRawStringHashes { max: max_hashes })
+        }
+        LevelTwoToken::Hash => parse_raw_string_literal(parser, s.text.len()),
+        LevelTwoToken::StrDelimiter => {
+            parser.next();
+            parse_string_literal(parser, raw, 0)
+        }
+        _ => Token::Other,
     }
 }
 
+fn starts_identifier(text: &str) -> bool {
+    text.starts_with(|c: char| c == '_' || c.is_alphabetic())
+}
+
 fn parse_raw_string_literal(parser: &mut VecParser<LevelTwoToken>, hash_len: usize) -> Token {
     parser.next();
 
-    if hash_len > 0 && !parser.next_if(|p| p.next_token_is(LevelTwoToken::StrDelimiter)) {
-        Token::invalid_raw_string_literal()
+    if !parser.next_if(|p| p.next_token_is(LevelTwoToken::StrDelimiter)) {
+        Token::Invalid(InvalidKind::InvalidRawStringLiteral)
     } else {
         parse_string_literal(parser, true, hash_len)
     }
@@ -461,7 +945,7 @@ fn parse_string_literal(
                     }
                 }
             }
-            None => return Token::unclosed_string_literal(),
+            None => return Token::Invalid(InvalidKind::UnclosedStringLiteral),
             _ => {}
         }
     }
@@ -470,23 +954,108 @@ fn parse_string_literal(
 fn parse_char_literal_or_elison(parser: &mut VecParser<LevelTwoToken>) -> Token {
     parser.next();
 
+    trace(3, parser.parsed_span(), || {
+        let current = parser.current_token();
+        let next = parser.next_token();
+        format!("char literal or lifetime, current {current:?} next {next:?}")
+    });
+
     match parser.current_token() {
+        Some(LevelTwoToken::BackSlash) if is_stray_quote(parser) => {
+            Token::Invalid(InvalidKind::StrayQuote)
+        }
         Some(LevelTwoToken::BackSlash) => {
             parser.next(); // ignore at least the next token that might be a CharDelimiter
             parse_until_close_char_literal(parser)
         }
         Some(LevelTwoToken::Word | LevelTwoToken::StrPrefix) => {
-            if parser.next_if(|p| p.next_token_is(LevelTwoToken::CharDelimiter)) {
+            // 'static' is a lifetime followed by a quote, as a char literal has a single char
+            let single_char = parser.parsed_str().chars().count() == 2;
+            if single_char && parser.next_if(|p| p.next_token_is(LevelTwoToken::CharDelimiter)) {
                 Token::CharLiteral
             } else {
-                Token::LifetimeElision
+                Token::Lifetime
             }
         }
-        Some(LevelTwoToken::StrDelimiter | LevelTwoToken::Other) => {
-            parse_until_close_char_literal(parser)
+        Some(
+            LevelTwoToken::Hash
+            | LevelTwoToken::StrDelimiter
+            | LevelTwoToken::Other
+            | LevelTwoToken::Whitespace,
+        ) => {
+            // the quote must follow a single char, so that a stray quote (as in Foo<' a>) does
+            // not consume the text after it while looking for the closing one
+            let single_char = parser
+                .current_item
+                .is_some_and(|s| s.text.chars().count() == 1);
+            if single_char && parser.next_if(|p| p.next_token_is(LevelTwoToken::CharDelimiter)) {
+                Token::CharLiteral
+            } else {
+                Token::Invalid(InvalidKind::UnclosedCharLiteral)
+            }
         }
-        Some(_) => Token::invalid_char_literal(),
-        None => Token::unclosed_char_literal(),
+        Some(_) => Token::Invalid(InvalidKind::InvalidCharLiteral),
+        None => Token::Invalid(InvalidKind::UnclosedCharLiteral),
+    }
+}
+
+// length of the longest escape after the backslash of a char literal, as in '\u{10FFFF}'
+const MAX_CHAR_ESCAPE_LEN: usize = 9;
+
+// a char literal with an escape is short and never spans lines, so a quote and backslash that are
+// not closed soon after, as in prose that ended up outside of a comment, are reported on their
+// own instead of swallowing the text up to the next quote
+fn is_stray_quote(parser: &VecParser<LevelTwoToken>) -> bool {
+    let mut upcoming = parser.upcoming();
+
+    // the token after the backslash is escaped, even if it is a quote
+    let mut len = match upcoming.next() {
+        Some(s) if matches!(s.token, LevelTwoToken::NewLine(_)) => return true,
+        Some(s) => s.text.len(),
+        None => return false,
+    };
+
+    for s in upcoming {
+        match s.token {
+            LevelTwoToken::CharDelimiter => return false,
+            LevelTwoToken::NewLine(_) => return true,
+            _ => len += s.text.len(),
+        }
+
+        if len > MAX_CHAR_ESCAPE_LEN {
+            return true;
+        }
+    }
+
+    // unclosed at the end of the file
+    false
+}
+
+fn lifetime_or_label(text: &str, span: Span, previous: &[Sequence<Token>]) -> Token {
+    let previous = previous
+        .iter()
+        .rev()
+        .find(|s| !matches!(s.token, Token::Whitespace { .. }));
+
+    if matches!(previous, Some(s) if s.text == "break" || s.text == "continue") {
+        return Token::LoopLabel;
+    }
+
+    let following = text[span.end..].trim_start_matches([' ', '\t']);
+    match following.strip_prefix(':') {
+        Some(labeled) if !labeled.starts_with(':') => {
+            let labeled = labeled.trim_start();
+            let keyword = labeled
+                .split(|c: char| c != '_' && !c.is_ascii_alphanumeric())
+                .next();
+
+            if labeled.starts_with('{') || matches!(keyword, Some("loop" | "while" | "for")) {
+                Token::LoopLabel
+            } else {
+                Token::Lifetime
+            }
+        }
+        _ => Token::Lifetime,
     }
 }
 
@@ -496,7 +1065,7 @@ fn parse_until_close_char_literal(parser: &mut VecParser<LevelTwoToken>) -> Toke
     });
 
     match parser.current_item {
-        None => Token::unclosed_char_literal(),
+        None => Token::Invalid(InvalidKind::UnclosedCharLiteral),
         _ => Token::CharLiteral,
     }
 }
@@ -506,7 +1075,7 @@ fn parse_single_line_comment(parser: &mut VecParser<LevelTwoToken>) -> Token {
     Token::SingleLineComment
 }
 
-fn parse_multi_line_comment(parser: &mut VecParser<LevelTwoToken>) -> Token {
+fn parse_multi_line_comment(parser: &mut VecParser<LevelTwoToken>, max_depth: usize) -> Token {
     let mut level: usize = 1;
 
     loop {
@@ -520,6 +1089,10 @@ fn parse_multi_line_comment(parser: &mut VecParser<LevelTwoToken>) -> Token {
         });
 
         match parser.current_token() {
+            Some(LevelTwoToken::BeginMultiLineComment) if level == max_depth => {
+                parse_until_end(parser);
+                return Token::Invalid(InvalidKind::CommentTooDeep { max: max_depth });
+            }
             Some(LevelTwoToken::BeginMultiLineComment) => level += 1,
             Some(LevelTwoToken::EndMultiLineComment) => {
                 level -= 1;
@@ -528,7 +1101,7 @@ fn parse_multi_line_comment(parser: &mut VecParser<LevelTwoToken>) -> Token {
                 }
             }
             _ => {
-                return Token::unclosed_multi_line_comment(level);
+                return Token::Invalid(InvalidKind::UnclosedMultiLineComment { levels: level });
             }
         };
     }
@@ -0,0 +1,145 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use std::path::{Path, PathBuf};
+
+use crate::{
+    arguments::BuildArgs,
+    messages::Message,
+    mod_files::{self, ModFile},
+    project::{self, Project},
+    terminal_helper::TerminalOutput,
+};
+
+pub enum Action {
+    Generate { input: PathBuf, output: PathBuf },
+    // the reason has already been reported as an error while planning
+    Skip { input: PathBuf },
+    WriteModFile(ModFile),
+}
+
+// Everything a build does, computed before any file is written, so that problems involving
+// several files are found first and the build can be previewed with --plan.
+pub struct Plan {
+    actions: Vec<Action>,
+}
+
+impl Plan {
+    pub fn new(
+        args: &BuildArgs,
+        project: &Project,
+        filenames: &[PathBuf],
+        output: &mut TerminalOutput,
+    ) -> Plan {
+        let diagnostics = project.check();
+        project::report(project, &diagnostics, output);
+        let colliding = project.colliding_files(&diagnostics);
+
+        let mut actions = Vec::new();
+        let mut generated = Vec::new();
+
+        for input in filenames {
+            let input = input.clone();
+
+            match args.output_file(&input) {
+                _ if colliding.contains(&input) => actions.push(Action::Skip { input }),
+                Some(file) => {
+                    generated.push(file.clone());
+                    actions.push(Action::Generate {
+                        input,
+                        output: file,
+                    });
+                }
+                None => {
+                    output.writeln_error(Message::OutputFileFailed);
+                    actions.push(Action::Skip { input });
+                }
+            }
+        }
+
+        if args.generate_mod_files() {
+            for root in args.output_roots() {
+                let under_root: Vec<PathBuf> = generated
+                    .iter()
+                    .filter(|f| f.starts_with(&root))
+                    .cloned()
+                    .collect();
+
+                let mod_files = mod_files::plan(&root, &under_root, output);
+                actions.extend(mod_files.into_iter().map(Action::WriteModFile));
+            }
+        }
+
+        Plan { actions }
+    }
+
+    pub fn inputs(&self) -> Vec<PathBuf> {
+        self.actions
+            .iter()
+            .filter_map(|a| match a {
+                Action::Generate { input, .. } => Some(input.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn output_of(&self, file: &Path) -> Option<&Path> {
+        self.actions.iter().find_map(|a| match a {
+            Action::Generate { input, output } if input == file => Some(output.as_path()),
+            _ => None,
+        })
+    }
+
+    pub fn mod_files(&self) -> impl Iterator<Item = &ModFile> {
+        self.actions.iter().filter_map(|a| match a {
+            Action::WriteModFile(mod_file) => Some(mod_file),
+            _ => None,
+        })
+    }
+
+    pub fn print(&self, output: &mut TerminalOutput) {
+        let mut skipped = 0;
+
+        for action in &self.actions {
+            let text = match action {
+                Action::Generate { input, output } => {
+                    Message::PlanGenerate(input.display().to_string(), output.display().to_string())
+                }
+                Action::Skip { input } => {
+                    skipped += 1;
+                    Message::PlanSkip(input.display().to_string())
+                }
+                Action::WriteModFile(mod_file) => {
+                    Message::PlanWriteModFile(mod_file.path.display().to_string())
+                }
+            };
+
+            output.writeln_result(text);
+        }
+
+        output.writeln_success(Message::PlanSummary(
+            self.inputs().len(),
+            skipped,
+            self.mod_files().count(),
+        ));
+    }
+}
@@ -0,0 +1,56 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use std::{fs, path::PathBuf};
+
+use crate::{messages::Message, terminal_helper::TerminalOutput};
+
+// number of files listed as the largest ones
+const LARGEST_FILES: usize = 5;
+
+// Summary of the files about to be processed, so that an unexpectedly large run can be spotted
+// (and interrupted) before it starts.
+pub fn report(filenames: &[PathBuf], output: &mut TerminalOutput) {
+    // the size of every file is only obtained when the summary is printed
+    if !output.is_verbose() {
+        return;
+    }
+
+    let mut sizes: Vec<(u64, &PathBuf)> = filenames
+        .iter()
+        .filter_map(|f| Some((fs::metadata(f).ok()?.len(), f)))
+        .collect();
+
+    let total = sizes.iter().map(|(size, _)| size).sum();
+    output.writeln_verbose(Message::FilesFound(filenames.len(), total));
+
+    if sizes.is_empty() {
+        return;
+    }
+
+    sizes.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(b.1)));
+
+    output.writeln_verbose(Message::LargestFiles);
+    for (size, file) in sizes.iter().take(LARGEST_FILES) {
+        output.writeln_verbose(Message::FileSize(file.display().to_string(), *size));
+    }
+}
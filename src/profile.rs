@@ -0,0 +1,208 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashSet},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::{
+    arguments::StatsFormat,
+    interner::{self, Interner},
+    messages::Message,
+    parser::{Sequence, Token},
+    terminal_helper::TerminalOutput,
+};
+
+#[derive(Clone, Copy, Default)]
+struct Metrics {
+    files: usize,
+    lines: usize,
+    tokens: usize,
+    comment_lines: usize,
+}
+
+impl Metrics {
+    fn of(tokens: &[Sequence<Token>]) -> Metrics {
+        let mut line_number = 1;
+        let mut comment_lines = HashSet::new();
+
+        for t in tokens {
+            if let Some(number) = t.token.line_after() {
+                line_number = number;
+            }
+            if matches!(t.token, Token::SingleLineComment | Token::MultiLineComment) {
+                comment_lines.extend(line_number..=line_number + t.text.matches('\n').count());
+            }
+        }
+
+        let ends_line = tokens.last().is_none_or(|t| t.token.line_after().is_some());
+        Metrics {
+            files: 1,
+            lines: if ends_line {
+                line_number - 1
+            } else {
+                line_number
+            },
+            tokens: tokens.len(),
+            comment_lines: comment_lines.len(),
+        }
+    }
+
+    fn add(&mut self, other: Metrics) {
+        self.files += other.files;
+        self.lines += other.lines;
+        self.tokens += other.tokens;
+        self.comment_lines += other.comment_lines;
+    }
+
+    fn comment_ratio(&self) -> f64 {
+        match self.lines {
+            0 => 0.0,
+            lines => self.comment_lines as f64 / lines as f64,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Profile {
+    format: StatsFormat,
+    files: usize,
+    tokens: usize,
+    parse_time: Duration,
+    uninterned_bytes: usize,
+    interner: Interner,
+    // file being processed and the metrics of each file, which are only kept for the CSV format
+    current: Option<PathBuf>,
+    breakdown: Vec<(PathBuf, Metrics)>,
+}
+
+thread_local! {
+    static PROFILE: RefCell<Option<Profile>> = const { RefCell::new(None) };
+}
+
+pub fn enable(format: StatsFormat) {
+    PROFILE.with(|p| {
+        *p.borrow_mut() = Some(Profile {
+            format,
+            ..Profile::default()
+        })
+    });
+}
+
+pub fn begin_file(file: &Path) {
+    PROFILE.with(|p| {
+        if let Some(profile) = p.borrow_mut().as_mut() {
+            profile.current = Some(file.to_path_buf());
+        }
+    });
+}
+
+pub fn record(tokens: &[Sequence<Token>], duration: Duration) {
+    PROFILE.with(|p| {
+        if let Some(profile) = p.borrow_mut().as_mut() {
+            profile.files += 1;
+            profile.tokens += tokens.len();
+            profile.parse_time += duration;
+            profile.uninterned_bytes += interner::uninterned_bytes(tokens);
+            interner::to_owned(tokens, &mut profile.interner);
+
+            // a file parsed again (as when checking that the output is reproducible) is counted once
+            if let (StatsFormat::Csv, Some(file)) = (profile.format, profile.current.take()) {
+                profile.breakdown.push((file, Metrics::of(tokens)));
+            }
+        }
+    });
+}
+
+fn csv_field(text: &str) -> String {
+    match text.contains([',', '"', '\n', '\r']) {
+        true => format!("\"{}\"", text.replace('"', "\"\"")),
+        false => text.to_string(),
+    }
+}
+
+fn csv_row(kind: &str, path: &Path, metrics: &Metrics) -> String {
+    format!(
+        "{},{},{},{},{},{},{:.3}",
+        kind,
+        csv_field(&path.to_string_lossy()),
+        metrics.files,
+        metrics.lines,
+        metrics.tokens,
+        metrics.comment_lines,
+        metrics.comment_ratio(),
+    )
+}
+
+// one row per file, then the totals of each directory (including the files of its
+// subdirectories) and of all the files
+fn report_csv(profile: &Profile, output: &mut TerminalOutput) {
+    let mut directories = BTreeMap::<&Path, Metrics>::new();
+    let mut total = Metrics::default();
+
+    // the scanned files have absolute paths, which are shown relative to the working directory
+    let working_dir = std::env::current_dir().unwrap_or_default();
+
+    output.writeln_result("kind,path,files,lines,tokens,comment_lines,comment_ratio");
+    for (file, metrics) in &profile.breakdown {
+        let file = file.strip_prefix(&working_dir).unwrap_or(file);
+        output.writeln_result(csv_row("file", file, metrics));
+
+        for directory in file.ancestors().skip(1) {
+            directories.entry(directory).or_default().add(*metrics);
+        }
+        total.add(*metrics);
+    }
+
+    for (directory, metrics) in directories
+        .iter()
+        .filter(|(d, _)| !d.as_os_str().is_empty())
+    {
+        output.writeln_result(csv_row("directory", directory, metrics));
+    }
+    output.writeln_result(csv_row("total", Path::new(""), &total));
+}
+
+pub fn report(output: &mut TerminalOutput) {
+    PROFILE.with(|p| {
+        if let Some(profile) = p.borrow().as_ref() {
+            if profile.format == StatsFormat::Csv {
+                report_csv(profile, output);
+                return;
+            }
+
+            output.writeln_result(Message::ProfileParsing(
+                profile.files,
+                profile.tokens,
+                profile.parse_time,
+            ));
+            output.writeln_result(Message::ProfileMemory(
+                profile.uninterned_bytes,
+                interner::interned_bytes(profile.tokens, &profile.interner),
+                profile.interner.len(),
+            ));
+        }
+    });
+}
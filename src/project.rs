@@ -0,0 +1,159 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    arguments::BuildArgs, error_codes::ErrorCode, messages::Message,
+    terminal_helper::TerminalOutput,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FileId(usize);
+
+pub struct SourceFile {
+    pub id: FileId,
+    pub path: PathBuf,
+    // None when the file name has no extension that can be replaced by .rs
+    pub output: Option<PathBuf>,
+}
+
+// diagnostic that involves several files of the project, such as two files generating the same
+// output file
+pub struct ProjectDiagnostic {
+    pub code: ErrorCode,
+    pub message: Message,
+    pub files: Vec<FileId>,
+}
+
+// The XR files of a build and the files generated from them. Unlike the diagnostics of each
+// file, which only need its tokens, the diagnostics of the project relate files to each other.
+pub struct Project {
+    files: Vec<SourceFile>,
+}
+
+impl Project {
+    pub fn new(args: &BuildArgs, filenames: &[PathBuf]) -> Project {
+        let files = filenames
+            .iter()
+            .enumerate()
+            .map(|(i, path)| SourceFile {
+                id: FileId(i),
+                path: path.clone(),
+                output: args.output_file(path),
+            })
+            .collect();
+
+        Project { files }
+    }
+
+    pub fn file(&self, id: FileId) -> &SourceFile {
+        &self.files[id.0]
+    }
+
+    pub fn check(&self) -> Vec<ProjectDiagnostic> {
+        let mut diagnostics = Vec::new();
+
+        for (output, files) in self.group_by(|f| f.output.clone()) {
+            if files.len() > 1 {
+                diagnostics.push(ProjectDiagnostic {
+                    code: ErrorCode::E0012,
+                    message: Message::OutputCollision(output.display().to_string()),
+                    files,
+                });
+            }
+        }
+
+        for ((dir, name), files) in self.group_by(|f| f.output.as_deref().and_then(module_of)) {
+            // files with the same output are already reported as a collision
+            let outputs: BTreeSet<_> = files.iter().map(|id| &self.file(*id).output).collect();
+
+            if outputs.len() > 1 {
+                diagnostics.push(ProjectDiagnostic {
+                    code: ErrorCode::E0011,
+                    message: Message::DuplicateModule(name, dir.display().to_string()),
+                    files,
+                });
+            }
+        }
+
+        diagnostics
+    }
+
+    // files that are not generated, as their output would be overwritten by another file
+    pub fn colliding_files(&self, diagnostics: &[ProjectDiagnostic]) -> BTreeSet<PathBuf> {
+        diagnostics
+            .iter()
+            .filter(|d| d.code == ErrorCode::E0012)
+            .flat_map(|d| &d.files)
+            .map(|id| self.file(*id).path.clone())
+            .collect()
+    }
+
+    fn group_by<K: Ord, F: Fn(&SourceFile) -> Option<K>>(
+        &self,
+        key: F,
+    ) -> BTreeMap<K, Vec<FileId>> {
+        let mut groups = BTreeMap::<K, Vec<FileId>>::new();
+
+        for file in &self.files {
+            if let Some(key) = key(file) {
+                groups.entry(key).or_default().push(file.id);
+            }
+        }
+
+        groups
+    }
+
+    fn file_list(&self, files: &[FileId]) -> String {
+        files
+            .iter()
+            .map(|id| format!("'{}'", self.file(*id).path.display()))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+// directory in which a generated file declares a module, and the name of the module
+fn module_of(output: &Path) -> Option<(PathBuf, String)> {
+    let stem = output.file_stem()?.to_str()?;
+    let dir = output.parent()?;
+
+    match stem {
+        "lib" | "main" => None,
+        "mod" => Some((
+            dir.parent()?.to_path_buf(),
+            dir.file_name()?.to_str()?.to_string(),
+        )),
+        _ => Some((dir.to_path_buf(), stem.to_string())),
+    }
+}
+
+pub fn report(project: &Project, diagnostics: &[ProjectDiagnostic], output: &mut TerminalOutput) {
+    for d in diagnostics {
+        let text = Message::FilesInvolved(d.message.to_string(), project.file_list(&d.files));
+        output.diagnostic(d.code, None, text);
+    }
+}
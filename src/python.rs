@@ -0,0 +1,57 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use pyo3::prelude::*;
+
+use crate::{export, parser};
+
+// kind, text, line and column
+type TokenTuple = (&'static str, String, usize, usize);
+
+// code, severity, message, line and column
+type DiagnosticTuple = (String, &'static str, String, usize, usize);
+
+/// Parses `text`, returning a list of `(kind, text, line, column)` tuples for its tokens and a
+/// list of `(code, severity, message, line, column)` tuples for its diagnostics.
+#[pyfunction]
+fn parse(text: &str) -> (Vec<TokenTuple>, Vec<DiagnosticTuple>) {
+    let exported = export::export(&parser::parse(text));
+
+    let tokens = exported
+        .tokens
+        .into_iter()
+        .map(|t| (t.kind, t.text.to_string(), t.line, t.column))
+        .collect();
+
+    let diagnostics = exported
+        .diagnostics
+        .into_iter()
+        .map(|d| (d.code, d.severity, d.message, d.line, d.column))
+        .collect();
+
+    (tokens, diagnostics)
+}
+
+#[pymodule]
+fn xr(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_function(wrap_pyfunction!(parse, module)?)
+}
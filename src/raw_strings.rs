@@ -0,0 +1,74 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use xr::literals::{self, StringLiteral};
+
+use crate::arguments::StringStyle;
+
+// Rewrites a string literal in the given style, or returns None when it is already in that
+// style or cannot be rewritten without changing its value.
+pub fn convert(literal: &str, style: StringStyle) -> Option<String> {
+    let literal = StringLiteral::parse(literal)?;
+    let kind = literal.kind.prefix();
+
+    match (style, literal.is_raw()) {
+        (StringStyle::Raw, false) => to_raw(kind, literal.contents),
+        (StringStyle::Plain, true) => Some(to_plain(kind, literal.contents)),
+        _ => None,
+    }
+}
+
+// Only strings with escaped backslashes are rewritten, and only when the rest of their escapes
+// are quotes, as escapes such as \n or \u{..} have no equivalent in raw strings.
+fn to_raw(kind: &str, contents: &str) -> Option<String> {
+    let mut unescaped = String::with_capacity(contents.len());
+    let mut has_backslash = false;
+    let mut chars = contents.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+
+        match chars.next()? {
+            '\\' => {
+                has_backslash = true;
+                unescaped.push('\\');
+            }
+            c @ ('"' | '\'') => unescaped.push(c),
+            _ => return None,
+        }
+    }
+
+    if !has_backslash {
+        return None;
+    }
+
+    let hashes = "#".repeat(literals::raw_hashes(&unescaped));
+    Some(format!("{kind}r{hashes}\"{unescaped}\"{hashes}"))
+}
+
+fn to_plain(kind: &str, contents: &str) -> String {
+    let escaped = contents.replace('\\', "\\\\").replace('"', "\\\"");
+    format!("{kind}\"{escaped}\"")
+}
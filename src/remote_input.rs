@@ -0,0 +1,141 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process,
+    sync::OnceLock,
+};
+#[cfg(feature = "remote")]
+use std::{io::Read, time::Duration};
+
+use crate::{cache::sha256, messages::Message};
+
+#[cfg(feature = "remote")]
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+// a URL may be pinned to the checksum of its contents, as in
+// https://example.com/parser.xr#sha256=<hex digest>
+const CHECKSUM_FRAGMENT: &str = "#sha256=";
+
+pub fn is_url(path: &Path) -> bool {
+    path.to_str()
+        .is_some_and(|p| p.starts_with("https://") || p.starts_with("http://"))
+}
+
+static CACHE_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+pub fn set_cache_dir(dir: Option<&Path>) {
+    let dir = dir.map_or_else(|| env::temp_dir().join("xr-remote"), Path::to_path_buf);
+    CACHE_DIR.get_or_init(|| dir);
+}
+
+fn split_checksum(url: &str) -> (&str, Option<String>) {
+    match url.split_once(CHECKSUM_FRAGMENT) {
+        Some((url, checksum)) => (url, Some(checksum.to_ascii_lowercase())),
+        None => (url, None),
+    }
+}
+
+// name of the file at the end of the URL, without its query or fragment
+pub fn file_name(url: &Path) -> Option<PathBuf> {
+    let url = url.to_str()?;
+    let path = url.split(['?', '#']).next()?;
+    let name = path.rsplit('/').next()?;
+
+    (!name.is_empty() && !name.contains(':')).then(|| PathBuf::from(name))
+}
+
+// Contents of a remote XR file. Pinned files are kept in the cache directory by their checksum,
+// so that they are only downloaded once; the others are downloaded every time, as they may
+// change.
+pub fn fetch(url: &Path, max_size: u64) -> Result<Vec<u8>, Message> {
+    let name = url.display().to_string();
+    let (address, checksum) = split_checksum(&name);
+
+    let cache_dir = CACHE_DIR.get_or_init(|| env::temp_dir().join("xr-remote"));
+    // only a well-formed digest names a file in the cache
+    let cached = checksum
+        .as_ref()
+        .filter(|c| c.len() == 64 && c.chars().all(|c| c.is_ascii_hexdigit()))
+        .map(|c| cache_dir.join(c));
+    if let Some(bytes) = cached.as_ref().and_then(|path| fs::read(path).ok()) {
+        if checksum.as_deref() == Some(sha256(&bytes).as_str()) {
+            return Ok(bytes);
+        }
+    }
+
+    let bytes = download(address, max_size)
+        .map_err(|e| Message::RemoteFileUnreadable(address.to_string(), e))?;
+
+    if let Some(expected) = &checksum {
+        let actual = sha256(&bytes);
+        if *expected != actual {
+            return Err(Message::ChecksumMismatch(
+                address.to_string(),
+                expected.clone(),
+                actual,
+            ));
+        }
+    }
+
+    if let Some(path) = &cached {
+        store(cache_dir, path, &bytes);
+    }
+
+    Ok(bytes)
+}
+
+#[cfg(feature = "remote")]
+fn download(url: &str, max_size: u64) -> Result<Vec<u8>, String> {
+    let agent = ureq::AgentBuilder::new().timeout(TIMEOUT).build();
+    let response = agent.get(url).call().map_err(|e| e.to_string())?;
+
+    // one more byte than allowed is read, so that larger files are reported as too large
+    let mut bytes = Vec::new();
+    response
+        .into_reader()
+        .take(max_size + 1)
+        .read_to_end(&mut bytes)
+        .map_err(|e| e.to_string())?;
+
+    Ok(bytes)
+}
+
+#[cfg(not(feature = "remote"))]
+fn download(_url: &str, _max_size: u64) -> Result<Vec<u8>, String> {
+    Err(Message::RemoteUnsupported.to_string())
+}
+
+// written under a temporary name and then renamed, as the cache may be shared by several runs
+fn store(cache_dir: &Path, path: &Path, bytes: &[u8]) {
+    let temporary = path.with_extension(process::id().to_string());
+
+    let written = fs::create_dir_all(cache_dir)
+        .and_then(|_| fs::write(&temporary, bytes))
+        .and_then(|_| fs::rename(&temporary, path));
+
+    if written.is_err() {
+        fs::remove_file(&temporary).ok();
+    }
+}
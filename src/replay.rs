@@ -0,0 +1,75 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use std::fs;
+
+use walkdir::WalkDir;
+
+use crate::{
+    arguments::ReplayArgs,
+    invariants,
+    messages::Message,
+    parser::{self, ParseError},
+    terminal_helper::TerminalOutput,
+};
+
+pub fn run(args: &ReplayArgs, output: &mut TerminalOutput) {
+    let mut replayed = 0;
+    let mut skipped = 0;
+
+    let entries = WalkDir::new(&args.dir)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file());
+
+    for entry in entries {
+        let filename = entry.path().display().to_string();
+
+        let Ok(bytes) = fs::read(entry.path()) else {
+            output.writeln_error(Message::FileUnreadable(filename));
+            continue;
+        };
+
+        output.writeln_verbose(Message::ProcessingFile(filename.clone()));
+
+        match parser::parse_bytes(&bytes) {
+            Ok(tokens) => {
+                replayed += 1;
+
+                if let Err(e) = invariants::check(&bytes, &tokens) {
+                    output.writeln_error(Message::InvariantViolated(filename, e.to_string()));
+                }
+            }
+            Err(ParseError::InvalidUtf8(offset)) => {
+                skipped += 1;
+                output.writeln_verbose(Message::NotUtf8(filename, offset));
+            }
+            Err(ParseError::Panic(e)) => {
+                replayed += 1;
+                output.writeln_error(Message::ParserPanicked(filename, e));
+            }
+        }
+    }
+
+    output.writeln_success(Message::InputsReplayed(replayed, skipped));
+}
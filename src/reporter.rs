@@ -0,0 +1,156 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use serde::Serialize;
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::{error_codes::ErrorCode, lint_levels, messages::Message};
+
+// a problem found in a file, or in the project when no file is being processed
+pub struct Diagnostic<'a> {
+    pub file: Option<&'a Path>,
+    pub code: ErrorCode,
+    pub line: Option<usize>,
+    pub message: &'a str,
+}
+
+// receives the events of a run, so that several reports can be produced at once
+pub trait Reporter {
+    fn file_started(&mut self, _file: &Path) {}
+
+    fn diagnostic(&mut self, diagnostic: &Diagnostic);
+
+    fn file_finished(&mut self, _file: &Path) {}
+
+    fn run_finished(&mut self, _errors: usize, _warnings: usize) -> Result<(), Message> {
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+struct JsonDiagnostic {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    line: Option<usize>,
+    code: String,
+    severity: &'static str,
+    message: String,
+}
+
+#[derive(Serialize)]
+struct JsonReport<'a> {
+    files: &'a [String],
+    errors: usize,
+    warnings: usize,
+    diagnostics: &'a [JsonDiagnostic],
+}
+
+// writes the files processed and their diagnostics to a JSON file at the end of the run
+pub struct JsonReporter {
+    path: PathBuf,
+    files: Vec<String>,
+    diagnostics: Vec<JsonDiagnostic>,
+}
+
+impl JsonReporter {
+    pub fn new(path: &Path) -> JsonReporter {
+        JsonReporter {
+            path: path.to_path_buf(),
+            files: Vec::new(),
+            diagnostics: Vec::new(),
+        }
+    }
+}
+
+impl Reporter for JsonReporter {
+    fn file_started(&mut self, file: &Path) {
+        self.files.push(file.display().to_string());
+    }
+
+    fn diagnostic(&mut self, diagnostic: &Diagnostic) {
+        self.diagnostics.push(JsonDiagnostic {
+            file: diagnostic.file.map(|f| f.display().to_string()),
+            line: diagnostic.line,
+            code: diagnostic.code.to_string(),
+            severity: lint_levels::severity(diagnostic.code).name(),
+            message: diagnostic.message.to_string(),
+        });
+    }
+
+    fn run_finished(&mut self, errors: usize, warnings: usize) -> Result<(), Message> {
+        let report = JsonReport {
+            files: &self.files,
+            errors,
+            warnings,
+            diagnostics: &self.diagnostics,
+        };
+
+        let filename = self.path.display().to_string();
+        let json = serde_json::to_string_pretty(&report)
+            .map_err(|_| Message::FileWriteFailed(filename.clone()))?;
+        fs::write(&self.path, json + "\n").map_err(|_| Message::FileWriteFailed(filename))
+    }
+}
+
+// prints the diagnostics as workflow commands, which GitHub Actions shows as annotations
+pub struct GitHubReporter;
+
+impl Reporter for GitHubReporter {
+    fn diagnostic(&mut self, diagnostic: &Diagnostic) {
+        let mut properties = Vec::new();
+        if let Some(file) = diagnostic.file {
+            properties.push(format!(
+                "file={}",
+                escape_property(&file.display().to_string())
+            ));
+        }
+        if let Some(line) = diagnostic.line {
+            properties.push(format!("line={line}"));
+        }
+        properties.push(format!("title={}", diagnostic.code));
+
+        let mut stdout = io::stdout().lock();
+        writeln!(
+            stdout,
+            "::{} {}::{}",
+            lint_levels::severity(diagnostic.code).name(),
+            properties.join(","),
+            escape_data(diagnostic.message)
+        )
+        .ok();
+    }
+}
+
+fn escape_data(text: &str) -> String {
+    text.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn escape_property(text: &str) -> String {
+    escape_data(text).replace(':', "%3A").replace(',', "%2C")
+}
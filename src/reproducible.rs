@@ -0,0 +1,126 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use std::{env, fs, path::PathBuf};
+
+use crate::messages::Message;
+
+// Content of a generated file that depends on when, where or on which machine it was generated.
+pub struct Finding {
+    pub line: usize,
+    pub description: Message,
+}
+
+// Absolute paths of the directories that depend on the machine and the user running xr.
+fn machine_paths() -> Vec<String> {
+    let mut paths: Vec<PathBuf> = Vec::new();
+
+    if let Ok(dir) = env::current_dir() {
+        paths.push(dir.canonicalize().unwrap_or(dir.clone()));
+        paths.push(dir);
+    }
+
+    if let Some(home) = env::var_os("HOME").or_else(|| env::var_os("USERPROFILE")) {
+        paths.push(PathBuf::from(home));
+    }
+
+    paths.push(env::temp_dir());
+
+    let mut paths: Vec<String> = paths
+        .iter()
+        .filter(|p| p.components().count() > 1)
+        .map(|p| {
+            p.display()
+                .to_string()
+                .trim_end_matches(['/', '\\'])
+                .to_string()
+        })
+        .collect();
+
+    paths.sort();
+    paths.dedup();
+    paths
+}
+
+fn machine_name() -> Option<String> {
+    env::var("HOSTNAME")
+        .or_else(|_| env::var("COMPUTERNAME"))
+        .ok()
+        .or_else(|| fs::read_to_string("/etc/hostname").ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-'
+}
+
+fn contains_word(line: &str, word: &str) -> bool {
+    line.match_indices(word).any(|(i, _)| {
+        let before = line[..i].chars().next_back();
+        let after = line[i + word.len()..].chars().next();
+        !before.is_some_and(is_word_char) && !after.is_some_and(is_word_char)
+    })
+}
+
+// date and time such as 2024-01-31 12:00 or 2024-01-31T12:00
+fn contains_timestamp(line: &str) -> bool {
+    const PATTERN: &[u8] = b"0000-00-00 00:00";
+
+    line.as_bytes().windows(PATTERN.len()).any(|window| {
+        window.iter().zip(PATTERN).all(|(&b, &p)| match p {
+            b'0' => b.is_ascii_digit(),
+            b' ' => b == b' ' || b == b'T',
+            _ => b == p,
+        })
+    })
+}
+
+pub fn audit(text: &str) -> Vec<Finding> {
+    let paths = machine_paths();
+    let name = machine_name();
+
+    let mut findings = Vec::new();
+
+    for (i, line) in text.lines().enumerate() {
+        let mut report = |description| {
+            findings.push(Finding {
+                line: i + 1,
+                description,
+            })
+        };
+
+        if contains_timestamp(line) {
+            report(Message::ContainsTimestamp);
+        }
+
+        if let Some(path) = paths.iter().find(|p| line.contains(p.as_str())) {
+            report(Message::ContainsAbsolutePath(path.clone()));
+        }
+
+        if let Some(name) = name.as_ref().filter(|name| contains_word(line, name)) {
+            report(Message::ContainsMachineName(name.clone()));
+        }
+    }
+
+    findings
+}
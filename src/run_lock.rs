@@ -0,0 +1,79 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use std::{
+    fs::{self, File, OpenOptions, TryLockError},
+    io::Write,
+    path::Path,
+    process,
+};
+
+use crate::{messages::Message, terminal_helper::TerminalOutput};
+
+const LOCK_FILE_NAME: &str = ".xr.lock";
+
+// Advisory lock on the directory of the generated files, held while they are written, so that
+// two runs (such as xr watch and a manual build) do not write the same files at the same time.
+// The lock is released when the file is closed, even if the process dies.
+pub struct RunLock {
+    _file: File,
+}
+
+// process holding the lock, which it writes into the lock file
+fn holder(path: &Path) -> String {
+    match fs::read_to_string(path) {
+        Ok(pid) if !pid.trim().is_empty() => pid.trim().to_string(),
+        _ => String::from("?"),
+    }
+}
+
+pub fn acquire(dir: &Path, wait: bool, output: &mut TerminalOutput) -> Result<RunLock, Message> {
+    let path = dir.join(LOCK_FILE_NAME);
+    let failed = |e: std::io::Error| Message::LockFailed(path.display().to_string(), e.to_string());
+
+    fs::create_dir_all(dir).map_err(failed)?;
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(&path)
+        .map_err(failed)?;
+
+    match file.try_lock() {
+        Ok(_) => {}
+        Err(TryLockError::WouldBlock) if wait => {
+            output.writeln_info(Message::WaitingForLock(holder(&path)));
+            file.lock().map_err(failed)?;
+        }
+        Err(TryLockError::WouldBlock) => {
+            return Err(Message::AnotherInstanceRunning(holder(&path)))
+        }
+        Err(TryLockError::Error(e)) => return Err(failed(e)),
+    }
+
+    file.set_len(0)
+        .and_then(|_| write!(file, "{}", process::id()))
+        .map_err(failed)?;
+
+    Ok(RunLock { _file: file })
+}
@@ -0,0 +1,64 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use std::{
+    io::Write,
+    path::Path,
+    process::{Command, Stdio},
+    thread,
+};
+
+use crate::messages::Message;
+
+// Formats the text of a generated file with rustfmt, which reads it from its standard input
+pub fn format(text: &str, program: &Path) -> Result<String, Message> {
+    let mut child = Command::new(program)
+        .args(["--emit", "stdout"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| Message::ProgramNotRun(program.display().to_string(), e.to_string()))?;
+
+    // written from another thread, so that rustfmt never blocks writing a large output
+    // while xr is still writing the input
+    let mut stdin = child.stdin.take();
+    let result = thread::scope(|scope| {
+        scope.spawn(move || {
+            if let Some(stdin) = &mut stdin {
+                stdin.write_all(text.as_bytes()).ok();
+            }
+        });
+        child.wait_with_output()
+    });
+
+    let result =
+        result.map_err(|e| Message::ProgramNotRun(program.display().to_string(), e.to_string()))?;
+
+    if result.status.success() {
+        Ok(String::from_utf8_lossy(&result.stdout).into_owned())
+    } else {
+        let stderr = String::from_utf8_lossy(&result.stderr);
+        let reason = stderr.lines().next().unwrap_or_default().to_string();
+        Err(Message::RustfmtFailed(reason))
+    }
+}
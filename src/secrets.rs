@@ -0,0 +1,139 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+use crate::{
+    arguments::InputArgs,
+    diagnostics::{self, Diagnostic},
+    error_codes::ErrorCode,
+    messages::Message,
+    parser::{Sequence, Span, Token},
+    suppressions,
+    terminal_helper::TerminalOutput,
+};
+
+// kinds of secrets with a recognizable format, so that they are found with few false positives
+const PATTERNS: [(&str, &str); 10] = [
+    ("AWS access key", r"\b(?:AKIA|ASIA)[0-9A-Z]{16}\b"),
+    ("GitHub token", r"\bgh[pousr]_[A-Za-z0-9]{36,}\b"),
+    ("GitHub token", r"\bgithub_pat_[A-Za-z0-9_]{22,}\b"),
+    ("GitLab token", r"\bglpat-[A-Za-z0-9_-]{20,}"),
+    ("Slack token", r"\bxox[abposr]-[A-Za-z0-9-]{10,}"),
+    ("Stripe key", r"\b[rs]k_live_[A-Za-z0-9]{20,}\b"),
+    ("Google API key", r"\bAIza[0-9A-Za-z_-]{35}"),
+    ("private key", r"-----BEGIN [A-Z ]*PRIVATE KEY-----"),
+    (
+        "JSON web token",
+        r"\beyJ[A-Za-z0-9_-]{10,}\.eyJ[A-Za-z0-9_-]{10,}\.[A-Za-z0-9_-]{10,}",
+    ),
+    (
+        "password or key assignment",
+        r#"(?i)\b(?:api[_-]?key|secret|token|passw(?:or)?d)\s*[:=]\s*['"]?[A-Za-z0-9/+_.=-]{16,}"#,
+    ),
+];
+
+fn patterns() -> &'static [(&'static str, Regex)] {
+    static COMPILED: OnceLock<Vec<(&str, Regex)>> = OnceLock::new();
+
+    COMPILED.get_or_init(|| {
+        PATTERNS
+            .iter()
+            .map(|(name, pattern)| (*name, Regex::new(pattern).unwrap()))
+            .collect()
+    })
+}
+
+// secrets are only searched for in literals and comments, as identifiers with long runs of
+// letters and digits would match some of the patterns
+pub fn scan<'a>(tokens: &[Sequence<'a, Token>]) -> Vec<Diagnostic<'a>> {
+    let mut diagnostics = Vec::new();
+    let mut line_number = 1;
+
+    for t in tokens {
+        if let Some(number) = t.token.line_after() {
+            line_number = number;
+        }
+
+        let in_string = match t.token {
+            Token::StrLiteral => true,
+            Token::SingleLineComment | Token::MultiLineComment => false,
+            _ => continue,
+        };
+
+        let first = diagnostics.len();
+
+        for (name, regex) in patterns() {
+            for m in regex.find_iter(t.text) {
+                let span = Span {
+                    start: t.span.start + m.start(),
+                    end: t.span.start + m.end(),
+                };
+
+                // a secret may match several patterns
+                let overlapping = diagnostics[first..]
+                    .iter()
+                    .any(|d: &Diagnostic| d.span.start < span.end && span.start < d.span.end);
+                if overlapping {
+                    continue;
+                }
+
+                let message = match in_string {
+                    true => Message::SecretInString(name.to_string()),
+                    false => Message::SecretInComment(name.to_string()),
+                };
+
+                diagnostics.push(Diagnostic {
+                    code: ErrorCode::E0014,
+                    message: message.to_string(),
+                    line: line_number + t.text[..m.start()].matches('\n').count(),
+                    span,
+                    text: m.as_str(),
+                });
+            }
+        }
+    }
+
+    diagnostics.sort_by_key(|d| d.span.start);
+    diagnostics
+}
+
+pub fn run(args: &InputArgs, output: &mut TerminalOutput) {
+    let filenames = args.files_to_process(output);
+    let mut found = 0;
+
+    crate::for_each_file(&filenames, output, |_, contents, output| {
+        let result = crate::parse_file(&contents, output);
+        let mut secrets = scan(&result);
+
+        // known false positives are silenced with xr-ignore-next-line E0014
+        let mut suppressions = suppressions::find(&result);
+        secrets.retain(|d| !suppressions.iter_mut().any(|s| s.suppress(d)));
+
+        found += secrets.len();
+        diagnostics::report(&secrets, output);
+    });
+
+    output.writeln_info(Message::SecretsFound(found));
+}
@@ -0,0 +1,99 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use std::{
+    cell::{Cell, RefCell},
+    io,
+    path::Path,
+};
+
+use xr::output_sink::{FileSystem, OutputSink, TarArchive};
+
+use crate::{backup, manifest, messages::Message, terminal_helper::TerminalOutput};
+
+// Destination of the generated files of the run, which is the filesystem unless they are
+// captured in an archive.
+thread_local! {
+    static SINK: RefCell<Box<dyn OutputSink>> = RefCell::new(Box::new(Files));
+    static HELD_BACK: Cell<bool> = const { Cell::new(false) };
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Written,
+    // kept by the sink instead of being written, such as a diff to review
+    HeldBack,
+}
+
+// Writes the files to the filesystem, after saving a backup of the files they replace when
+// backups are enabled.
+pub struct Files;
+
+impl OutputSink for Files {
+    fn write(&mut self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        backup::save(path, contents)?;
+        FileSystem.write(path, contents)
+    }
+}
+
+pub fn open_archive(path: &Path, reproducible: bool) -> Result<(), Message> {
+    let mut archive = TarArchive::create(path)
+        .map_err(|e| Message::ArchiveUnwritable(path.display().to_string(), e.to_string()))?;
+
+    // the time of the generation would make every archive different
+    if reproducible {
+        archive = archive.with_mtime(0);
+    }
+
+    replace(Box::new(archive));
+    Ok(())
+}
+
+pub fn replace(sink: Box<dyn OutputSink>) {
+    SINK.with(|s| *s.borrow_mut() = sink);
+}
+
+// called by a sink that does not write the file given to it
+pub fn hold_back() {
+    HELD_BACK.set(true);
+}
+
+pub fn write(path: &Path, contents: &[u8]) -> io::Result<Outcome> {
+    HELD_BACK.set(false);
+    SINK.with(|s| s.borrow_mut().write(path, contents))?;
+
+    if HELD_BACK.get() {
+        return Ok(Outcome::HeldBack);
+    }
+    manifest::record(path, contents);
+    Ok(Outcome::Written)
+}
+
+pub fn finish(archive: &Path, output: &mut TerminalOutput) {
+    match SINK.with(|s| s.borrow_mut().finish()) {
+        Ok(_) => output.writeln_info(Message::ArchiveWritten(archive.display().to_string())),
+        Err(e) => output.writeln_error(Message::ArchiveUnwritable(
+            archive.display().to_string(),
+            e.to_string(),
+        )),
+    }
+}
@@ -0,0 +1,111 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use walkdir::WalkDir;
+
+use crate::{
+    arguments::TestArgs, diagnostics, messages::Message, terminal_helper::TerminalOutput,
+    token_dump,
+};
+
+const FIXTURE_EXTENSION: &str = "xr";
+const SNAPSHOT_EXTENSION: &str = "expected";
+
+// Diagnostics are identified only by their code and line, as their messages depend on the
+// language of the messages.
+fn snapshot(contents: &str, output: &mut TerminalOutput) -> String {
+    let tokens = crate::parse_file(contents, output);
+
+    let mut lines = token_dump::dump(&tokens);
+    lines.push("--- diagnostics".to_string());
+
+    for d in diagnostics::collect(&tokens) {
+        lines.push(format!("{} at line {}", d.code, d.line));
+    }
+
+    lines.push(String::new());
+    lines.join("\n")
+}
+
+fn first_difference(expected: &str, actual: &str) -> (usize, String, String) {
+    let mut expected_lines = expected.lines();
+    let mut actual_lines = actual.lines();
+    let mut line = 1;
+
+    loop {
+        match (expected_lines.next(), actual_lines.next()) {
+            (Some(e), Some(a)) if e == a => line += 1,
+            (e, a) => {
+                let describe = |l: Option<&str>| l.unwrap_or("<end of file>").to_string();
+                return (line, describe(e), describe(a));
+            }
+        }
+    }
+}
+
+fn fixtures(dir: &Path) -> Vec<PathBuf> {
+    WalkDir::new(dir)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .map(|e| e.into_path())
+        .filter(|p| p.extension().is_some_and(|e| e == FIXTURE_EXTENSION))
+        .collect()
+}
+
+pub fn run(args: &TestArgs, output: &mut TerminalOutput) {
+    let fixtures = fixtures(&args.dir);
+    let mut passed = 0;
+
+    crate::for_each_file(&fixtures, output, |file, contents, output| {
+        let actual = snapshot(&contents, output);
+
+        let snapshot_file = file.with_extension(SNAPSHOT_EXTENSION);
+        let filename = snapshot_file.display().to_string();
+        let expected = fs::read_to_string(&snapshot_file).ok();
+
+        if expected.as_deref() == Some(actual.as_str()) {
+            passed += 1;
+        } else if args.bless {
+            match fs::write(&snapshot_file, &actual) {
+                Ok(_) => {
+                    passed += 1;
+                    output.writeln_info(Message::SnapshotUpdated(filename));
+                }
+                Err(_) => output.writeln_error(Message::FileWriteFailed(filename)),
+            }
+        } else if let Some(expected) = expected {
+            let (line, expected, actual) = first_difference(&expected, &actual);
+            output.writeln_error(Message::SnapshotMismatch(filename, line, expected, actual));
+        } else {
+            output.writeln_error(Message::SnapshotMissing(filename));
+        }
+    });
+
+    output.writeln_success(Message::SnapshotsPassed(passed, fixtures.len()));
+}
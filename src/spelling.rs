@@ -0,0 +1,206 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use std::{collections::HashSet, fs, path::PathBuf};
+
+use crate::{
+    analyze::is_keyword,
+    diagnostics::Diagnostic,
+    error_codes::ErrorCode,
+    messages::Message,
+    parser::{Sequence, Span, Token},
+    suppressions,
+};
+
+// words of programming that are common in comments but missing from most dictionaries
+const BUNDLED_WORDS: &[&str] = &[
+    "api",
+    "apis",
+    "args",
+    "ascii",
+    "async",
+    "backend",
+    "bool",
+    "boolean",
+    "booleans",
+    "builtin",
+    "cargo",
+    "checksum",
+    "codepoint",
+    "config",
+    "const",
+    "deserialize",
+    "enum",
+    "enums",
+    "frontend",
+    "getter",
+    "hashmap",
+    "impl",
+    "impls",
+    "init",
+    "iterator",
+    "iterators",
+    "json",
+    "lifetime",
+    "lifetimes",
+    "macro",
+    "macros",
+    "metadata",
+    "middleware",
+    "mutex",
+    "namespace",
+    "params",
+    "parser",
+    "regex",
+    "runtime",
+    "rustc",
+    "rustfmt",
+    "serde",
+    "serialize",
+    "setter",
+    "stderr",
+    "stdin",
+    "stdout",
+    "struct",
+    "structs",
+    "timestamp",
+    "todo",
+    "toml",
+    "tuple",
+    "tuples",
+    "unicode",
+    "url",
+    "urls",
+    "utf",
+    "whitespace",
+    "xr",
+];
+
+pub struct Dictionary {
+    words: HashSet<String>,
+}
+
+impl Dictionary {
+    // Plain word lists and Hunspell .dic files (whose first line is the number of words and whose
+    // words may be followed by /flags) are both accepted
+    pub fn load(paths: &[PathBuf], project_words: &[String]) -> Result<Dictionary, Message> {
+        // the bundled words alone would report most of the words of any language
+        if paths.is_empty() {
+            return Err(Message::DictionaryMissing);
+        }
+
+        let mut words: HashSet<String> = BUNDLED_WORDS.iter().map(|w| w.to_string()).collect();
+        words.extend(project_words.iter().map(|w| w.to_lowercase()));
+
+        for path in paths {
+            let contents = fs::read_to_string(path).map_err(|e| {
+                Message::DictionaryUnreadable(path.display().to_string(), e.to_string())
+            })?;
+
+            for line in contents.lines() {
+                let word = line.split('/').next().unwrap_or_default().trim();
+                if !word.is_empty() && !word.starts_with('#') && word.parse::<usize>().is_err() {
+                    words.insert(word.to_lowercase());
+                }
+            }
+        }
+
+        Ok(Dictionary { words })
+    }
+
+    fn knows(&self, word: &str) -> bool {
+        let word = word.to_lowercase();
+        let singular = word
+            .strip_suffix("'s")
+            .or_else(|| word.strip_suffix("’s"))
+            .unwrap_or(&word);
+
+        self.words.contains(&word) || self.words.contains(singular) || is_keyword(singular)
+    }
+
+    // Misspelled words of the comments, and the number of words checked. Words in `code`, URLs,
+    // paths, identifiers (with digits, underscores, dots or capitals after the first letter) and
+    // acronyms are skipped.
+    pub fn lint<'a>(&self, tokens: &[Sequence<'a, Token>]) -> (Vec<Diagnostic<'a>>, usize) {
+        let mut diagnostics = Vec::new();
+        let mut checked = 0;
+        let mut line_number = 1;
+
+        for t in tokens {
+            if let Some(number) = t.token.line_after() {
+                line_number = number;
+            }
+            let Some(comment) = t.comment() else {
+                continue;
+            };
+            // the codes suppressed are not words, and neither is xr-ignore-next-line
+            if suppressions::is_directive(t.text) {
+                continue;
+            }
+
+            let mut in_code = false;
+            let lines = comment.lines();
+            for chunk in lines.iter().flat_map(|l| l.split(char::is_whitespace)) {
+                let was_in_code = in_code;
+                in_code ^= chunk.matches('`').count() % 2 == 1;
+                if was_in_code || chunk.contains('`') || chunk.contains("://") {
+                    continue;
+                }
+
+                let core = chunk.trim_matches(|c: char| !c.is_alphanumeric());
+                if core.contains(|c: char| !(c.is_alphabetic() || matches!(c, '\'' | '’' | '-')))
+                {
+                    continue;
+                }
+
+                let chunk_start = chunk.as_ptr() as usize - t.text.as_ptr() as usize;
+                let core_start = chunk_start + (core.as_ptr() as usize - chunk.as_ptr() as usize);
+
+                for word in core.split('-').filter(|w| is_prose(w)) {
+                    checked += 1;
+                    if self.knows(word) {
+                        continue;
+                    }
+
+                    let start = core_start + (word.as_ptr() as usize - core.as_ptr() as usize);
+                    diagnostics.push(Diagnostic {
+                        code: ErrorCode::W0013,
+                        message: Message::Misspelled(word.to_string()).to_string(),
+                        line: line_number + t.text[..start].matches('\n').count(),
+                        span: Span {
+                            start: t.span.start + start,
+                            end: t.span.start + start + word.len(),
+                        },
+                        text: word,
+                    });
+                }
+            }
+        }
+
+        (diagnostics, checked)
+    }
+}
+
+// words with a capital after the first letter are identifiers (camelCase) or acronyms
+fn is_prose(word: &str) -> bool {
+    !word.is_empty() && !word.chars().skip(1).any(char::is_uppercase)
+}
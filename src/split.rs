@@ -0,0 +1,177 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    arguments::OutputEncoding,
+    messages::Message,
+    parser::{self, Token},
+    sink::Outcome,
+    terminal_helper::TerminalOutput,
+};
+
+// Line breaks after the end of a top level item (a line ending with } or ; outside of any
+// braces, brackets or parentheses), where a part may end, with the number of the next line.
+// A comment after an item keeps the line break from being a split point, so that comments stay
+// with the item they document.
+fn split_points(text: &str, start: usize) -> Vec<(usize, usize)> {
+    let mut points = Vec::new();
+    let mut depth: usize = 0;
+    let mut ends_item = false;
+
+    for t in parser::parse(&text[start..]) {
+        match t.token {
+            Token::Other => {
+                for c in t.text.chars() {
+                    match c {
+                        '{' | '[' | '(' => depth += 1,
+                        '}' | ']' | ')' => depth = depth.saturating_sub(1),
+                        _ => {}
+                    }
+                }
+                ends_item = t.text.ends_with(['}', ';']);
+            }
+            Token::BlankLine(line) | Token::NewLine(line) => {
+                if depth == 0 && ends_item {
+                    points.push((start + t.span.end, line));
+                }
+            }
+            Token::Whitespace { .. } => {}
+            _ => ends_item = false,
+        }
+    }
+
+    points
+}
+
+// Comments and inner attributes at the beginning of the file, which stay in the file that
+// includes the parts as they are not allowed in an included file.
+fn preamble_len(text: &str) -> usize {
+    let mut len = 0;
+
+    for line in text.split_inclusive('\n') {
+        let trimmed = line.trim();
+        let outer_doc = trimmed.starts_with("///") && !trimmed.starts_with("////");
+
+        if trimmed.is_empty()
+            || (trimmed.starts_with("//") && !outer_doc)
+            || trimmed.starts_with("#![")
+        {
+            len += line.len();
+        } else {
+            break;
+        }
+    }
+
+    len
+}
+
+// Offsets where each part begins, so that no part is longer than `max_lines` unless a single
+// item is.
+fn cuts(text: &str, start: usize, max_lines: usize) -> Vec<usize> {
+    let mut cuts = vec![(start, 1)];
+    let mut last = None;
+
+    for (offset, line) in split_points(text, start) {
+        let (begin, begin_line) = *cuts.last().unwrap();
+
+        if line - begin_line > max_lines {
+            match last {
+                Some((l, l_line)) if l > begin => {
+                    cuts.push((l, l_line));
+                    // a single item longer than the maximum is a part on its own
+                    if line - l_line > max_lines {
+                        cuts.push((offset, line));
+                    }
+                }
+                _ => cuts.push((offset, line)),
+            }
+        }
+
+        last = Some((offset, line));
+    }
+
+    // the whitespace after the last item goes with it
+    if cuts.len() > 1 && text[cuts.last().unwrap().0..].trim().is_empty() {
+        cuts.pop();
+    }
+
+    cuts.into_iter().map(|(offset, _)| offset).collect()
+}
+
+fn part_path(new_file: &Path, n: usize) -> PathBuf {
+    let stem = new_file.file_stem().unwrap_or_default().to_string_lossy();
+    new_file.with_file_name(format!("{stem}.part{n:03}.rs"))
+}
+
+// Writes a generated file longer than `max_lines` as numbered parts, which the file includes
+// with include! so that the items of every part remain in the same module.
+pub fn write(
+    new_file: &Path,
+    text: &str,
+    max_lines: usize,
+    encoding: OutputEncoding,
+    output: &mut TerminalOutput,
+) -> Option<Outcome> {
+    let preamble = preamble_len(text);
+    let cuts = cuts(text, preamble, max_lines);
+
+    let mut index = text[..preamble].to_string();
+    let mut parts = 0;
+    let mut outcome = Outcome::Written;
+
+    if cuts.len() > 1 {
+        let ends = cuts.iter().skip(1).copied().chain([text.len()]);
+
+        for (n, (begin, end)) in cuts.iter().copied().zip(ends).enumerate() {
+            let path = part_path(new_file, n + 1);
+            if crate::write_output(&path, &text[begin..end], encoding, output)? == Outcome::HeldBack
+            {
+                outcome = Outcome::HeldBack;
+            }
+
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            index.push_str(&format!("include!(\"{name}\");\n"));
+            parts += 1;
+        }
+    }
+
+    // parts left by a previous generation of a longer file
+    let mut n = parts + 1;
+    while fs::remove_file(part_path(new_file, n)).is_ok() {
+        n += 1;
+    }
+
+    if parts == 0 {
+        return crate::write_output(new_file, text, encoding, output);
+    }
+
+    output.writeln_verbose(Message::OutputSplit(new_file.display().to_string(), parts));
+    match crate::write_output(new_file, &index, encoding, output)? {
+        Outcome::Written => Some(outcome),
+        Outcome::HeldBack => Some(Outcome::HeldBack),
+    }
+}
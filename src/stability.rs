@@ -0,0 +1,46 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+//! Semver policy of the library.
+//!
+//! - The enums that grow as the parser learns more of the language ([`Token`], [`TokenKind`],
+//!   [`InvalidKind`], [`ErrorCode`], [`Message`] and the others marked `#[non_exhaustive]`) may
+//!   get new variants in minor versions, so matching them needs a wildcard arm.
+//! - The names of [`TokenKind::as_str`], which are also the `kind` of the exported tokens, are
+//!   never changed or reused; a kind that is removed keeps its name reserved.
+//! - Removing or renaming anything public, or changing the meaning of a token kind, needs a new
+//!   major version, and so does a new version of the exported format.
+//!
+//! [`Token`]: crate::parser::Token
+//! [`TokenKind`]: crate::parser::TokenKind
+//! [`TokenKind::as_str`]: crate::parser::TokenKind::as_str
+//! [`InvalidKind`]: crate::parser::InvalidKind
+//! [`ErrorCode`]: crate::error_codes::ErrorCode
+//! [`Message`]: crate::messages::Message
+
+/// Version of the set of token kinds, incremented whenever a kind is added, so that consumers
+/// that persist token kinds can tell whether they know all of them.
+pub const TOKEN_KINDS_VERSION: u32 = 1;
+
+/// Version of the JSON produced by [`crate::export::parse_to_json`], incremented whenever a
+/// field is removed or changes meaning.
+pub const EXPORT_FORMAT_VERSION: u32 = 1;
@@ -0,0 +1,99 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use crate::{
+    diagnostics::Diagnostic,
+    error_codes::ErrorCode,
+    messages::Message,
+    parser::{Sequence, Token},
+};
+
+// Checks of the contents of string literals, which are only done when configured
+#[derive(Default)]
+pub struct StringLints {
+    pub max_length: Option<usize>,
+    pub disallowed: Vec<String>,
+    pub non_printable: bool,
+}
+
+// line breaks and tabs may be part of a literal that spans several lines
+fn is_non_printable(c: char) -> bool {
+    c.is_control() && !matches!(c, '\t' | '\n' | '\r')
+}
+
+impl StringLints {
+    pub fn is_enabled(&self) -> bool {
+        self.max_length.is_some() || !self.disallowed.is_empty() || self.non_printable
+    }
+
+    fn messages(&self, contents: &str) -> Vec<(ErrorCode, Message)> {
+        let mut messages = Vec::new();
+
+        let length = contents.chars().count();
+        if let Some(max) = self.max_length.filter(|max| length > *max) {
+            messages.push((ErrorCode::W0006, Message::StringTooLong(length, max)));
+        }
+
+        for text in self
+            .disallowed
+            .iter()
+            .filter(|t| contents.contains(t.as_str()))
+        {
+            messages.push((ErrorCode::W0007, Message::DisallowedInString(text.clone())));
+        }
+
+        if self.non_printable {
+            if let Some(c) = contents.chars().find(|c| is_non_printable(*c)) {
+                messages.push((ErrorCode::W0008, Message::NonPrintableInString(c)));
+            }
+        }
+
+        messages
+    }
+
+    pub fn lint<'a>(&self, tokens: &[Sequence<'a, Token>]) -> Vec<Diagnostic<'a>> {
+        let mut diagnostics = Vec::new();
+        let mut line_number = 1;
+
+        for t in tokens {
+            if let Some(number) = t.token.line_after() {
+                line_number = number;
+            }
+
+            let Some(literal) = t.string_literal() else {
+                continue;
+            };
+
+            for (code, message) in self.messages(literal.contents) {
+                diagnostics.push(Diagnostic {
+                    code,
+                    message: message.to_string(),
+                    line: line_number,
+                    span: t.span,
+                    text: t.text,
+                });
+            }
+        }
+
+        diagnostics
+    }
+}
@@ -0,0 +1,84 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+};
+
+use crate::{error_codes::ErrorCode, messages::Message, terminal_helper::TerminalOutput};
+
+#[derive(Default)]
+struct CodeCount {
+    occurrences: usize,
+    files: BTreeSet<PathBuf>,
+}
+
+// Diagnostics reported during the run, aggregated by code, so that the most frequent kinds of
+// problems stand out at the end of a long run.
+#[derive(Default)]
+struct Summary {
+    file: Option<PathBuf>,
+    codes: BTreeMap<ErrorCode, CodeCount>,
+}
+
+thread_local! {
+    static SUMMARY: RefCell<Summary> = RefCell::default();
+}
+
+pub fn begin_file(file: &Path) {
+    SUMMARY.with(|s| s.borrow_mut().file = Some(file.to_path_buf()));
+}
+
+pub fn record(code: ErrorCode) {
+    SUMMARY.with(|s| {
+        let summary = &mut *s.borrow_mut();
+        let count = summary.codes.entry(code).or_default();
+
+        count.occurrences += 1;
+        if let Some(file) = &summary.file {
+            count.files.insert(file.clone());
+        }
+    });
+}
+
+pub fn report(output: &mut TerminalOutput) {
+    SUMMARY.with(|s| {
+        let summary = s.borrow();
+        if summary.codes.is_empty() {
+            return;
+        }
+
+        let mut codes: Vec<_> = summary.codes.iter().collect();
+        codes.sort_by(|a, b| b.1.occurrences.cmp(&a.1.occurrences).then(a.0.cmp(b.0)));
+
+        output.writeln_info(Message::DiagnosticsByCode);
+        for (code, count) in codes {
+            output.writeln_info(Message::CodeSummary(
+                *code,
+                count.occurrences,
+                count.files.len(),
+            ));
+        }
+    });
+}
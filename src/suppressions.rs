@@ -0,0 +1,185 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use crate::{
+    diagnostics::Diagnostic,
+    error_codes::ErrorCode,
+    parser::{Sequence, Span, Token},
+};
+
+const IGNORE_NEXT_LINE: &str = "xr-ignore-next-line";
+const IGNORE_BLOCK: &str = "xr-ignore-block";
+
+pub struct Suppression<'a> {
+    pub line: usize,
+    pub span: Span,
+    pub text: &'a str,
+    first_line: usize,
+    last_line: usize,
+    // None suppresses every diagnostic
+    codes: Option<Vec<ErrorCode>>,
+    unknown_codes: Vec<&'a str>,
+    used: bool,
+}
+
+impl<'a> Suppression<'a> {
+    pub fn is_used(&self) -> bool {
+        self.used
+    }
+
+    // words of the comment written as codes that xr does not have, such as E0099
+    pub fn unknown_codes(&self) -> &[&'a str] {
+        &self.unknown_codes
+    }
+
+    pub fn suppress(&mut self, diagnostic: &Diagnostic) -> bool {
+        let suppressed = (self.first_line..=self.last_line).contains(&diagnostic.line)
+            && match &self.codes {
+                Some(codes) => codes.contains(&diagnostic.code),
+                None => true,
+            };
+
+        self.used |= suppressed;
+        suppressed
+    }
+}
+
+enum Scope {
+    NextLine,
+    Block,
+}
+
+struct Directive<'a> {
+    scope: Scope,
+    // only a directive followed by no words suppresses every diagnostic
+    codes: Option<Vec<ErrorCode>>,
+    unknown_codes: Vec<&'a str>,
+}
+
+// "// xr-ignore-next-line E0001 E0003", "/* xr-ignore-block */", ...
+// whether the comment is an xr-ignore comment rather than prose
+pub fn is_directive(comment: &str) -> bool {
+    parse_directive(comment).is_some()
+}
+
+fn parse_directive(comment: &str) -> Option<Directive<'_>> {
+    let text = comment
+        .trim_start_matches(['/', '*', '!'])
+        .trim_end()
+        .trim_end_matches("*/");
+
+    let mut words = text.split_whitespace();
+    let scope = match words.next()? {
+        IGNORE_NEXT_LINE => Scope::NextLine,
+        IGNORE_BLOCK => Scope::Block,
+        _ => return None,
+    };
+
+    let words: Vec<&str> = words
+        .map(|w| w.trim_matches(','))
+        .filter(|w| !w.is_empty())
+        .collect();
+
+    // the other words, such as the reason for the suppression, are not codes
+    let mut codes = Vec::new();
+    let mut unknown_codes = Vec::new();
+    for word in words.iter().filter(|w| is_code(w)) {
+        match ErrorCode::parse(word) {
+            Some(code) => codes.push(code),
+            None => unknown_codes.push(*word),
+        }
+    }
+
+    Some(Directive {
+        scope,
+        codes: (!words.is_empty()).then_some(codes),
+        unknown_codes,
+    })
+}
+
+// a word written as a code, an E or a W followed by four digits
+fn is_code(word: &str) -> bool {
+    let mut chars = word.chars();
+
+    matches!(chars.next(), Some('E' | 'W')) && word.len() == 5 && chars.all(|c| c.is_ascii_digit())
+}
+
+// last line of the block that encloses the token at the given index
+fn end_of_block(tokens: &[Sequence<Token>], index: usize, mut line: usize) -> usize {
+    let mut depth: usize = 0;
+
+    for t in &tokens[index..] {
+        match &t.token {
+            Token::BlankLine(number) | Token::NewLine(number) => line = *number,
+            Token::Other => {
+                for c in t.text.chars() {
+                    match c {
+                        '{' => depth += 1,
+                        '}' if depth == 0 => return line,
+                        '}' => depth -= 1,
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    usize::MAX
+}
+
+pub fn find<'a>(tokens: &[Sequence<'a, Token>]) -> Vec<Suppression<'a>> {
+    let mut suppressions = Vec::new();
+    let mut line_number = 1;
+
+    for (index, t) in tokens.iter().enumerate() {
+        match &t.token {
+            Token::BlankLine(number) | Token::NewLine(number) => line_number = *number,
+            Token::SingleLineComment | Token::MultiLineComment => {
+                let last_comment_line = line_number + t.text.matches('\n').count();
+
+                let Some(directive) = parse_directive(t.text) else {
+                    continue;
+                };
+
+                let (first_line, last_line) = match directive.scope {
+                    Scope::NextLine => (last_comment_line + 1, last_comment_line + 1),
+                    Scope::Block => (line_number, end_of_block(tokens, index, line_number)),
+                };
+
+                suppressions.push(Suppression {
+                    line: line_number,
+                    span: t.span,
+                    text: t.text,
+                    first_line,
+                    last_line,
+                    codes: directive.codes,
+                    unknown_codes: directive.unknown_codes,
+                    used: false,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    suppressions
+}
@@ -20,12 +20,121 @@
     DEALINGS IN THE SOFTWARE.
 */
 
-use std::{fmt::Display, io::Write};
-use termcolor::{Color, ColorSpec, StandardStream, WriteColor};
+use std::{
+    fmt::Display,
+    io::{self, IsTerminal, Write},
+    path::{Path, PathBuf},
+};
+use termcolor::{Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
+
+use crate::{
+    error_codes::{ErrorCode, Severity},
+    hyperlinks, lint_levels,
+    messages::Message,
+    reporter::{Diagnostic, Reporter},
+    summary, terminal_width,
+};
+
+#[derive(Clone, Copy, PartialEq, PartialOrd)]
+pub enum Verbosity {
+    Silent,
+    Quiet,
+    Normal,
+    Verbose,
+}
 
 pub struct TerminalOutput {
     stdout: StandardStream,
     stderr: StandardStream,
+    verbosity: Verbosity,
+    errors: usize,
+    warnings: usize,
+    reporters: Vec<Box<dyn Reporter>>,
+    file: Option<PathBuf>,
+    width: Option<usize>,
+    hyperlinks: bool,
+    captured: Option<Captured>,
+}
+
+// errors and warnings written while they are captured, for a client of xr daemon
+#[derive(Default)]
+pub struct Captured {
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+// prints the diagnostics to the terminal, as xr always did
+pub struct HumanReporter {
+    stderr: StandardStream,
+    verbosity: Verbosity,
+    // columns of the terminal, when the diagnostics are wrapped to fit it
+    width: Option<usize>,
+    hyperlinks: bool,
+}
+
+impl HumanReporter {
+    pub fn new(
+        color: ColorChoice,
+        verbosity: Verbosity,
+        width: Option<usize>,
+        hyperlinks: bool,
+    ) -> HumanReporter {
+        HumanReporter {
+            stderr: StandardStream::stderr(stream_color_choice(color, io::stderr().is_terminal())),
+            verbosity,
+            width,
+            hyperlinks,
+        }
+    }
+}
+
+impl Reporter for HumanReporter {
+    fn diagnostic(&mut self, diagnostic: &Diagnostic) {
+        if self.verbosity == Verbosity::Silent {
+            return;
+        }
+
+        let (color_spec, prefix) = match lint_levels::severity(diagnostic.code) {
+            Severity::Error => (
+                error_color_spec(),
+                Message::ErrorCodePrefix(diagnostic.code),
+            ),
+            Severity::Warning => (
+                warn_color_spec(),
+                Message::WarningCodePrefix(diagnostic.code),
+            ),
+        };
+        let prefix = prefix.to_string();
+        let text = match diagnostic.line {
+            Some(line) => Message::ErrorAtLine(line, diagnostic.message.to_string()).to_string(),
+            None => diagnostic.message.to_string(),
+        };
+
+        let mut lines = match self.width {
+            Some(width) => terminal_width::wrap(&text, width, prefix.chars().count()),
+            None => vec![text],
+        };
+
+        // the location at the beginning of the diagnostic links to its file
+        if let (true, Some(file), Some(line)) = (self.hyperlinks, diagnostic.file, diagnostic.line)
+        {
+            let location = Message::ErrorAtLine(line, String::new()).to_string();
+            let location = location.trim_end();
+            lines[0] = lines[0].replacen(location, &hyperlinks::link(file, location), 1);
+        }
+
+        write(&mut self.stderr, &color_spec, &prefix);
+        for line in lines {
+            writeln!(&mut self.stderr, "{line}").ok();
+        }
+    }
+}
+
+fn stream_color_choice(choice: ColorChoice, is_terminal: bool) -> ColorChoice {
+    match choice {
+        ColorChoice::Auto if !is_terminal => ColorChoice::Never,
+        other => other,
+    }
 }
 
 fn set_color(stream: &mut StandardStream, color_spec: &ColorSpec) {
@@ -64,9 +173,9 @@ fn error_color_spec() -> ColorSpec {
     colored_bold_color_spec(Color::Red)
 }
 
-// fn warn_color_spec() -> ColorSpec {
-//     colored_bold_color_spec(Color::Yellow)
-// }
+fn warn_color_spec() -> ColorSpec {
+    colored_bold_color_spec(Color::Yellow)
+}
 
 fn success_color_spec() -> ColorSpec {
     colored_bold_color_spec(Color::Green)
@@ -77,32 +186,197 @@ fn info_color_spec() -> ColorSpec {
 }
 
 impl TerminalOutput {
-    pub fn new() -> TerminalOutput {
+    pub fn new(color: ColorChoice, verbosity: Verbosity) -> TerminalOutput {
         TerminalOutput {
-            stdout: StandardStream::stdout(termcolor::ColorChoice::Auto),
-            stderr: StandardStream::stderr(termcolor::ColorChoice::Auto),
+            stdout: StandardStream::stdout(stream_color_choice(color, io::stdout().is_terminal())),
+            stderr: StandardStream::stderr(stream_color_choice(color, io::stderr().is_terminal())),
+            verbosity,
+            errors: 0,
+            warnings: 0,
+            reporters: vec![Box::new(HumanReporter::new(color, verbosity, None, false))],
+            file: None,
+            width: None,
+            hyperlinks: false,
+            captured: None,
+        }
+    }
+
+    pub fn set_width(&mut self, width: Option<usize>) {
+        self.width = width;
+    }
+
+    pub fn set_hyperlinks(&mut self, hyperlinks: bool) {
+        self.hyperlinks = hyperlinks;
+    }
+
+    // the message about a path, with the middle of the path left out when the message would not
+    // fit in the terminal, and the path linking to the file when hyperlinks are enabled
+    pub fn fit_path<M: Display>(&self, path: &str, message: impl Fn(String) -> M) -> M {
+        let shown = match self.width {
+            Some(width) => {
+                let others = message(String::new()).to_string().chars().count();
+                terminal_width::truncate_middle(path, width.saturating_sub(others))
+            }
+            None => path.to_string(),
+        };
+
+        match self.hyperlinks {
+            true => message(hyperlinks::link(Path::new(path), &shown)),
+            false => message(shown),
+        }
+    }
+
+    pub fn set_reporters(&mut self, reporters: Vec<Box<dyn Reporter>>) {
+        self.reporters = reporters;
+    }
+
+    pub fn file_started(&mut self, file: &Path) {
+        self.file = Some(file.to_path_buf());
+        for reporter in &mut self.reporters {
+            reporter.file_started(file);
+        }
+    }
+
+    pub fn file_finished(&mut self, file: &Path) {
+        for reporter in &mut self.reporters {
+            reporter.file_finished(file);
+        }
+        self.file = None;
+    }
+
+    // lets the reporters write what they collected, such as a JSON file
+    pub fn run_finished(&mut self) {
+        let (errors, warnings) = (self.errors, self.warnings);
+        let failures: Vec<Message> = self
+            .reporters
+            .iter_mut()
+            .filter_map(|r| r.run_finished(errors, warnings).err())
+            .collect();
+
+        for failure in failures {
+            self.writeln_error(failure);
+        }
+    }
+
+    // a problem with a code, found in the file being processed (if any) at the given line
+    pub fn diagnostic<T: Display>(&mut self, code: ErrorCode, line: Option<usize>, text: T) {
+        if lint_levels::is_allowed(code) {
+            return;
+        }
+
+        let severity = lint_levels::severity(code);
+        match severity {
+            Severity::Error => self.errors += 1,
+            Severity::Warning => self.warnings += 1,
+        }
+        summary::record(code);
+
+        let message = text.to_string();
+        self.record_captured(severity, &message);
+        let diagnostic = Diagnostic {
+            file: self.file.as_deref(),
+            code,
+            line,
+            message: &message,
+        };
+        for reporter in &mut self.reporters {
+            reporter.diagnostic(&diagnostic);
+        }
+    }
+
+    // the errors and warnings are also kept until take_captured is called
+    pub fn capture(&mut self) {
+        self.captured = Some(Captured::default());
+    }
+
+    pub fn take_captured(&mut self) -> Captured {
+        self.captured.take().unwrap_or_default()
+    }
+
+    fn record_captured(&mut self, severity: Severity, text: &str) {
+        if let Some(captured) = &mut self.captured {
+            match severity {
+                Severity::Error => captured.errors.push(text.to_string()),
+                Severity::Warning => captured.warnings.push(text.to_string()),
+            }
         }
     }
 
+    pub fn error_count(&self) -> usize {
+        self.errors
+    }
+
+    pub fn warning_count(&self) -> usize {
+        self.warnings
+    }
+
+    pub fn is_verbose(&self) -> bool {
+        self.verbosity >= Verbosity::Verbose
+    }
+
     pub fn writeln_success<T: Display>(&mut self, text: T) {
-        writeln(&mut self.stdout, &success_color_spec(), text);
+        if self.verbosity >= Verbosity::Normal {
+            writeln(&mut self.stdout, &success_color_spec(), text);
+        }
     }
 
     pub fn writeln_info<T: Display>(&mut self, text: T) {
-        writeln(&mut self.stdout, &info_color_spec(), text);
+        if self.verbosity >= Verbosity::Normal {
+            writeln(&mut self.stdout, &info_color_spec(), text);
+        }
     }
 
     pub fn writeln_error<T: Display>(&mut self, text: T) {
-        write(&mut self.stderr, &error_color_spec(), "Error: ");
+        self.errors += 1;
+        self.record_captured(Severity::Error, &text.to_string());
+
+        if self.verbosity == Verbosity::Silent {
+            return;
+        }
+
+        write(&mut self.stderr, &error_color_spec(), Message::ErrorPrefix);
         writeln!(&mut self.stderr, "{text}").ok();
     }
 
-    // pub fn writeln_warning<T: Display>(&mut self, text: T) {
-    //     write(&mut self.stderr, &warn_color_spec(), "Warning: ");
-    //     writeln!(&mut self.stderr, "{text}").ok();
-    // }
+    pub fn writeln_warning<T: Display>(&mut self, text: T) {
+        self.warnings += 1;
+        self.record_captured(Severity::Warning, &text.to_string());
+
+        if self.verbosity == Verbosity::Silent {
+            return;
+        }
+
+        write(&mut self.stderr, &warn_color_spec(), Message::WarningPrefix);
+        writeln!(&mut self.stderr, "{text}").ok();
+    }
 
     pub fn writeln<T: Display>(&mut self, text: T) {
+        if self.verbosity >= Verbosity::Normal {
+            writeln!(&mut self.stdout, "{text}").ok();
+        }
+    }
+
+    // output requested by the user, which is printed regardless of the verbosity
+    pub fn writeln_result<T: Display>(&mut self, text: T) {
         writeln!(&mut self.stdout, "{text}").ok();
     }
+
+    // part of a line of output requested by the user, in the given color
+    pub fn write_result<T: Display>(&mut self, color: Option<Color>, text: T) {
+        let mut color_spec = default_color_spec();
+        color_spec.set_fg(color);
+        write(&mut self.stdout, &color_spec, text);
+    }
+
+    // a question to the user, printed regardless of the verbosity and without ending the line
+    pub fn prompt<T: Display>(&mut self, text: T) {
+        write!(&mut self.stdout, "{text}").ok();
+        self.stdout.flush().ok();
+    }
+
+    pub fn writeln_verbose<T: Display>(&mut self, text: T) {
+        if self.verbosity >= Verbosity::Verbose {
+            writeln!(&mut self.stdout, "{text}").ok();
+        }
+    }
 }
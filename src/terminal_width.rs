@@ -0,0 +1,115 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use std::{
+    env,
+    io::{self, IsTerminal},
+    mem,
+};
+
+const ELLIPSIS: char = '…';
+
+// below this, wrapping leaves too little room on each line to help
+const MIN_WRAP_WIDTH: usize = 20;
+
+// the number of columns of the terminal where the diagnostics are printed, if known; COLUMNS
+// takes precedence, so that log viewers may set it too
+pub fn detect() -> Option<usize> {
+    if let Some(columns) = env::var("COLUMNS").ok().and_then(|c| c.trim().parse().ok()) {
+        return (columns > 0).then_some(columns);
+    }
+
+    if !io::stderr().is_terminal() {
+        return None;
+    }
+    terminal_columns()
+}
+
+#[cfg(unix)]
+fn terminal_columns() -> Option<usize> {
+    let mut size = libc::winsize {
+        ws_row: 0,
+        ws_col: 0,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    };
+    // TIOCGWINSZ only writes to the winsize passed
+    let result = unsafe { libc::ioctl(libc::STDERR_FILENO, libc::TIOCGWINSZ, &mut size) };
+    (result == 0 && size.ws_col > 0).then_some(usize::from(size.ws_col))
+}
+
+#[cfg(not(unix))]
+fn terminal_columns() -> Option<usize> {
+    None
+}
+
+// replaces the middle of a text longer than the width by an ellipsis, keeping the beginning and
+// the end of a path, which identify it best
+pub fn truncate_middle(text: &str, width: usize) -> String {
+    let length = text.chars().count();
+    if length <= width || width < 3 {
+        return text.to_string();
+    }
+
+    let kept = width - 1;
+    let end = kept / 2;
+    let start = kept - end;
+
+    let mut truncated: String = text.chars().take(start).collect();
+    truncated.push(ELLIPSIS);
+    truncated.extend(text.chars().skip(length - end));
+    truncated
+}
+
+// splits a text printed after a prefix as long as the indentation into lines of at most the
+// width (except for words longer than it), where every line after the first is indented
+pub fn wrap(text: &str, width: usize, indent: usize) -> Vec<String> {
+    if width < indent + MIN_WRAP_WIDTH {
+        return vec![text.to_string()];
+    }
+
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    let mut length = indent;
+    let mut empty = true;
+
+    for word in text.split(' ') {
+        let word_length = word.chars().count();
+        if !empty && length + 1 + word_length > width {
+            lines.push(mem::take(&mut line));
+            line.push_str(&" ".repeat(indent));
+            length = indent;
+            empty = true;
+        }
+        if !empty {
+            line.push(' ');
+            length += 1;
+        }
+
+        line.push_str(word);
+        length += word_length;
+        empty = false;
+    }
+
+    lines.push(line);
+    lines
+}
@@ -0,0 +1,190 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use termcolor::Color;
+
+use crate::terminal_helper::TerminalOutput;
+
+// lines of unchanged text shown around each change
+const CONTEXT: usize = 3;
+
+// above this many pairs of lines, the changed lines are not matched against each other
+const MAX_TABLE: usize = 16_000_000;
+
+#[derive(Clone, Copy)]
+pub enum Line<'a> {
+    Context(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+pub struct Hunk<'a> {
+    old_start: usize,
+    new_start: usize,
+    lines: Vec<Line<'a>>,
+}
+
+impl Hunk<'_> {
+    fn header(&self) -> String {
+        let old_len = self
+            .lines
+            .iter()
+            .filter(|l| !matches!(l, Line::Added(_)))
+            .count();
+        let new_len = self
+            .lines
+            .iter()
+            .filter(|l| !matches!(l, Line::Removed(_)))
+            .count();
+
+        format!(
+            "@@ -{},{} +{},{} @@",
+            self.old_start, old_len, self.new_start, new_len
+        )
+    }
+}
+
+// Matches the lines with the longest common subsequence, after skipping the lines that are the
+// same at the start and at the end, which are most of the lines of a file edited by hand.
+fn edits<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<Line<'a>> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let a = &old[prefix..old.len() - suffix];
+    let b = &new[prefix..new.len() - suffix];
+
+    let mut lines: Vec<Line> = old[..prefix].iter().map(|l| Line::Context(l)).collect();
+    let (mut i, mut j) = (0, 0);
+
+    if a.len() * b.len() <= MAX_TABLE {
+        // length of the common subsequence of a[i..] and b[j..]
+        let width = b.len() + 1;
+        let mut table = vec![0u32; (a.len() + 1) * width];
+
+        for i in (0..a.len()).rev() {
+            for j in (0..b.len()).rev() {
+                table[i * width + j] = if a[i] == b[j] {
+                    table[(i + 1) * width + j + 1] + 1
+                } else {
+                    table[(i + 1) * width + j].max(table[i * width + j + 1])
+                };
+            }
+        }
+
+        while i < a.len() && j < b.len() {
+            if a[i] == b[j] {
+                lines.push(Line::Context(a[i]));
+                i += 1;
+                j += 1;
+            } else if table[(i + 1) * width + j] >= table[i * width + j + 1] {
+                lines.push(Line::Removed(a[i]));
+                i += 1;
+            } else {
+                lines.push(Line::Added(b[j]));
+                j += 1;
+            }
+        }
+    }
+
+    lines.extend(a[i..].iter().map(|l| Line::Removed(l)));
+    lines.extend(b[j..].iter().map(|l| Line::Added(l)));
+    lines.extend(old[old.len() - suffix..].iter().map(|l| Line::Context(l)));
+    lines
+}
+
+// the changed lines, grouped with their context in the way of a unified diff
+pub fn hunks<'a>(old: &'a str, new: &'a str) -> Vec<Hunk<'a>> {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let lines = edits(&old_lines, &new_lines);
+
+    // line numbers of the old and new text before each line
+    let mut positions = Vec::with_capacity(lines.len());
+    let (mut old_line, mut new_line) = (1, 1);
+    for line in &lines {
+        positions.push((old_line, new_line));
+        match line {
+            Line::Context(_) => {
+                old_line += 1;
+                new_line += 1;
+            }
+            Line::Removed(_) => old_line += 1,
+            Line::Added(_) => new_line += 1,
+        }
+    }
+
+    let changes: Vec<usize> = (0..lines.len())
+        .filter(|&i| !matches!(lines[i], Line::Context(_)))
+        .collect();
+
+    let mut hunks = Vec::new();
+    let mut k = 0;
+
+    while k < changes.len() {
+        let start = changes[k].saturating_sub(CONTEXT);
+        let mut end = changes[k] + 1;
+
+        // changes whose contexts would overlap go in the same hunk
+        while k + 1 < changes.len() && changes[k + 1] <= end + 2 * CONTEXT {
+            k += 1;
+            end = changes[k] + 1;
+        }
+        k += 1;
+
+        let end = (end + CONTEXT).min(lines.len());
+        let (old_start, new_start) = positions[start];
+
+        hunks.push(Hunk {
+            old_start,
+            new_start,
+            lines: lines[start..end].to_vec(),
+        });
+    }
+
+    hunks
+}
+
+pub fn print(hunks: &[Hunk], output: &mut TerminalOutput) {
+    for hunk in hunks {
+        output.write_result(Some(Color::Cyan), hunk.header());
+        output.writeln_result("");
+
+        for line in &hunk.lines {
+            match line {
+                Line::Context(l) => output.writeln_result(format!(" {l}")),
+                Line::Removed(l) => {
+                    output.write_result(Some(Color::Red), format!("-{l}"));
+                    output.writeln_result("");
+                }
+                Line::Added(l) => {
+                    output.write_result(Some(Color::Green), format!("+{l}"));
+                    output.writeln_result("");
+                }
+            }
+        }
+    }
+}
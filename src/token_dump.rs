@@ -0,0 +1,151 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use termcolor::Color;
+
+use crate::{
+    arguments::TokensArgs,
+    highlight,
+    parser::{self, Sequence, Token},
+    terminal_helper::TerminalOutput,
+};
+
+// characters of the text shown for each token, before it is escaped
+const PREVIEW_CHARS: usize = 40;
+
+struct Entry<'a> {
+    kind: String,
+    color: Option<Color>,
+    text: &'a str,
+    line: usize,
+    start: usize,
+    end: usize,
+}
+
+pub fn dump(tokens: &[Sequence<Token>]) -> Vec<String> {
+    let mut lines = Vec::with_capacity(tokens.len());
+    let mut line_number = 1;
+
+    for t in tokens {
+        lines.push(format!("{line_number:>5}  {:?} {:?}", t.token, t.text));
+
+        if let Some(number) = t.token.line_after() {
+            line_number = number;
+        }
+    }
+
+    lines
+}
+
+fn color(token: &Token) -> Option<Color> {
+    match highlight::css_class(token)? {
+        "comment" => Some(Color::Green),
+        "string" | "char" => Some(Color::Yellow),
+        "invalid" => Some(Color::Red),
+        _ => Some(Color::Magenta),
+    }
+}
+
+// the tokens of every level cover the whole text, so the line of each one is the number of line
+// breaks before it
+fn entries<'a, T: PartialEq>(
+    tokens: &[Sequence<'a, T>],
+    describe: impl Fn(&T) -> (String, Option<Color>),
+) -> Vec<Entry<'a>> {
+    let mut line = 1;
+
+    tokens
+        .iter()
+        .map(|t| {
+            let (kind, color) = describe(&t.token);
+            let entry = Entry {
+                kind,
+                color,
+                text: t.text,
+                line,
+                start: t.span.start,
+                end: t.span.end,
+            };
+            line += t.text.matches('\n').count();
+            entry
+        })
+        .collect()
+}
+
+fn preview(text: &str) -> String {
+    let shown: String = text.chars().take(PREVIEW_CHARS).collect();
+    match shown.len() < text.len() {
+        true => format!("{shown:?}…"),
+        false => format!("{shown:?}"),
+    }
+}
+
+// tokens grouped under the line where they begin
+fn print(entries: &[Entry], output: &mut TerminalOutput) {
+    let kind_width = entries
+        .iter()
+        .map(|e| e.kind.len())
+        .max()
+        .unwrap_or_default();
+    let span_width = entries
+        .iter()
+        .map(|e| format!("{}..{}", e.start, e.end).len())
+        .max()
+        .unwrap_or_default();
+
+    for (i, e) in entries.iter().enumerate() {
+        if i == 0 || entries[i - 1].line != e.line {
+            output.write_result(Some(Color::Cyan), format!("line {}", e.line));
+            output.writeln_result("");
+        }
+
+        let last = entries.get(i + 1).is_none_or(|next| next.line != e.line);
+        let branch = if last { "└─" } else { "├─" };
+        let span = format!("{}..{}", e.start, e.end);
+
+        output.write_result(None, format!("  {branch} "));
+        output.write_result(e.color, format!("{:<kind_width$}", e.kind));
+        output.writeln_result(format!("  {span:<span_width$}  {}", preview(e.text)));
+    }
+}
+
+pub fn run(args: &TokensArgs, output: &mut TerminalOutput) {
+    let filenames = args.input.files_to_process(output);
+
+    crate::for_each_file(&filenames, output, |_, contents, output| {
+        let tokens = crate::parse_file(&contents, output);
+
+        if args.plain {
+            dump(&tokens)
+                .into_iter()
+                .for_each(|line| output.writeln_result(line));
+            return;
+        }
+
+        let entries = match args.level {
+            1 => entries(&parser::level_one_tokens(&contents), |k| (k.clone(), None)),
+            2 => entries(&parser::level_two_tokens(&contents), |k| (k.clone(), None)),
+            _ => entries(&tokens, |t| (format!("{t:?}"), color(t))),
+        };
+        print(&entries, output);
+    });
+}
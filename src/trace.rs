@@ -0,0 +1,107 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use std::{
+    cell::RefCell,
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::{messages::Message, parser, terminal_helper::TerminalOutput};
+
+// Log of the decisions of the parser, each file under a header with its name, so that a
+// misparse in a file reported by a user can be followed without a debugger.
+struct Trace {
+    writer: BufWriter<File>,
+    path: PathBuf,
+    file: Option<PathBuf>,
+    failed: bool,
+}
+
+thread_local! {
+    static TRACE: RefCell<Option<Trace>> = const { RefCell::new(None) };
+}
+
+pub fn open(path: &Path) -> Result<(), Message> {
+    let file = File::create(path)
+        .map_err(|e| Message::TraceUnwritable(path.display().to_string(), e.to_string()))?;
+
+    parser::start_trace();
+    TRACE.with(|t| {
+        *t.borrow_mut() = Some(Trace {
+            writer: BufWriter::new(file),
+            path: path.to_path_buf(),
+            file: None,
+            failed: false,
+        })
+    });
+
+    Ok(())
+}
+
+pub fn begin_file(file: &Path) {
+    TRACE.with(|t| {
+        if let Some(trace) = t.borrow_mut().as_mut() {
+            trace.file = Some(file.to_path_buf());
+        }
+    });
+}
+
+pub fn record(output: &mut TerminalOutput) {
+    TRACE.with(|t| {
+        let mut trace = t.borrow_mut();
+        let Some(trace) = trace.as_mut() else {
+            return;
+        };
+
+        let lines = parser::take_trace();
+        if trace.failed {
+            return;
+        }
+
+        let written = write_file(trace, &lines);
+        if let Err(e) = written {
+            // reported once, instead of for every file
+            trace.failed = true;
+            output.writeln_error(Message::TraceUnwritable(
+                trace.path.display().to_string(),
+                e.to_string(),
+            ));
+        }
+    });
+}
+
+fn write_file(trace: &mut Trace, lines: &[String]) -> std::io::Result<()> {
+    let name = match &trace.file {
+        Some(file) => file.display().to_string(),
+        None => "-".to_string(),
+    };
+
+    writeln!(trace.writer, "== {name}")?;
+    for line in lines {
+        writeln!(trace.writer, "{line}")?;
+    }
+
+    // flushed at once, so that the trace is complete even if the process dies afterwards
+    trace.writer.flush()
+}
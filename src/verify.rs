@@ -0,0 +1,203 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::{self, Command},
+};
+
+use serde_json::Value;
+use xr::line_index::LineIndex;
+
+use crate::{
+    header::Header, messages::Message, terminal_helper::TerminalOutput, visitor::Rewritten,
+};
+
+const RUSTC: &str = "rustc";
+const EDITION: &str = "2021";
+
+// Maps the lines of a generated file to the lines of the XR file it was generated from, through
+// the provenance of the rendered text and the positions of the text inserted in it by the header.
+pub struct SourceMap {
+    // XR line of each line of the generated file, None for the lines of the header
+    xr_lines: Vec<Option<usize>>,
+}
+
+impl SourceMap {
+    pub fn new(header: &Header, rendered: &Rewritten, input: &str) -> SourceMap {
+        let input_lines = LineIndex::new(input);
+
+        let xr_line = |offset: usize| {
+            let p = rendered.find(offset)?;
+
+            // the text of the tokens that were not replaced can be traced byte by byte
+            let input_offset = if p.output.len() == p.input.len() {
+                p.input.start + (offset - p.output.start)
+            } else {
+                p.input.start
+            };
+
+            Some(input_lines.line(input_offset))
+        };
+
+        let insertions = header.insertions(&rendered.text);
+
+        let xr_lines = LineIndex::new(&header.apply(&rendered.text))
+            .line_starts()
+            .iter()
+            .map(|&offset| {
+                // length of the text inserted before the offset
+                let mut shift = 0;
+
+                for &(index, len) in &insertions {
+                    if offset < index + shift {
+                        break;
+                    }
+                    if offset < index + shift + len {
+                        return None;
+                    }
+                    shift += len;
+                }
+
+                xr_line(offset - shift)
+            })
+            .collect();
+
+        SourceMap { xr_lines }
+    }
+
+    fn xr_line(&self, line: usize) -> Option<usize> {
+        self.xr_lines.get(line.checked_sub(1)?).copied().flatten()
+    }
+}
+
+fn out_dir() -> PathBuf {
+    env::temp_dir().join(format!("xr-verify-{}", process::id()))
+}
+
+// Compiles a generated file as a library with rustc (only up to the metadata, so no code is
+// generated) and reports its errors at the lines of the XR file, when the source map is known
+pub fn verify(
+    generated: &Path,
+    input: &Path,
+    source_map: Option<&SourceMap>,
+    output: &mut TerminalOutput,
+) {
+    let out_dir = out_dir();
+
+    let result = Command::new(RUSTC)
+        .args([
+            "--emit=metadata",
+            "--crate-type",
+            "lib",
+            "--crate-name",
+            "xr_verify",
+        ])
+        .args([
+            "--edition",
+            EDITION,
+            "--error-format",
+            "json",
+            "--cap-lints",
+            "allow",
+        ])
+        .arg("--out-dir")
+        .arg(&out_dir)
+        .arg(generated)
+        .output();
+
+    fs::remove_dir_all(&out_dir).ok();
+
+    let result = match result {
+        Ok(result) => result,
+        Err(e) => {
+            output.writeln_error(Message::ProgramNotRun(RUSTC.to_string(), e.to_string()));
+            return;
+        }
+    };
+
+    let stderr = String::from_utf8_lossy(&result.stderr);
+
+    for diagnostic in stderr
+        .lines()
+        .filter_map(|l| serde_json::from_str::<Value>(l).ok())
+    {
+        if diagnostic["level"] != "error" {
+            continue;
+        }
+
+        let message = diagnostic["message"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+        let line = diagnostic["spans"]
+            .as_array()
+            .and_then(|spans| spans.iter().find(|s| s["is_primary"] == true))
+            .and_then(|span| span["line_start"].as_u64());
+
+        // errors without a location (such as "aborting due to previous error") only summarize
+        // the others
+        let Some(line) = line.map(|l| l as usize) else {
+            continue;
+        };
+
+        report(generated, input, source_map, line, message, output);
+    }
+}
+
+// Parses the text of a generated file with syn, which only finds syntax errors (and only the
+// first one), but does not need rustc
+#[cfg(feature = "syn")]
+pub fn verify_syntax(
+    text: &str,
+    generated: &Path,
+    input: &Path,
+    source_map: Option<&SourceMap>,
+    output: &mut TerminalOutput,
+) {
+    if let Err(e) = syn::parse_file(text) {
+        let line = e.span().start().line;
+        report(generated, input, source_map, line, e.to_string(), output);
+    }
+}
+
+fn report(
+    generated: &Path,
+    input: &Path,
+    source_map: Option<&SourceMap>,
+    line: usize,
+    message: String,
+    output: &mut TerminalOutput,
+) {
+    let generated = generated.display().to_string();
+
+    match source_map.and_then(|map| map.xr_line(line)) {
+        Some(xr_line) => output.writeln_error(Message::InvalidGeneratedCode(
+            input.display().to_string(),
+            xr_line,
+            generated,
+            message,
+        )),
+        None => output.writeln_error(Message::InvalidGeneratedCodeAt(generated, line, message)),
+    }
+}
@@ -0,0 +1,109 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use std::{
+    borrow::Cow,
+    cell::RefCell,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use xr::output_sink::OutputSink;
+
+use crate::{header::Header, messages::Message, sink, terminal_helper::TerminalOutput, text_diff};
+
+struct Mismatch {
+    path: PathBuf,
+    existing: Option<Vec<u8>>,
+    generated: Vec<u8>,
+}
+
+#[derive(Default)]
+struct Verification {
+    checked: usize,
+    mismatches: Vec<Mismatch>,
+}
+
+thread_local! {
+    static VERIFICATION: RefCell<Verification> = RefCell::default();
+}
+
+// Instead of writing the generated files, compares them with the files already on disk, so that
+// the generated files committed to a repository cannot be edited by hand unnoticed.
+struct Comparison {
+    header: Header,
+}
+
+impl OutputSink for Comparison {
+    fn write(&mut self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        let existing = fs::read(path).ok();
+        let existing = existing
+            .as_deref()
+            .map(|e| self.header.without_timestamp(e));
+        let generated = self.header.without_timestamp(contents);
+
+        VERIFICATION.with(|v| {
+            let mut verification = v.borrow_mut();
+            verification.checked += 1;
+
+            if existing.as_deref() != Some(&generated) {
+                verification.mismatches.push(Mismatch {
+                    path: path.to_path_buf(),
+                    existing: existing.map(Cow::into_owned),
+                    generated: generated.into_owned(),
+                });
+            }
+        });
+
+        Ok(())
+    }
+}
+
+pub fn enable(header: &Header) {
+    sink::replace(Box::new(Comparison {
+        header: header.clone(),
+    }));
+}
+
+pub fn report(output: &mut TerminalOutput) {
+    let verification = VERIFICATION.with(|v| v.take());
+
+    for mismatch in &verification.mismatches {
+        let path = mismatch.path.display().to_string();
+
+        let Some(existing) = &mismatch.existing else {
+            output.writeln_error(Message::GeneratedFileMissing(path));
+            continue;
+        };
+
+        output.writeln_error(Message::GeneratedFileDiffers(path));
+
+        let existing = String::from_utf8_lossy(existing);
+        let generated = String::from_utf8_lossy(&mismatch.generated);
+        text_diff::print(&text_diff::hunks(&existing, &generated), output);
+    }
+
+    output.writeln_info(Message::GeneratedFilesVerified(
+        verification.checked,
+        verification.mismatches.len(),
+    ));
+}
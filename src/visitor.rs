@@ -0,0 +1,204 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use crate::parser::{InvalidKind, Sequence, Span, Token};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Position {
+    /// Line, starting at 1
+    pub line: usize,
+    /// Column in characters, starting at 1
+    pub column: usize,
+    /// Byte offset from the beginning of the file
+    pub offset: usize,
+}
+
+/// Callbacks for each kind of token of a parsed file, called by [`walk`] in order.
+///
+/// Every callback does nothing by default. [`TokenVisitor::visit_token`] is called for every
+/// token and dispatches to the callback of its kind, so it can be overridden to handle all
+/// tokens in the same way.
+pub trait TokenVisitor<'a> {
+    fn visit_token(&mut self, token: &Token, text: &'a str, position: Position) {
+        match token {
+            Token::BlankLine(_) => self.visit_blank_line(text, position),
+            Token::CharLiteral => self.visit_char_literal(text, position),
+            Token::Invalid(kind) => self.visit_invalid(kind, text, position),
+            Token::Lifetime => self.visit_lifetime(text, position),
+            Token::LoopLabel => self.visit_loop_label(text, position),
+            Token::MultiLineComment => self.visit_multi_line_comment(text, position),
+            Token::NewLine(_) => self.visit_new_line(text, position),
+            Token::Other => self.visit_other(text, position),
+            Token::SingleLineComment => self.visit_single_line_comment(text, position),
+            Token::StrLiteral => self.visit_str_literal(text, position),
+            Token::Whitespace { spaces, tabs } => {
+                self.visit_whitespace(*spaces, *tabs, text, position)
+            }
+        }
+    }
+
+    fn visit_blank_line(&mut self, _text: &'a str, _position: Position) {}
+
+    fn visit_char_literal(&mut self, _text: &'a str, _position: Position) {}
+
+    fn visit_invalid(&mut self, _kind: &InvalidKind, _text: &'a str, _position: Position) {}
+
+    fn visit_lifetime(&mut self, _text: &'a str, _position: Position) {}
+
+    fn visit_loop_label(&mut self, _text: &'a str, _position: Position) {}
+
+    fn visit_multi_line_comment(&mut self, _text: &'a str, _position: Position) {}
+
+    fn visit_new_line(&mut self, _text: &'a str, _position: Position) {}
+
+    fn visit_other(&mut self, _text: &'a str, _position: Position) {}
+
+    fn visit_single_line_comment(&mut self, _text: &'a str, _position: Position) {}
+
+    fn visit_str_literal(&mut self, _text: &'a str, _position: Position) {}
+
+    fn visit_whitespace(
+        &mut self,
+        _spaces: usize,
+        _tabs: usize,
+        _text: &'a str,
+        _position: Position,
+    ) {
+    }
+}
+
+/// Calls the visitor for each token, with the position where the token begins.
+pub fn walk<'a, V: TokenVisitor<'a>>(tokens: &[Sequence<'a, Token>], visitor: &mut V) {
+    let mut position = Position {
+        line: 1,
+        column: 1,
+        offset: 0,
+    };
+
+    for t in tokens {
+        position.offset = t.span.start;
+        visitor.visit_token(&t.token, t.text, position);
+
+        match t.text.rfind('\n') {
+            Some(i) => {
+                position.line += t.text.matches('\n').count();
+                position.column = t.text[i + 1..].chars().count() + 1;
+            }
+            None => position.column += t.text.chars().count(),
+        }
+    }
+}
+
+/// Span of the text written for a token by a rewrite, and span of the token in the input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Provenance {
+    pub output: Span,
+    pub input: Span,
+}
+
+/// Text produced by a rewrite, with the provenance of the text of each token, in order.
+#[derive(Clone, Debug, Default)]
+pub struct Rewritten {
+    pub text: String,
+    pub provenance: Vec<Provenance>,
+}
+
+impl Rewritten {
+    /// Provenance of the token that produced the byte at `offset` of the output.
+    ///
+    /// The end of the output belongs to the last token, so that positions such as the end of
+    /// file can also be traced.
+    pub fn find(&self, offset: usize) -> Option<&Provenance> {
+        let i = self.provenance.partition_point(|p| p.output.end <= offset);
+        self.provenance
+            .get(i)
+            .or(self.provenance.last().filter(|p| p.output.end == offset))
+    }
+}
+
+/// Visitor that copies the text of every token to a new string, replacing the text of the
+/// tokens for which `replace` returns a value.
+pub struct Rewriter<F> {
+    replace: F,
+    output: Rewritten,
+}
+
+impl<F: FnMut(&Token, &str, Position) -> Option<String>> Rewriter<F> {
+    pub fn new(replace: F) -> Rewriter<F> {
+        Rewriter {
+            replace,
+            output: Rewritten::default(),
+        }
+    }
+
+    pub fn finish(self) -> String {
+        self.output.text
+    }
+
+    pub fn finish_with_provenance(self) -> Rewritten {
+        self.output
+    }
+}
+
+impl<'a, F: FnMut(&Token, &str, Position) -> Option<String>> TokenVisitor<'a> for Rewriter<F> {
+    fn visit_token(&mut self, token: &Token, text: &'a str, position: Position) {
+        let start = self.output.text.len();
+
+        match (self.replace)(token, text, position) {
+            Some(replacement) => self.output.text.push_str(&replacement),
+            None => self.output.text.push_str(text),
+        }
+
+        // tokens removed by the rewrite have no text to trace
+        let end = self.output.text.len();
+        if end > start {
+            self.output.provenance.push(Provenance {
+                output: Span { start, end },
+                input: Span {
+                    start: position.offset,
+                    end: position.offset + text.len(),
+                },
+            });
+        }
+    }
+}
+
+/// Rewrites a parsed file, replacing the text of the tokens for which `replace` returns a value.
+pub fn rewrite<F>(tokens: &[Sequence<Token>], replace: F) -> String
+where
+    F: FnMut(&Token, &str, Position) -> Option<String>,
+{
+    let mut rewriter = Rewriter::new(replace);
+    walk(tokens, &mut rewriter);
+    rewriter.finish()
+}
+
+/// Same as [`rewrite`], also returning the provenance of the rewritten text.
+pub fn rewrite_with_provenance<F>(tokens: &[Sequence<Token>], replace: F) -> Rewritten
+where
+    F: FnMut(&Token, &str, Position) -> Option<String>,
+{
+    let mut rewriter = Rewriter::new(replace);
+    walk(tokens, &mut rewriter);
+    rewriter.finish_with_provenance()
+}
@@ -0,0 +1,205 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+    time::SystemTime,
+};
+
+use crate::{
+    arguments::{Args, Command, WatchArgs},
+    header::Header,
+    interrupt,
+    messages::Message,
+    project::Project,
+    run_lock,
+    terminal_helper::{TerminalOutput, Verbosity},
+};
+
+fn modification_time(file: &Path) -> Option<SystemTime> {
+    fs::metadata(file).and_then(|m| m.modified()).ok()
+}
+
+// files that every generated file depends on, whose changes regenerate every file
+fn shared_files(args: &WatchArgs, config: Option<&Path>) -> Vec<PathBuf> {
+    let generate = &args.build.generate;
+
+    [config, generate.header_file(), generate.license_file()]
+        .into_iter()
+        .flatten()
+        .map(Path::to_path_buf)
+        .collect()
+}
+
+fn modification_times(files: &[PathBuf]) -> Vec<Option<SystemTime>> {
+    files.iter().map(|f| modification_time(f)).collect()
+}
+
+// The options are obtained again from the command line and the configuration file, so that the
+// changes to the configuration apply to the following runs. The limits are kept as they were.
+fn reload(output: &mut TerminalOutput) -> Option<(WatchArgs, Header, Option<PathBuf>)> {
+    let mut args = Args::obtain();
+
+    let config = match args.apply_config() {
+        Ok(config) => config,
+        Err(e) => {
+            output.writeln_error(e);
+            return None;
+        }
+    };
+
+    let Command::Watch(watch) = args.into_command() else {
+        return None;
+    };
+    let header = crate::obtain_header(&watch.build.generate, output)?;
+
+    Some((watch, header, config))
+}
+
+fn load_state(path: Option<&Path>) -> HashSet<PathBuf> {
+    let contents = path.and_then(|p| fs::read_to_string(p).ok());
+    contents
+        .iter()
+        .flat_map(|c| c.lines())
+        .map(PathBuf::from)
+        .collect()
+}
+
+fn save_state(path: &Path, with_problems: &HashSet<PathBuf>, output: &mut TerminalOutput) {
+    let mut files: Vec<String> = with_problems
+        .iter()
+        .map(|f| f.display().to_string() + "\n")
+        .collect();
+    files.sort();
+
+    if fs::write(path, files.concat()).is_err() {
+        output.writeln_error(Message::FileWriteFailed(path.display().to_string()));
+    }
+}
+
+pub fn run(args: &WatchArgs, header: &Header, config: Option<&Path>, output: &mut TerminalOutput) {
+    let mut reloaded: Option<(WatchArgs, Header, Option<PathBuf>)> = None;
+
+    let mut modified = HashMap::<PathBuf, Option<SystemTime>>::new();
+    let mut first_scan = true;
+
+    let mut shared = shared_files(args, config);
+    let mut shared_modified = modification_times(&shared);
+
+    // files whose last run reported errors or warnings, which are processed first, so that the
+    // feedback on the files known to have problems comes as soon as possible
+    let mut with_problems = load_state(args.state.as_deref());
+
+    loop {
+        let times = modification_times(&shared);
+        let shared_changed = times != shared_modified;
+
+        if shared_changed {
+            let changed_file = shared
+                .iter()
+                .zip(times.iter().zip(&shared_modified))
+                .find(|(_, (now, before))| now != before);
+            if let Some((file, _)) = changed_file {
+                output.writeln_info(Message::SharedFileChanged(file.display().to_string()));
+            }
+
+            if let Some(options) = reload(output) {
+                reloaded = Some(options);
+            }
+        }
+
+        let (args, header, config) = match &reloaded {
+            Some((args, header, config)) => (args, header, config.as_deref()),
+            None => (args, header, config),
+        };
+
+        if shared_changed {
+            shared = shared_files(args, config);
+            shared_modified = modification_times(&shared);
+        }
+
+        // problems found while scanning are only reported once
+        let filenames = if first_scan {
+            args.build.input.files_to_process(output)
+        } else {
+            let mut silent = TerminalOutput::new(termcolor::ColorChoice::Never, Verbosity::Silent);
+            args.build.input.files_to_process(&mut silent)
+        };
+        first_scan = false;
+
+        let mut changed: Vec<PathBuf> = filenames
+            .iter()
+            .filter(|f| match modified.get(*f) {
+                Some(time) => shared_changed || *time != modification_time(f),
+                None => true,
+            })
+            .cloned()
+            .collect();
+
+        modified.retain(|f, _| filenames.contains(f));
+
+        if !changed.is_empty() {
+            for f in &changed {
+                modified.insert(f.clone(), modification_time(f));
+            }
+
+            // stable sort, so that the files keep their order otherwise
+            changed.sort_by_key(|f| !with_problems.contains(f));
+
+            // an amalgamated file is always generated from every file
+            let inputs = match args.build.amalgamate {
+                Some(_) => &filenames,
+                None => &changed,
+            };
+
+            // a manual run writing the same files is waited for
+            let lock = match run_lock::acquire(&args.build.lock_dir(), true, output) {
+                Ok(lock) => Some(lock),
+                Err(e) => {
+                    output.writeln_error(e);
+                    None
+                }
+            };
+
+            let project = Project::new(&args.build, &filenames);
+            let problems = crate::build_files(&args.build, &project, inputs, header, output);
+            drop(lock);
+
+            with_problems.retain(|f| !inputs.contains(f));
+            with_problems.extend(problems);
+            if let Some(path) = &args.state {
+                save_state(path, &with_problems, output);
+            }
+
+            output.writeln_info(Message::WaitingForChanges);
+        }
+
+        if interrupt::requested() {
+            return;
+        }
+        thread::sleep(Duration::from_millis(args.interval));
+    }
+}
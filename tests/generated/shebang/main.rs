@@ -0,0 +1,6 @@
+#!/usr/bin/env run-cargo-script
+// @generated by xr v0.1.0 — do not edit
+// generated on 2024-01-01 00:00:00 UTC
+fn main() {
+    println!(😀"shebang"😀);
+}
@@ -0,0 +1,68 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+use std::{fs, path::Path, process::Command};
+
+// Checks a file with an unused suppression, which is a W0001 warning, under the given [lints]
+// table of xr.toml, returning whether the run succeeded and what it printed.
+fn check(case: &str, lints: &str) -> (bool, String) {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR"))
+        .join("lint_levels")
+        .join(case);
+    fs::remove_dir_all(&dir).ok();
+    fs::create_dir_all(&dir).expect("directory is created");
+    fs::write(dir.join("a.xr"), "// xr-ignore-next-line\nx\n").expect("XR file is written");
+    fs::write(dir.join("xr.toml"), format!("[lints]\n{lints}\n")).expect("xr.toml is written");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_xr"))
+        .args(["--lang", "en", "check", "a.xr"])
+        .current_dir(&dir)
+        .output()
+        .expect("xr runs");
+    let printed = String::from_utf8_lossy(&output.stdout).into_owned()
+        + &String::from_utf8_lossy(&output.stderr);
+
+    (output.status.success(), printed)
+}
+
+#[test]
+fn denied_warning_is_an_error() {
+    let (success, printed) = check("deny", "W0001 = \"deny\"");
+
+    assert!(!success, "{printed}");
+    assert!(printed.contains("Error[W0001]"), "{printed}");
+}
+
+#[test]
+fn allowed_code_is_not_reported() {
+    let (success, printed) = check("allow", "w1 = \"allow\"");
+
+    assert!(success, "{printed}");
+    assert!(!printed.contains("W0001"), "{printed}");
+}
+
+#[test]
+fn unknown_code_is_rejected() {
+    let (success, printed) = check("unknown", "W9999 = \"allow\"");
+
+    assert!(!success, "{printed}");
+    assert!(printed.contains("Unknown error code 'W9999'"), "{printed}");
+}
@@ -0,0 +1,97 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use std::{fs, path::Path, process::Command};
+
+// Builds the files of a directory twice with the given options, adding a file in between, whose
+// module must be declared by the mod.rs written by the first build.
+fn rebuild(case: &str, options: &[&str]) {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR"))
+        .join("mod_files")
+        .join(case);
+    fs::remove_dir_all(&dir).ok();
+    fs::create_dir_all(&dir).expect("directory is created");
+    fs::write(dir.join("header.txt"), "// custom header\n").expect("header is written");
+    fs::write(dir.join("a.xr"), "// a\n").expect("XR file is written");
+
+    let build = || {
+        let output = Command::new(env!("CARGO_BIN_EXE_xr"))
+            .args(["--lang", "en", "build", "--mod-files"])
+            .args(options)
+            .current_dir(&dir)
+            .output()
+            .expect("xr runs");
+        let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+
+        assert!(output.status.success(), "{case}: {stderr}");
+        assert!(!stderr.contains("Warning"), "{case}: {stderr}");
+    };
+
+    build();
+    fs::write(dir.join("b.xr"), "// b\n").expect("XR file is written");
+    build();
+
+    let mod_file = fs::read_to_string(dir.join("mod.rs")).expect("mod.rs is written");
+    assert!(
+        mod_file.contains("pub mod a;\npub mod b;\n"),
+        "{case}: {mod_file}"
+    );
+}
+
+#[test]
+fn mod_file_with_banner() {
+    rebuild("banner", &[]);
+}
+
+#[test]
+fn mod_file_without_header() {
+    rebuild("no_header", &["--no-header"]);
+}
+
+#[test]
+fn mod_file_with_custom_header() {
+    rebuild("custom_header", &["--header-file", "header.txt"]);
+}
+
+#[test]
+fn hand_written_mod_file_is_kept() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR"))
+        .join("mod_files")
+        .join("hand_written");
+    fs::remove_dir_all(&dir).ok();
+    fs::create_dir_all(&dir).expect("directory is created");
+    fs::write(dir.join("a.xr"), "// a\n").expect("XR file is written");
+    fs::write(dir.join("mod.rs"), "mod mine;\n").expect("mod.rs is written");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_xr"))
+        .args(["--lang", "en", "build", "--mod-files"])
+        .current_dir(&dir)
+        .output()
+        .expect("xr runs");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(stderr.contains("was not generated by xr"), "{stderr}");
+    assert_eq!(
+        fs::read_to_string(dir.join("mod.rs")).expect("mod.rs is read"),
+        "mod mine;\n"
+    );
+}
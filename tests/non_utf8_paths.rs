@@ -0,0 +1,51 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+#![cfg(unix)]
+
+use std::{ffi::OsStr, fs, os::unix::ffi::OsStrExt, path::Path, process::Command};
+
+// The name of the XR file is not valid UTF-8 and its path is longer than the paths Windows opens
+// without the verbatim prefix, so both the reading and the writing go through long_paths.
+#[test]
+fn long_non_utf8_path() {
+    let root = Path::new(env!("CARGO_TARGET_TMPDIR")).join("non_utf8_paths");
+    fs::remove_dir_all(&root).ok();
+
+    let dir = (0..6).fold(root.clone(), |dir, _| dir.join("d".repeat(50)));
+    fs::create_dir_all(&dir).expect("directory is created");
+
+    let input = dir.join(OsStr::from_bytes(b"\xffinput.xr"));
+    fs::write(&input, "// comment\n").expect("XR file is written");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_xr"))
+        .args(["--lang", "en", "--no-truncate", "build"])
+        .arg(&input)
+        .current_dir(&root)
+        .output()
+        .expect("xr runs");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(output.status.success(), "{stdout}");
+    assert!(input.with_extension("rs").exists(), "{stdout}");
+    assert!(stdout.contains("\u{fffd}input.xr"), "{stdout}");
+}
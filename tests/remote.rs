@@ -0,0 +1,68 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+#![cfg(feature = "remote")]
+
+use std::{fs, path::Path, process::Command};
+
+// Nothing listens on the port, so the request must fail to connect, which only happens once the
+// https scheme is accepted by the HTTP client.
+const UNREACHABLE: &str = "https://127.0.0.1:1";
+
+fn xr(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_xr"))
+        .args(["--lang", "en"])
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .expect("xr runs");
+
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+fn assert_connection_failed(stderr: &str) {
+    assert!(!stderr.contains("Unknown Scheme"), "{stderr}");
+    assert!(stderr.contains("Connection Failed"), "{stderr}");
+}
+
+#[test]
+fn https_input() {
+    let url = format!("{UNREACHABLE}/main.xr");
+    let stderr = xr(
+        Path::new(env!("CARGO_TARGET_TMPDIR")),
+        &["check", "--allow-remote", &url],
+    );
+
+    assert_connection_failed(&stderr);
+}
+
+#[test]
+fn https_remote_cache() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("remote_cache");
+    fs::remove_dir_all(&dir).ok();
+    fs::create_dir_all(&dir).expect("directory is created");
+    fs::write(dir.join("main.xr"), "// comment\n").expect("XR file is written");
+
+    let stderr = xr(&dir, &["build", "--cache-url", UNREACHABLE, "main.xr"]);
+
+    assert_connection_failed(&stderr);
+}
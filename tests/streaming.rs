@@ -0,0 +1,51 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+use std::{fs, path::Path, process::Command};
+
+// The files read by the workers are checked in the order in which the scan found them, and a file
+// given explicitly that was already found is checked only once.
+#[test]
+fn streamed_files_are_checked_in_order() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("streaming");
+    fs::remove_dir_all(&dir).ok();
+    fs::create_dir_all(&dir).expect("directory is created");
+    for i in 0..500 {
+        fs::write(dir.join(format!("f{i:03}.xr")), "// file\n").expect("XR file is written");
+    }
+
+    let output = Command::new(env!("CARGO_BIN_EXE_xr"))
+        .args(["--lang", "en", "check", "-d", "."])
+        .arg(dir.join("f000.xr"))
+        .current_dir(&dir)
+        .output()
+        .expect("xr runs");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(output.status.success(), "{stdout}");
+    let processed: Vec<_> = stdout
+        .lines()
+        .filter(|line| line.starts_with("Processing file"))
+        .collect();
+    assert_eq!(processed.len(), 500, "{stdout}");
+    assert!(processed.windows(2).all(|w| w[0] < w[1]), "{stdout}");
+    assert!(stdout.contains("500 file(s) checked"), "{stdout}");
+}
@@ -0,0 +1,59 @@
+/*
+    Copyright 2023 Noel Lopes
+
+    Permission is hereby granted, free of charge, to any person obtaining a
+    copy of this software and associated documentation files (the "Software"),
+    to deal in the Software without restriction, including without limitation
+    the rights to use, copy, modify, merge, publish, distribute, sublicense,
+    and/or sell copies of the Software, and to permit persons to whom the
+    Software is furnished to do so, subject to the following conditions:
+
+    The above copyright notice and this permission notice shall be included in
+    all copies or substantial portions of the Software.
+
+    THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+    IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+    FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+    AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+    LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+    FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+    DEALINGS IN THE SOFTWARE.
+*/
+
+use std::{path::Path, process::Command};
+
+// Each directory of tests/generated holds an XR file, the configuration used to generate it and
+// the file generated by an earlier run, whose timestamp differs from the one of a new run.
+fn verify(case: &str) {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/generated")
+        .join(case);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_xr"))
+        .args(["--lang", "en", "build", "--verify-generated", "main.xr"])
+        .current_dir(&dir)
+        .output()
+        .expect("xr runs");
+
+    assert!(
+        output.status.success(),
+        "{case}: {}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn timestamp_after_byte_order_mark() {
+    verify("bom");
+}
+
+#[test]
+fn timestamp_after_block_comment_license() {
+    verify("block_license");
+}
+
+#[test]
+fn timestamp_after_shebang() {
+    verify("shebang");
+}